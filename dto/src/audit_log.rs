@@ -0,0 +1,119 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The actions on personal data that are recorded for accountability.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AuditAction {
+    CsvCheck,
+    UdaCheck,
+    LookUp,
+    Notification,
+    UdaConfirmation,
+    /// Personal data erased once the retention period is over.
+    PersonalDataPurge,
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "CsvCheck" => Ok(AuditAction::CsvCheck),
+            "UdaCheck" => Ok(AuditAction::UdaCheck),
+            "LookUp" => Ok(AuditAction::LookUp),
+            "Notification" => Ok(AuditAction::Notification),
+            "UdaConfirmation" => Ok(AuditAction::UdaConfirmation),
+            "PersonalDataPurge" => Ok(AuditAction::PersonalDataPurge),
+            _ => Err(format!("Unknown audit action: {value}")),
+        }
+    }
+}
+
+/// Who did what and when.
+/// The count is the number of members the action was about,
+/// while the targets identify those members: membership numbers or UDA ids.
+/// Notified members are only counted, so that their email addresses aren't kept.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone)]
+pub struct AuditLogEntry {
+    date: NaiveDateTime,
+    actor: String,
+    action: AuditAction,
+    count: usize,
+    targets: Vec<String>,
+}
+
+impl AuditLogEntry {
+    pub fn new(
+        date: NaiveDateTime,
+        actor: String,
+        action: AuditAction,
+        count: usize,
+        targets: Vec<String>,
+    ) -> Self {
+        Self {
+            date,
+            actor,
+            action,
+            count,
+            targets,
+        }
+    }
+}
+
+/// Criteria to browse the audit log. Missing criteria don't filter anything.
+/// Dates are inclusive.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone, Default)]
+pub struct AuditLogFilter {
+    actor: Option<String>,
+    action: Option<AuditAction>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl AuditLogFilter {
+    pub fn new(
+        actor: Option<String>,
+        action: Option<AuditAction>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Self {
+        Self {
+            actor,
+            action,
+            from,
+            to,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod audit_action {
+        use crate::audit_log::AuditAction;
+        use parameterized::{ide, parameterized};
+        use std::str::FromStr;
+
+        ide!();
+
+        #[parameterized(
+            action = {AuditAction::CsvCheck, AuditAction::UdaCheck, AuditAction::LookUp, AuditAction::Notification, AuditAction::UdaConfirmation, AuditAction::PersonalDataPurge},
+        )]
+        fn round_trip(action: AuditAction) {
+            assert_eq!(Ok(action), AuditAction::from_str(&action.to_string()));
+        }
+
+        #[test]
+        fn unknown() {
+            assert!(AuditAction::from_str("Delete").is_err());
+        }
+    }
+}
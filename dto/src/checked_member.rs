@@ -1,7 +1,8 @@
+use crate::match_explanation::MatchExplanation;
 use crate::member_to_check::MemberToCheck;
 use crate::membership::Membership;
-use crate::membership_status::MemberStatus::Unknown;
-use crate::membership_status::{MemberStatus, compute_member_status};
+use crate::membership_status::MemberStatus::{Unknown, UpToDate};
+use crate::membership_status::{EventPeriod, MemberStatus, compute_member_status};
 use derive_getters::Getters;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -10,12 +11,56 @@ use std::cmp::Ordering;
 pub enum CheckResult {
     Match(Membership),
     PartialMatch(Membership),
+    /// A membership whose names look like the member's ones, without being equal.
+    /// It comes along with a confidence, in percent, and should be confirmed by the checker.
+    FuzzyMatch(Membership, u8),
+    /// Several people match the member's names, and nothing tells them apart.
+    /// The most recent membership of each of them is a candidate, and the checker has to pick one.
+    Ambiguous(Vec<Membership>),
     NoMatch,
 }
 
+impl CheckResult {
+    /// The membership that has been found, if any.
+    pub fn membership(&self) -> Option<&Membership> {
+        match self {
+            CheckResult::Match(membership)
+            | CheckResult::PartialMatch(membership)
+            | CheckResult::FuzzyMatch(membership, _) => Some(membership),
+            CheckResult::Ambiguous(_) | CheckResult::NoMatch => None,
+        }
+    }
+
+    /// The same result, with another membership of the same member.
+    /// Results without a membership are left as is.
+    pub fn with_membership(self, membership: Membership) -> Self {
+        match self {
+            CheckResult::Match(_) => CheckResult::Match(membership),
+            CheckResult::PartialMatch(_) => CheckResult::PartialMatch(membership),
+            CheckResult::FuzzyMatch(_, confidence) => {
+                CheckResult::FuzzyMatch(membership, confidence)
+            }
+            CheckResult::Ambiguous(_) | CheckResult::NoMatch => self,
+        }
+    }
+
+    /// The higher, the better the match.
+    fn level(&self) -> u8 {
+        match self {
+            CheckResult::Match(_) => 4,
+            CheckResult::PartialMatch(_) => 3,
+            CheckResult::Ambiguous(_) => 2,
+            CheckResult::FuzzyMatch(_, _) => 1,
+            CheckResult::NoMatch => 0,
+        }
+    }
+}
+
 /// Ordering is based on whether there are match.
-/// Put simply, Match is greater than Partial Match, which in turn is greater than NoMatch.
-/// If both self & other have the same level, then it is based on the memberships themselves.
+/// Put simply, Match is greater than Partial Match, which is greater than Ambiguous,
+/// then Fuzzy Match, which in turn is greater than NoMatch.
+/// If both self & other have the same level, then it is based on the confidence (for fuzzy matches)
+/// and on the memberships themselves.
 impl PartialOrd for CheckResult {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -23,26 +68,28 @@ impl PartialOrd for CheckResult {
 }
 
 /// Ordering is based on whether there are match.
-/// Put simply, Match is greater than Partial Match, which in turn is greater than NoMatch.
-/// If both self & other have the same level, then it is based on the memberships themselves.
+/// Put simply, Match is greater than Partial Match, which is greater than Ambiguous,
+/// then Fuzzy Match, which in turn is greater than NoMatch.
+/// If both self & other have the same level, then it is based on the confidence (for fuzzy matches)
+/// and on the memberships themselves.
 impl Ord for CheckResult {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self {
-            CheckResult::Match(self_membership) => match other {
-                CheckResult::Match(other_membership) => self_membership.cmp(other_membership),
-                _ => Ordering::Greater,
-            },
-            CheckResult::PartialMatch(self_membership) => match other {
-                CheckResult::Match(_) => Ordering::Less,
-                CheckResult::PartialMatch(other_membership) => {
-                    self_membership.cmp(other_membership)
-                }
-                CheckResult::NoMatch => Ordering::Greater,
-            },
-            CheckResult::NoMatch => match other {
-                CheckResult::NoMatch => Ordering::Equal,
-                _ => Ordering::Less,
-            },
+        match (self, other) {
+            (CheckResult::Match(self_membership), CheckResult::Match(other_membership))
+            | (
+                CheckResult::PartialMatch(self_membership),
+                CheckResult::PartialMatch(other_membership),
+            ) => self_membership.cmp(other_membership),
+            (
+                CheckResult::FuzzyMatch(self_membership, self_confidence),
+                CheckResult::FuzzyMatch(other_membership, other_confidence),
+            ) => self_confidence
+                .cmp(other_confidence)
+                .then_with(|| self_membership.cmp(other_membership)),
+            (CheckResult::Ambiguous(self_candidates), CheckResult::Ambiguous(other_candidates)) => {
+                self_candidates.cmp(other_candidates)
+            }
+            _ => self.level().cmp(&other.level()),
         }
     }
 }
@@ -53,24 +100,58 @@ impl Ord for CheckResult {
 pub struct CheckedMember<T: MemberToCheck> {
     member_to_check: T,
     membership: CheckResult,
+    /// The event the member has been checked for, if any.
+    #[serde(default)]
+    event_period: Option<EventPeriod>,
+    /// The member's memberships which together cover the event, from the earliest.
+    /// Empty if there is no event or if they don't cover it.
+    #[serde(default)]
+    event_memberships: Vec<Membership>,
+    #[serde(default)]
+    explanation: Option<MatchExplanation>,
 }
 
 impl<T: MemberToCheck> CheckedMember<T> {
-    pub fn new(member_to_check: T, membership: CheckResult) -> Self {
+    pub fn new(
+        member_to_check: T,
+        membership: CheckResult,
+        event_period: Option<EventPeriod>,
+        event_memberships: Vec<Membership>,
+        explanation: Option<MatchExplanation>,
+    ) -> Self {
         Self {
             member_to_check,
             membership,
+            event_period,
+            event_memberships,
+            explanation,
         }
     }
 
+    /// A fuzzy match has to be confirmed by the checker before the membership status can be trusted.
     pub fn compute_member_status(&self) -> MemberStatus {
         match &self.membership {
+            CheckResult::Ambiguous(_) => MemberStatus::Ambiguous,
             CheckResult::NoMatch => Unknown,
-            CheckResult::Match(membership) | CheckResult::PartialMatch(membership) => {
-                compute_member_status(Some(membership))
+            CheckResult::FuzzyMatch(_, _) => MemberStatus::FuzzyMatch,
+            CheckResult::Match(_) | CheckResult::PartialMatch(_) => {
+                self.compute_membership_status()
             }
         }
     }
+
+    /// The status of the membership found, even if the checker still has to confirm it.
+    /// A member who renewed their membership during the event is up-to-date
+    /// as long as their successive memberships cover the whole event.
+    pub fn compute_membership_status(&self) -> MemberStatus {
+        match self.membership.membership() {
+            None => Unknown,
+            Some(_) if self.event_period.is_some() && !self.event_memberships.is_empty() => {
+                UpToDate
+            }
+            Some(membership) => compute_member_status(Some(membership), self.event_period.as_ref()),
+        }
+    }
 }
 
 impl<T: MemberToCheck> PartialOrd for CheckedMember<T> {
@@ -96,6 +177,7 @@ mod tests {
             None,
             Some("".to_owned()),
             Some("".to_owned()),
+            None,
         )
     }
 
@@ -105,6 +187,7 @@ mod tests {
             None,
             Some("".to_owned()),
             Some("".to_owned()),
+            None,
         )
     }
 
@@ -120,6 +203,8 @@ mod tests {
             Default::default(),
             "".to_owned(),
             "".to_owned(),
+            None,
+            None,
         )
     }
 
@@ -135,12 +220,16 @@ mod tests {
             Default::default(),
             "".to_owned(),
             "".to_owned(),
+            None,
+            None,
         )
     }
     // endregion
 
     mod check_result_cmp {
-        use crate::checked_member::CheckResult::{Match, NoMatch, PartialMatch};
+        use crate::checked_member::CheckResult::{
+            Ambiguous, FuzzyMatch, Match, NoMatch, PartialMatch,
+        };
         use crate::checked_member::tests::{get_membership_1, get_membership_2};
         use std::cmp::Ordering;
 
@@ -211,6 +300,47 @@ mod tests {
             assert_eq!(Ordering::Less, NoMatch.cmp(&PartialMatch(membership)));
         }
 
+        #[test]
+        fn partial_match_fuzzy_match_is_greater() {
+            let membership_1 = get_membership_1();
+            let membership_2 = get_membership_2();
+            assert_eq!(
+                Ordering::Greater,
+                PartialMatch(membership_1).cmp(&FuzzyMatch(membership_2, 100))
+            );
+        }
+
+        #[test]
+        fn ambiguous_is_between_partial_match_and_fuzzy_match() {
+            let membership_1 = get_membership_1();
+            let membership_2 = get_membership_2();
+            let ambiguous = Ambiguous(vec![membership_1.clone(), membership_2.clone()]);
+            assert_eq!(
+                Ordering::Less,
+                ambiguous.cmp(&PartialMatch(membership_1.clone()))
+            );
+            assert_eq!(
+                Ordering::Greater,
+                ambiguous.cmp(&FuzzyMatch(membership_1, 100))
+            );
+        }
+
+        #[test]
+        fn fuzzy_match_no_match_is_greater() {
+            let membership = get_membership_1();
+            assert_eq!(Ordering::Greater, FuzzyMatch(membership, 80).cmp(&NoMatch));
+        }
+
+        #[test]
+        fn fuzzy_match_fuzzy_match_is_confidence_ord() {
+            let membership_1 = get_membership_1();
+            let membership_2 = get_membership_2();
+            assert_eq!(
+                Ordering::Less,
+                FuzzyMatch(membership_2, 80).cmp(&FuzzyMatch(membership_1, 90))
+            );
+        }
+
         #[test]
         fn match_match_same_membership_is_membership_ord() {
             let membership_1 = get_membership_1();
@@ -232,11 +362,37 @@ mod tests {
         }
     }
 
+    mod with_membership {
+        use crate::checked_member::CheckResult;
+        use crate::checked_member::tests::{get_membership_1, get_membership_2};
+
+        #[test]
+        fn keep_the_kind_of_result() {
+            assert_eq!(
+                CheckResult::PartialMatch(get_membership_2()),
+                CheckResult::PartialMatch(get_membership_1()).with_membership(get_membership_2())
+            );
+            assert_eq!(
+                CheckResult::FuzzyMatch(get_membership_2(), 90),
+                CheckResult::FuzzyMatch(get_membership_1(), 90).with_membership(get_membership_2())
+            );
+            assert_eq!(
+                CheckResult::NoMatch,
+                CheckResult::NoMatch.with_membership(get_membership_2())
+            );
+        }
+    }
+
     mod compute_member_status {
-        use crate::checked_member::tests::get_member_to_check_1;
+        use crate::checked_member::tests::{
+            get_member_to_check_1, get_membership_1, get_membership_2,
+        };
         use crate::checked_member::{CheckResult, CheckedMember};
         use crate::membership::Membership;
-        use crate::membership_status::MemberStatus::{Expired, Unknown, UpToDate};
+        use crate::membership_status::MemberStatus::{
+            Expired, NotCoveringEvent, Unknown, UpToDate,
+        };
+        use crate::membership_status::{EventPeriod, MemberStatus};
         use chrono::{Days, Months, Utc};
 
         #[test]
@@ -258,10 +414,17 @@ mod tests {
                     .unwrap(),
                 "".to_owned(),
                 "".to_owned(),
+                None,
+                None,
             );
 
-            let checked_member =
-                CheckedMember::new(get_member_to_check_1(), CheckResult::Match(membership));
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::Match(membership),
+                None,
+                vec![],
+                None,
+            );
             assert_eq!(UpToDate, checked_member.compute_member_status());
         }
 
@@ -284,16 +447,156 @@ mod tests {
                     .unwrap(),
                 "".to_owned(),
                 "".to_owned(),
+                None,
+                None,
             );
 
-            let checked_member =
-                CheckedMember::new(get_member_to_check_1(), CheckResult::Match(membership));
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::Match(membership),
+                None,
+                vec![],
+                None,
+            );
             assert_eq!(Expired, checked_member.compute_member_status());
         }
 
+        #[test]
+        fn should_not_cover_event() {
+            let today = Utc::now().date_naive();
+            let membership = Membership::new(
+                "1".to_owned(),
+                "".to_owned(),
+                None,
+                "".to_owned(),
+                None,
+                "".to_owned(),
+                today.checked_sub_months(Months::new(12)).unwrap(),
+                today.checked_add_days(Days::new(10)).unwrap(),
+                "".to_owned(),
+                "".to_owned(),
+                None,
+                None,
+            );
+            // The membership is still valid today, but it expires during the event
+            let event_period = EventPeriod::new(
+                today.checked_add_days(Days::new(9)).unwrap(),
+                today.checked_add_days(Days::new(11)).unwrap(),
+            );
+
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::Match(membership),
+                Some(event_period),
+                vec![],
+                None,
+            );
+            assert_eq!(NotCoveringEvent, checked_member.compute_member_status());
+        }
+
+        #[test]
+        fn should_be_up_to_date_when_successive_memberships_cover_event() {
+            let today = Utc::now().date_naive();
+            let membership = |start_date, end_date| {
+                Membership::new(
+                    "1".to_owned(),
+                    "".to_owned(),
+                    None,
+                    "".to_owned(),
+                    None,
+                    "".to_owned(),
+                    start_date,
+                    end_date,
+                    "".to_owned(),
+                    "".to_owned(),
+                    None,
+                    None,
+                )
+            };
+            let previous_membership = membership(
+                today.checked_sub_months(Months::new(12)).unwrap(),
+                today.checked_add_days(Days::new(10)).unwrap(),
+            );
+            let renewed_membership = membership(
+                today.checked_add_days(Days::new(11)).unwrap(),
+                today.checked_add_months(Months::new(12)).unwrap(),
+            );
+            let event_period = EventPeriod::new(
+                today.checked_add_days(Days::new(9)).unwrap(),
+                today.checked_add_days(Days::new(11)).unwrap(),
+            );
+
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::Match(renewed_membership.clone()),
+                Some(event_period),
+                vec![previous_membership, renewed_membership],
+                None,
+            );
+            assert_eq!(UpToDate, checked_member.compute_member_status());
+        }
+
+        #[test]
+        fn should_be_fuzzy_match() {
+            let membership = Membership::new(
+                "1".to_owned(),
+                "".to_owned(),
+                None,
+                "".to_owned(),
+                None,
+                "".to_owned(),
+                Utc::now()
+                    .date_naive()
+                    .checked_sub_months(Months::new(12))
+                    .unwrap(),
+                Utc::now()
+                    .date_naive()
+                    .checked_add_months(Months::new(12))
+                    .unwrap(),
+                "".to_owned(),
+                "".to_owned(),
+                None,
+                None,
+            );
+
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::FuzzyMatch(membership, 90),
+                None,
+                vec![],
+                None,
+            );
+            assert_eq!(
+                MemberStatus::FuzzyMatch,
+                checked_member.compute_member_status()
+            );
+            assert_eq!(UpToDate, checked_member.compute_membership_status());
+        }
+
+        #[test]
+        fn should_be_ambiguous() {
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::Ambiguous(vec![get_membership_1(), get_membership_2()]),
+                None,
+                vec![],
+                None,
+            );
+            assert_eq!(
+                MemberStatus::Ambiguous,
+                checked_member.compute_member_status()
+            );
+        }
+
         #[test]
         fn should_be_unknown() {
-            let checked_member = CheckedMember::new(get_member_to_check_1(), CheckResult::NoMatch);
+            let checked_member = CheckedMember::new(
+                get_member_to_check_1(),
+                CheckResult::NoMatch,
+                None,
+                vec![],
+                None,
+            );
             assert_eq!(Unknown, checked_member.compute_member_status());
         }
     }
@@ -313,10 +616,20 @@ mod tests {
             let member_to_check_1 = get_member_to_check_1();
             let member_to_check_2 = get_member_to_check_2();
 
-            let checked_member_1 =
-                CheckedMember::new(member_to_check_1, CheckResult::Match(membership_1));
-            let checked_member_2 =
-                CheckedMember::new(member_to_check_2, CheckResult::Match(membership_2));
+            let checked_member_1 = CheckedMember::new(
+                member_to_check_1,
+                CheckResult::Match(membership_1),
+                None,
+                vec![],
+                None,
+            );
+            let checked_member_2 = CheckedMember::new(
+                member_to_check_2,
+                CheckResult::Match(membership_2),
+                None,
+                vec![],
+                None,
+            );
             assert_eq!(Some(Less), checked_member_1.partial_cmp(&checked_member_2))
         }
 
@@ -327,9 +640,15 @@ mod tests {
             let member_to_check_1 = get_member_to_check_1();
             let member_to_check_2 = get_member_to_check_2();
 
-            let checked_member_1 =
-                CheckedMember::new(member_to_check_1, CheckResult::Match(membership_1));
-            let checked_member_2 = CheckedMember::new(member_to_check_2, CheckResult::NoMatch);
+            let checked_member_1 = CheckedMember::new(
+                member_to_check_1,
+                CheckResult::Match(membership_1),
+                None,
+                vec![],
+                None,
+            );
+            let checked_member_2 =
+                CheckedMember::new(member_to_check_2, CheckResult::NoMatch, None, vec![], None);
             assert_eq!(
                 Some(Greater),
                 checked_member_1.partial_cmp(&checked_member_2)
@@ -343,9 +662,15 @@ mod tests {
             let member_to_check_1 = get_member_to_check_1();
             let member_to_check_2 = get_member_to_check_2();
 
-            let checked_member_1 = CheckedMember::new(member_to_check_1, CheckResult::NoMatch);
-            let checked_member_2 =
-                CheckedMember::new(member_to_check_2, CheckResult::Match(membership_2));
+            let checked_member_1 =
+                CheckedMember::new(member_to_check_1, CheckResult::NoMatch, None, vec![], None);
+            let checked_member_2 = CheckedMember::new(
+                member_to_check_2,
+                CheckResult::Match(membership_2),
+                None,
+                vec![],
+                None,
+            );
             assert_eq!(Some(Less), checked_member_1.partial_cmp(&checked_member_2))
         }
     }
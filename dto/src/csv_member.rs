@@ -1,4 +1,5 @@
 use crate::member_to_check::MemberToCheck;
+use chrono::NaiveDate;
 use derive_getters::Getters;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -12,6 +13,8 @@ pub struct CsvMember {
     identity: Option<String>,
     name: Option<String>,
     first_name: Option<String>,
+    #[serde(default)]
+    birthdate: Option<NaiveDate>,
 }
 
 impl CsvMember {
@@ -20,12 +23,14 @@ impl CsvMember {
         identity: Option<String>,
         name: Option<String>,
         first_name: Option<String>,
+        birthdate: Option<NaiveDate>,
     ) -> Self {
         Self {
             membership_num,
             identity,
             name,
             first_name,
+            birthdate,
         }
     }
 }
@@ -51,6 +56,10 @@ impl MemberToCheck for CsvMember {
         self.name.clone()
     }
 
+    fn birthdate(&self) -> Option<NaiveDate> {
+        self.birthdate
+    }
+
     fn email(&self) -> Option<String> {
         None
     }
@@ -76,8 +85,11 @@ impl Ord for CsvMember {
             self.name.cmp(other.name())
         } else if self.first_name != other.first_name {
             self.first_name().cmp(other.first_name())
-        } else {
+        } else if self.membership_num != other.membership_num {
             self.membership_num.cmp(&other.membership_num)
+        } else {
+            // Namesakes may only differ by their birthdate
+            self.birthdate.cmp(&other.birthdate)
         }
     }
 }
@@ -86,6 +98,7 @@ impl Ord for CsvMember {
 mod tests {
     use crate::csv_member::CsvMember;
     use crate::member_to_check::MemberToCheck;
+    use chrono::NaiveDate;
 
     fn get_membership_number() -> String {
         "0123456789".to_owned()
@@ -99,6 +112,9 @@ mod tests {
     fn get_last_name() -> String {
         "Snow".to_owned()
     }
+    fn get_birthdate() -> NaiveDate {
+        NaiveDate::from_ymd_opt(1990, 11, 5).unwrap()
+    }
 
     fn get_csv_member() -> CsvMember {
         CsvMember::new(
@@ -106,6 +122,7 @@ mod tests {
             Some(get_identity()),
             Some(get_last_name()),
             Some(get_first_name()),
+            Some(get_birthdate()),
         )
     }
 
@@ -142,6 +159,12 @@ mod tests {
         assert_eq!(Some(get_last_name()), MemberToCheck::last_name(&member));
     }
 
+    #[test]
+    fn should_get_birthdate() {
+        let member = get_csv_member();
+        assert_eq!(Some(get_birthdate()), MemberToCheck::birthdate(&member));
+    }
+
     #[test]
     fn should_get_email() {
         let member = get_csv_member();
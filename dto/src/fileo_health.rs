@@ -0,0 +1,64 @@
+use chrono::NaiveDateTime;
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The steps of the flow used to retrieve the memberships from Fileo.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum FileoFlowStep {
+    Login,
+    Filter,
+    ExportLink,
+    Download,
+    CsvHeader,
+}
+
+impl Display for FileoFlowStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The result of a run of the Fileo flow.
+/// When a step broke, the report tells which one and why,
+/// along with a sanitized snippet of the unexpected content.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone)]
+pub struct FileoHealthReport {
+    date: NaiveDateTime,
+    failed_step: Option<FileoFlowStep>,
+    reason: Option<String>,
+    snippet: Option<String>,
+}
+
+impl FileoHealthReport {
+    pub fn new(
+        date: NaiveDateTime,
+        failed_step: Option<FileoFlowStep>,
+        reason: Option<String>,
+        snippet: Option<String>,
+    ) -> Self {
+        Self {
+            date,
+            failed_step,
+            reason,
+            snippet,
+        }
+    }
+
+    pub fn healthy(date: NaiveDateTime) -> Self {
+        Self::new(date, None, None, None)
+    }
+
+    pub fn broken(
+        date: NaiveDateTime,
+        failed_step: FileoFlowStep,
+        reason: String,
+        snippet: Option<String>,
+    ) -> Self {
+        Self::new(date, Some(failed_step), Some(reason), snippet)
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.failed_step.is_none()
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::NaiveDateTime;
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The lists that are refreshed from remote services.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ImportedElement {
+    Memberships,
+    UdaInstances,
+}
+
+/// What triggered an import.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ImportSource {
+    /// The list has been retrieved from the remote service on a user's request.
+    Scraping,
+    /// The list has been uploaded by a user.
+    ManualUpload,
+    /// The list has been retrieved by the scheduled refresh.
+    Scheduler,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ImportOutcome {
+    Success,
+    /// The import has been refused as it looked suspicious.
+    Refused,
+    Failure,
+}
+
+impl Display for ImportedElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for ImportedElement {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Memberships" => Ok(ImportedElement::Memberships),
+            "UdaInstances" => Ok(ImportedElement::UdaInstances),
+            _ => Err(format!("Unknown imported element: {value}")),
+        }
+    }
+}
+
+impl Display for ImportSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for ImportSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Scraping" => Ok(ImportSource::Scraping),
+            "ManualUpload" => Ok(ImportSource::ManualUpload),
+            "Scheduler" => Ok(ImportSource::Scheduler),
+            _ => Err(format!("Unknown import source: {value}")),
+        }
+    }
+}
+
+impl Display for ImportOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for ImportOutcome {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Success" => Ok(ImportOutcome::Success),
+            "Refused" => Ok(ImportOutcome::Refused),
+            "Failure" => Ok(ImportOutcome::Failure),
+            _ => Err(format!("Unknown import outcome: {value}")),
+        }
+    }
+}
+
+/// How many rows an import has deleted, inserted, updated and rejected.
+/// As in the import diff, a membership only counts as updated when its email address,
+/// end date or club has changed.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ImportCounts {
+    deleted: usize,
+    inserted: usize,
+    updated: usize,
+    rejected: usize,
+}
+
+impl ImportCounts {
+    pub fn new(deleted: usize, inserted: usize, updated: usize, rejected: usize) -> Self {
+        Self {
+            deleted,
+            inserted,
+            updated,
+            rejected,
+        }
+    }
+}
+
+/// A refresh of a list, whatever its outcome.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone)]
+pub struct ImportHistoryEntry {
+    element: ImportedElement,
+    source: ImportSource,
+    actor: Option<String>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    duration_in_milliseconds: i64,
+    counts: ImportCounts,
+    outcome: ImportOutcome,
+    error: Option<String>,
+}
+
+impl ImportHistoryEntry {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        element: ImportedElement,
+        source: ImportSource,
+        actor: Option<String>,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        counts: ImportCounts,
+        outcome: ImportOutcome,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            element,
+            source,
+            actor,
+            start,
+            end,
+            duration_in_milliseconds: (end - start).num_milliseconds(),
+            counts,
+            outcome,
+            error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod import_history_entry {
+        use crate::import_history::{
+            ImportCounts, ImportHistoryEntry, ImportOutcome, ImportSource, ImportedElement,
+        };
+        use chrono::{NaiveDate, TimeDelta};
+
+        #[test]
+        fn compute_duration() {
+            let start = NaiveDate::from_ymd_opt(2025, 4, 30)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap();
+            let entry = ImportHistoryEntry::new(
+                ImportedElement::Memberships,
+                ImportSource::Scheduler,
+                None,
+                start,
+                start + TimeDelta::milliseconds(1500),
+                ImportCounts::default(),
+                ImportOutcome::Success,
+                None,
+            );
+
+            assert_eq!(&1500, entry.duration_in_milliseconds());
+        }
+    }
+
+    mod from_str {
+        use crate::import_history::{ImportOutcome, ImportSource, ImportedElement};
+        use std::str::FromStr;
+
+        #[test]
+        fn round_trip() {
+            for source in [
+                ImportSource::Scraping,
+                ImportSource::ManualUpload,
+                ImportSource::Scheduler,
+            ] {
+                assert_eq!(Ok(source), ImportSource::from_str(&source.to_string()));
+            }
+            assert_eq!(
+                Ok(ImportedElement::UdaInstances),
+                ImportedElement::from_str("UdaInstances")
+            );
+            assert_eq!(
+                Ok(ImportOutcome::Refused),
+                ImportOutcome::from_str("Refused")
+            );
+        }
+
+        #[test]
+        fn fail_when_unknown() {
+            assert!(ImportSource::from_str("Carrier pigeon").is_err());
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+/// Why a memberships import has been refused.
+/// Such an import can still be forced if it is legitimate.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone)]
+pub struct ImportRejection {
+    previous_count: usize,
+    new_count: usize,
+    max_drop_percentage: u8,
+}
+
+impl ImportRejection {
+    pub fn new(previous_count: usize, new_count: usize, max_drop_percentage: u8) -> Self {
+        Self {
+            previous_count,
+            new_count,
+            max_drop_percentage,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+/// A row of the memberships file that couldn't be imported.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Hash, Clone)]
+pub struct RejectedRow {
+    line: u64,
+    record: String,
+    reason: String,
+}
+
+impl RejectedRow {
+    pub fn new(line: u64, record: String, reason: String) -> Self {
+        Self {
+            line,
+            record,
+            reason,
+        }
+    }
+}
+
+/// What went wrong while reading a memberships file.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone, Default)]
+pub struct ImportReport {
+    rejected_rows: Vec<RejectedRow>,
+}
+
+impl ImportReport {
+    pub fn new(rejected_rows: Vec<RejectedRow>) -> Self {
+        Self { rejected_rows }
+    }
+}
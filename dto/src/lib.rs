@@ -1,9 +1,17 @@
+pub mod audit_log;
 pub mod checked_member;
 pub mod csv_member;
 pub mod email;
+pub mod fileo_health;
+pub mod import_history;
+pub mod import_rejection;
+pub mod import_report;
+pub mod match_explanation;
 pub mod member_to_check;
 pub mod member_to_look_up;
 pub mod membership;
+pub mod membership_candidate;
+pub mod membership_diff;
 pub mod membership_status;
 pub mod uda_instance;
 pub mod uda_member;
@@ -0,0 +1,54 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+/// The rule of the check which produced a result, in the order they are applied.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MatchingRule {
+    MembershipNumberAndNames,
+    MembershipNumberAndIdentity,
+    MembershipNumber,
+    /// The membership number is provided, but it doesn't match anything.
+    UnknownMembershipNumber,
+    Names,
+    Identity,
+    EmailAddress,
+    SimilarNames,
+    /// No rule has matched.
+    NoMatchingMembership,
+}
+
+/// The fields of a member which can be compared to the ones of a membership.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ComparedField {
+    MembershipNumber,
+    LastName,
+    FirstName,
+    Identity,
+    Birthdate,
+    EmailAddress,
+    MembershipType,
+}
+
+/// Why a member has been given a check result:
+/// the rule that matched, and the fields that agreed or disagreed with the membership found.
+/// Fields missing on either side are neither agreeing nor disagreeing.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone)]
+pub struct MatchExplanation {
+    rule: MatchingRule,
+    agreeing_fields: Vec<ComparedField>,
+    disagreeing_fields: Vec<ComparedField>,
+}
+
+impl MatchExplanation {
+    pub fn new(
+        rule: MatchingRule,
+        agreeing_fields: Vec<ComparedField>,
+        disagreeing_fields: Vec<ComparedField>,
+    ) -> Self {
+        Self {
+            rule,
+            agreeing_fields,
+            disagreeing_fields,
+        }
+    }
+}
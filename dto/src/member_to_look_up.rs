@@ -1,11 +1,31 @@
+use chrono::NaiveDate;
 use derive_getters::Getters;
 use serde::{Deserialize, Serialize};
 
+/// How the given information should be compared to the known memberships.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy)]
+pub enum LookUpMode {
+    /// Normalized fields should be equal.
+    #[default]
+    Exact,
+    /// Normalized fields should start with the given information, e.g. for partially typed names.
+    Prefix,
+    /// Normalized names should be similar to the given ones, so that typos are tolerated.
+    /// The membership number, if given, should be equal.
+    Fuzzy,
+}
+
 #[derive(Getters, Debug, Serialize, Deserialize)]
 pub struct MemberToLookUp {
     membership_num: Option<String>,
     last_name: Option<String>,
     first_name: Option<String>,
+    /// When set, only the memberships that were valid on this date are looked up,
+    /// including those that are no longer part of the current list.
+    #[serde(default)]
+    valid_on: Option<NaiveDate>,
+    #[serde(default)]
+    mode: LookUpMode,
 }
 
 impl MemberToLookUp {
@@ -13,11 +33,15 @@ impl MemberToLookUp {
         membership_num: Option<String>,
         last_name: Option<String>,
         first_name: Option<String>,
+        valid_on: Option<NaiveDate>,
+        mode: LookUpMode,
     ) -> Self {
         Self {
             membership_num,
             last_name,
             first_name,
+            valid_on,
+            mode,
         }
     }
 }
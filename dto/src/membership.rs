@@ -15,6 +15,8 @@ pub struct Membership {
     end_date: NaiveDate,
     club: String,
     structure_code: String,
+    membership_type: Option<String>,
+    tariff: Option<String>,
 }
 
 impl Membership {
@@ -30,6 +32,8 @@ impl Membership {
         end_date: NaiveDate,
         club: String,
         structure_code: String,
+        membership_type: Option<String>,
+        tariff: Option<String>,
     ) -> Self {
         Self {
             membership_number,
@@ -42,8 +46,28 @@ impl Membership {
             end_date,
             club,
             structure_code,
+            membership_type,
+            tariff,
         }
     }
+
+    /// The same membership, without its email address, cell number and birthdate.
+    pub fn without_personal_data(&self) -> Self {
+        Self {
+            birthdate: None,
+            cell_number: None,
+            email_address: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Email addresses are compared regardless of case and surrounding spaces.
+    /// An empty address never matches, as it is unknown.
+    pub fn has_email_address(&self, email_address: &str) -> bool {
+        let email_address = email_address.trim();
+        !email_address.is_empty()
+            && self.email_address.trim().to_lowercase() == email_address.to_lowercase()
+    }
 }
 
 impl PartialOrd for Membership {
@@ -83,12 +107,14 @@ pub mod tests {
                 end_date,
                 club: "".to_string(),
                 structure_code: "".to_string(),
+                membership_type: None,
+                tariff: None,
             }
         }
     }
 
-    const HEADER: &str = "Nom d'usage;Prénom;Date de Naissance;Numéro d'adhérent;Téléphone portable;Email;Date Début d'adhésion;Date Fin d'adhésion;Nom de structure;Code de structure";
-    const MEMBERSHIP_AS_CSV: &str = "Doe;Jon;01-02-1980;123456;+33 6 12 34 56 78;email@address.com;30-09-2024;30-09-2025;My club;Z01234";
+    const HEADER: &str = "Nom d'usage;Prénom;Date de Naissance;Numéro d'adhérent;Téléphone portable;Email;Date Début d'adhésion;Date Fin d'adhésion;Nom de structure;Code de structure;Type d'adhésion;Tarif";
+    const MEMBERSHIP_AS_CSV: &str = "Doe;Jon;01-02-1980;123456;+33 6 12 34 56 78;email@address.com;30-09-2024;30-09-2025;My club;Z01234;Compétition;Adulte";
     pub const MEMBER_NAME: &str = "Doe";
     pub const MEMBER_FIRST_NAME: &str = "Jon";
     pub const MEMBERSHIP_NUMBER: &str = "123456";
@@ -107,6 +133,8 @@ pub mod tests {
             end_date: NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
             club: "My club".to_string(),
             structure_code: "Z01234".to_string(),
+            membership_type: Some("Compétition".to_string()),
+            tariff: Some("Adulte".to_string()),
         }
     }
 
@@ -118,6 +146,15 @@ pub mod tests {
         format!("{HEADER}\n{MALFORMED_MEMBERSHIP_AS_CSV}")
     }
 
+    #[parameterized(
+        email_address = {"email@address.com", " Email@Address.COM ", "other@address.com", ""},
+        expected_result = {true, true, false, false},
+    )]
+    fn should_compare_email_addresses(email_address: &str, expected_result: bool) {
+        let membership = get_expected_membership();
+        assert_eq!(expected_result, membership.has_email_address(email_address));
+    }
+
     #[parameterized(
         end_dates = {
         ((2020, 10, 12), (2020, 11, 12)),
@@ -0,0 +1,17 @@
+use crate::membership::Membership;
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+/// A membership found by a prefix or a fuzzy lookup,
+/// along with a score going from 0 (nothing in common) to 1 (perfect match).
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Clone)]
+pub struct MembershipCandidate {
+    membership: Membership,
+    score: f64,
+}
+
+impl MembershipCandidate {
+    pub fn new(membership: Membership, score: f64) -> Self {
+        Self { membership, score }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::membership::Membership;
+use chrono::{NaiveDate, NaiveDateTime};
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MembershipChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Display for MembershipChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A change on a single membership, identified by its membership number and its start date.
+/// For an added membership, only the new values are set.
+/// For a removed membership, only the previous values are set.
+/// For a changed membership, both values are set for the fields that changed, and only for those.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Hash, Clone)]
+pub struct MembershipChange {
+    kind: MembershipChangeKind,
+    membership_number: String,
+    name: String,
+    first_name: String,
+    start_date: NaiveDate,
+    previous_email_address: Option<String>,
+    email_address: Option<String>,
+    previous_end_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    previous_club: Option<String>,
+    club: Option<String>,
+}
+
+impl MembershipChange {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        kind: MembershipChangeKind,
+        membership_number: String,
+        name: String,
+        first_name: String,
+        start_date: NaiveDate,
+        previous_email_address: Option<String>,
+        email_address: Option<String>,
+        previous_end_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+        previous_club: Option<String>,
+        club: Option<String>,
+    ) -> Self {
+        Self {
+            kind,
+            membership_number,
+            name,
+            first_name,
+            start_date,
+            previous_email_address,
+            email_address,
+            previous_end_date,
+            end_date,
+            previous_club,
+            club,
+        }
+    }
+
+    pub fn added(membership: &Membership) -> Self {
+        Self::new(
+            MembershipChangeKind::Added,
+            membership.membership_number().clone(),
+            membership.name().clone(),
+            membership.first_name().clone(),
+            *membership.start_date(),
+            None,
+            Some(membership.email_address().clone()),
+            None,
+            Some(*membership.end_date()),
+            None,
+            Some(membership.club().clone()),
+        )
+    }
+
+    pub fn removed(membership: &Membership) -> Self {
+        Self::new(
+            MembershipChangeKind::Removed,
+            membership.membership_number().clone(),
+            membership.name().clone(),
+            membership.first_name().clone(),
+            *membership.start_date(),
+            Some(membership.email_address().clone()),
+            None,
+            Some(*membership.end_date()),
+            None,
+            Some(membership.club().clone()),
+            None,
+        )
+    }
+
+    /// Compare the tracked fields (email address, end date and club) of two versions of the same membership.
+    /// Return None if none of them has changed.
+    pub fn between(previous: &Membership, current: &Membership) -> Option<Self> {
+        fn diff<T: PartialEq + Clone>(previous: &T, current: &T) -> (Option<T>, Option<T>) {
+            if previous == current {
+                (None, None)
+            } else {
+                (Some(previous.clone()), Some(current.clone()))
+            }
+        }
+
+        let (previous_email_address, email_address) =
+            diff(previous.email_address(), current.email_address());
+        let (previous_end_date, end_date) = diff(previous.end_date(), current.end_date());
+        let (previous_club, club) = diff(previous.club(), current.club());
+
+        if email_address.is_none() && end_date.is_none() && club.is_none() {
+            return None;
+        }
+
+        Some(Self::new(
+            MembershipChangeKind::Changed,
+            current.membership_number().clone(),
+            current.name().clone(),
+            current.first_name().clone(),
+            *current.start_date(),
+            previous_email_address,
+            email_address,
+            previous_end_date,
+            end_date,
+            previous_club,
+            club,
+        ))
+    }
+}
+
+/// All the changes brought by a memberships import.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone)]
+pub struct MembershipsDiff {
+    date: NaiveDateTime,
+    changes: Vec<MembershipChange>,
+}
+
+impl MembershipsDiff {
+    pub fn new(date: NaiveDateTime, changes: Vec<MembershipChange>) -> Self {
+        Self { date, changes }
+    }
+
+    pub fn count(&self, kind: MembershipChangeKind) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == kind)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod between {
+        use crate::membership::Membership;
+        use crate::membership::tests::get_expected_membership;
+        use crate::membership_diff::{MembershipChange, MembershipChangeKind};
+        use chrono::NaiveDate;
+
+        #[test]
+        fn none_when_identical() {
+            let membership = get_expected_membership();
+            assert_eq!(None, MembershipChange::between(&membership, &membership));
+        }
+
+        #[test]
+        fn none_when_untracked_field_changed() {
+            let previous = get_expected_membership();
+            let current = Membership::new(
+                previous.name().clone(),
+                previous.first_name().clone(),
+                None,
+                previous.membership_number().clone(),
+                None,
+                previous.email_address().clone(),
+                *previous.start_date(),
+                *previous.end_date(),
+                previous.club().clone(),
+                previous.structure_code().clone(),
+                None,
+                None,
+            );
+            assert_eq!(None, MembershipChange::between(&previous, &current));
+        }
+
+        #[test]
+        fn some_when_tracked_fields_changed() {
+            let previous = get_expected_membership();
+            let new_end_date = NaiveDate::from_ymd_opt(2026, 9, 30).unwrap();
+            let current = Membership::new(
+                previous.name().clone(),
+                previous.first_name().clone(),
+                *previous.birthdate(),
+                previous.membership_number().clone(),
+                previous.cell_number().clone(),
+                "new@address.com".to_owned(),
+                *previous.start_date(),
+                new_end_date,
+                previous.club().clone(),
+                previous.structure_code().clone(),
+                None,
+                None,
+            );
+
+            let expected_change = MembershipChange::new(
+                MembershipChangeKind::Changed,
+                previous.membership_number().clone(),
+                previous.name().clone(),
+                previous.first_name().clone(),
+                *previous.start_date(),
+                Some(previous.email_address().clone()),
+                Some("new@address.com".to_owned()),
+                Some(*previous.end_date()),
+                Some(new_end_date),
+                None,
+                None,
+            );
+            assert_eq!(
+                Some(expected_change),
+                MembershipChange::between(&previous, &current)
+            );
+        }
+    }
+}
@@ -1,23 +1,265 @@
 use crate::membership::Membership;
-use crate::membership_status::MemberStatus::{Expired, Unknown, UpToDate};
-use chrono::Utc;
+use crate::membership_status::MemberStatus::{Expired, NotCoveringEvent, Unknown, UpToDate};
+use chrono::{NaiveDate, Utc};
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum MemberStatus {
     UpToDate,
     Expired,
+    /// The membership starts after the beginning of the event, or expires during it.
+    NotCoveringEvent,
+    /// The membership has only been found by similar names, and the checker has to confirm it.
+    FuzzyMatch,
+    /// Several memberships may be the member's one, and the checker has to pick the right one.
+    Ambiguous,
     Unknown,
 }
 
-pub fn compute_member_status(membership: Option<&Membership>) -> MemberStatus {
-    match membership {
-        None => Unknown,
-        Some(membership) => {
+/// The dates of the event members are checked for. Both dates are included.
+#[derive(Debug, Serialize, Deserialize, Getters, PartialEq, Eq, Clone, Copy)]
+pub struct EventPeriod {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+}
+
+impl EventPeriod {
+    pub fn new(start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        Self {
+            start_date,
+            end_date,
+        }
+    }
+
+    /// Among the memberships of a member, the fewest ones which together cover every day of the event,
+    /// from the earliest. A single membership is enough when it covers the whole event.
+    /// Empty if they can't cover it, e.g. because of a gap between two of them.
+    pub fn select_covering_memberships<'a>(
+        &self,
+        memberships: &[&'a Membership],
+    ) -> Vec<&'a Membership> {
+        let mut covering_memberships = vec![];
+        let mut uncovered_from = self.start_date;
+        loop {
+            // The membership covering the first uncovered day the longest is the best pick
+            let Some(membership) = memberships
+                .iter()
+                .filter(|membership| {
+                    *membership.start_date() <= uncovered_from
+                        && uncovered_from <= *membership.end_date()
+                })
+                .max_by_key(|membership| membership.end_date())
+            else {
+                return vec![];
+            };
+            covering_memberships.push(*membership);
+            if self.end_date <= *membership.end_date() {
+                return covering_memberships;
+            }
+            match membership.end_date().succ_opt() {
+                Some(next_day) => uncovered_from = next_day,
+                None => return covering_memberships,
+            }
+        }
+    }
+}
+
+/// Without event, a membership is up-to-date as long as it hasn't ended yet.
+/// With an event, a membership is up-to-date only if it covers the whole event.
+pub fn compute_member_status(
+    membership: Option<&Membership>,
+    event_period: Option<&EventPeriod>,
+) -> MemberStatus {
+    match (membership, event_period) {
+        (None, _) => Unknown,
+        (Some(membership), None) => {
             if Utc::now().date_naive() <= *membership.end_date() {
                 UpToDate
             } else {
                 Expired
             }
         }
+        (Some(membership), Some(event_period)) => {
+            if membership.end_date() < event_period.start_date() {
+                Expired
+            } else if membership.start_date() <= event_period.start_date()
+                && event_period.end_date() <= membership.end_date()
+            {
+                UpToDate
+            } else {
+                NotCoveringEvent
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod compute_member_status {
+        use crate::membership::Membership;
+        use crate::membership_status::MemberStatus::{Expired, NotCoveringEvent, UpToDate};
+        use crate::membership_status::{EventPeriod, compute_member_status};
+        use chrono::NaiveDate;
+
+        fn membership() -> Membership {
+            Membership::new(
+                "Doe".to_owned(),
+                "Jon".to_owned(),
+                None,
+                "123456".to_owned(),
+                None,
+                "".to_owned(),
+                NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+                "".to_owned(),
+                "".to_owned(),
+                None,
+                None,
+            )
+        }
+
+        fn event_period(start_date: (u32, u32), end_date: (u32, u32)) -> EventPeriod {
+            EventPeriod::new(
+                NaiveDate::from_ymd_opt(2025, start_date.0, start_date.1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, end_date.0, end_date.1).unwrap(),
+            )
+        }
+
+        #[test]
+        fn up_to_date_when_covering_event() {
+            let event_period = event_period((9, 28), (9, 30));
+            assert_eq!(
+                UpToDate,
+                compute_member_status(Some(&membership()), Some(&event_period))
+            );
+        }
+
+        #[test]
+        fn expired_when_ended_before_event() {
+            let event_period = event_period((10, 1), (10, 3));
+            assert_eq!(
+                Expired,
+                compute_member_status(Some(&membership()), Some(&event_period))
+            );
+        }
+
+        #[test]
+        fn not_covering_event_when_ending_during_event() {
+            let event_period = event_period((9, 29), (10, 2));
+            assert_eq!(
+                NotCoveringEvent,
+                compute_member_status(Some(&membership()), Some(&event_period))
+            );
+        }
+
+        #[test]
+        fn not_covering_event_when_starting_after_event() {
+            let event_period = event_period((1, 1), (1, 3));
+            let membership = Membership::new(
+                "Doe".to_owned(),
+                "Jon".to_owned(),
+                None,
+                "123456".to_owned(),
+                None,
+                "".to_owned(),
+                NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+                "".to_owned(),
+                "".to_owned(),
+                None,
+                None,
+            );
+            assert_eq!(
+                NotCoveringEvent,
+                compute_member_status(Some(&membership), Some(&event_period))
+            );
+        }
+    }
+    mod select_covering_memberships {
+        use crate::membership::Membership;
+        use crate::membership_status::EventPeriod;
+        use chrono::NaiveDate;
+
+        fn membership(start_date: (i32, u32, u32), end_date: (i32, u32, u32)) -> Membership {
+            Membership::new(
+                "Doe".to_owned(),
+                "Jon".to_owned(),
+                None,
+                "123456".to_owned(),
+                None,
+                "".to_owned(),
+                NaiveDate::from_ymd_opt(start_date.0, start_date.1, start_date.2).unwrap(),
+                NaiveDate::from_ymd_opt(end_date.0, end_date.1, end_date.2).unwrap(),
+                "".to_owned(),
+                "".to_owned(),
+                None,
+                None,
+            )
+        }
+
+        fn event_period(start_date: (u32, u32), end_date: (u32, u32)) -> EventPeriod {
+            EventPeriod::new(
+                NaiveDate::from_ymd_opt(2025, start_date.0, start_date.1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, end_date.0, end_date.1).unwrap(),
+            )
+        }
+
+        #[test]
+        fn previous_membership_when_renewed_after_event() {
+            let previous_membership = membership((2024, 10, 1), (2025, 9, 30));
+            let renewed_membership = membership((2025, 10, 1), (2026, 9, 30));
+            assert_eq!(
+                vec![&previous_membership],
+                event_period((9, 28), (9, 30))
+                    .select_covering_memberships(&[&renewed_membership, &previous_membership])
+            );
+        }
+
+        #[test]
+        fn successive_memberships_when_renewed_during_event() {
+            let previous_membership = membership((2024, 10, 1), (2025, 9, 30));
+            let renewed_membership = membership((2025, 10, 1), (2026, 9, 30));
+            assert_eq!(
+                vec![&previous_membership, &renewed_membership],
+                event_period((9, 29), (10, 2))
+                    .select_covering_memberships(&[&renewed_membership, &previous_membership])
+            );
+        }
+
+        #[test]
+        fn single_membership_when_enough() {
+            let short_membership = membership((2025, 9, 1), (2025, 9, 30));
+            let long_membership = membership((2025, 1, 1), (2025, 12, 31));
+            let later_membership = membership((2025, 10, 1), (2026, 9, 30));
+            assert_eq!(
+                vec![&long_membership],
+                event_period((9, 29), (10, 2)).select_covering_memberships(&[
+                    &short_membership,
+                    &long_membership,
+                    &later_membership
+                ])
+            );
+        }
+
+        #[test]
+        fn none_when_gap_between_memberships() {
+            let previous_membership = membership((2024, 10, 1), (2025, 9, 30));
+            let renewed_membership = membership((2025, 10, 2), (2026, 9, 30));
+            assert!(
+                event_period((9, 29), (10, 2))
+                    .select_covering_memberships(&[&previous_membership, &renewed_membership])
+                    .is_empty()
+            );
+        }
+
+        #[test]
+        fn none_when_no_membership() {
+            assert!(
+                event_period((9, 29), (10, 2))
+                    .select_covering_memberships(&[])
+                    .is_empty()
+            );
+        }
     }
 }
@@ -1,4 +1,5 @@
 use crate::member_to_check::MemberToCheck;
+use chrono::NaiveDate;
 
 pub type UdaMember = uda_dto::uda_member::UdaMember;
 
@@ -23,6 +24,11 @@ impl MemberToCheck for UdaMember {
         Some(self.last_name().clone())
     }
 
+    /// UDA doesn't provide the birthdate of its members.
+    fn birthdate(&self) -> Option<NaiveDate> {
+        None
+    }
+
     fn email(&self) -> Option<String> {
         Some(self.email().clone())
     }
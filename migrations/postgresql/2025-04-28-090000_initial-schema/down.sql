@@ -0,0 +1,8 @@
+DROP TABLE fileo_health_check;
+DROP TABLE membership_rejected_row;
+DROP TABLE membership_history;
+DROP TABLE membership_change;
+DROP TABLE membership_import;
+DROP TABLE uda_instance;
+DROP TABLE membership;
+DROP TABLE last_update;
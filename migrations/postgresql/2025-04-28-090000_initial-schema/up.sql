@@ -0,0 +1,116 @@
+-- PostgreSQL counterpart of all the SQLite migrations up to the membership type and tariff.
+-- Both sets of migrations should lead to the same schema.
+CREATE TABLE last_update
+(
+    element VARCHAR NOT NULL PRIMARY KEY,
+    date    VARCHAR NOT NULL
+);
+
+CREATE TABLE membership
+(
+    id                              SERIAL  NOT NULL PRIMARY KEY,
+    last_name                       VARCHAR NOT NULL,
+    first_name                      VARCHAR NOT NULL,
+    birthdate                       VARCHAR,
+    membership_number               VARCHAR NOT NULL,
+    email_address                   VARCHAR NOT NULL,
+    end_date                        VARCHAR NOT NULL,
+    club                            VARCHAR NOT NULL,
+    structure_code                  VARCHAR NOT NULL,
+    normalized_membership_number    VARCHAR NOT NULL,
+    normalized_last_name            VARCHAR NOT NULL,
+    normalized_first_name           VARCHAR NOT NULL,
+    normalized_last_name_first_name VARCHAR NOT NULL,
+    normalized_first_name_last_name VARCHAR NOT NULL,
+    cell_number                     VARCHAR,
+    start_date                      VARCHAR NOT NULL,
+    membership_type                 VARCHAR,
+    tariff                          VARCHAR
+);
+
+CREATE INDEX normalized_membership_number_index ON membership (normalized_membership_number);
+CREATE INDEX normalized_last_name_index ON membership (normalized_last_name);
+CREATE INDEX normalized_first_name_index ON membership (normalized_first_name);
+CREATE INDEX normalized_last_name_first_name_index ON membership (normalized_last_name_first_name);
+CREATE INDEX normalized_first_name_last_name_index ON membership (normalized_first_name_last_name);
+CREATE INDEX normalized_membership_number_start_date_index ON membership (normalized_membership_number, start_date);
+
+CREATE TABLE uda_instance
+(
+    id   SERIAL  NOT NULL PRIMARY KEY,
+    slug VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    url  VARCHAR NOT NULL
+);
+
+CREATE TABLE membership_import
+(
+    id   SERIAL  NOT NULL PRIMARY KEY,
+    date VARCHAR NOT NULL
+);
+
+CREATE TABLE membership_change
+(
+    id                     SERIAL  NOT NULL PRIMARY KEY,
+    import_id              INTEGER NOT NULL REFERENCES membership_import (id) ON DELETE CASCADE,
+    kind                   VARCHAR NOT NULL,
+    membership_number      VARCHAR NOT NULL,
+    last_name              VARCHAR NOT NULL,
+    first_name             VARCHAR NOT NULL,
+    start_date             VARCHAR NOT NULL,
+    previous_email_address VARCHAR,
+    email_address          VARCHAR,
+    previous_end_date      VARCHAR,
+    end_date               VARCHAR,
+    previous_club          VARCHAR,
+    club                   VARCHAR
+);
+
+CREATE INDEX membership_change_import_id_index ON membership_change (import_id);
+
+CREATE TABLE membership_history
+(
+    id                              SERIAL  NOT NULL PRIMARY KEY,
+    import_id                       INTEGER NOT NULL REFERENCES membership_import (id),
+    last_name                       VARCHAR NOT NULL,
+    first_name                      VARCHAR NOT NULL,
+    birthdate                       VARCHAR,
+    membership_number               VARCHAR NOT NULL,
+    cell_number                     VARCHAR,
+    email_address                   VARCHAR NOT NULL,
+    start_date                      VARCHAR NOT NULL,
+    end_date                        VARCHAR NOT NULL,
+    club                            VARCHAR NOT NULL,
+    structure_code                  VARCHAR NOT NULL,
+    normalized_membership_number    VARCHAR NOT NULL,
+    normalized_last_name            VARCHAR NOT NULL,
+    normalized_first_name           VARCHAR NOT NULL,
+    normalized_last_name_first_name VARCHAR NOT NULL,
+    normalized_first_name_last_name VARCHAR NOT NULL,
+    membership_type                 VARCHAR,
+    tariff                          VARCHAR
+);
+
+CREATE INDEX history_normalized_membership_number_index ON membership_history (normalized_membership_number);
+CREATE INDEX history_normalized_last_name_index ON membership_history (normalized_last_name);
+CREATE INDEX history_normalized_first_name_index ON membership_history (normalized_first_name);
+
+CREATE TABLE membership_rejected_row
+(
+    id        SERIAL  NOT NULL PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES membership_import (id) ON DELETE CASCADE,
+    line      BIGINT  NOT NULL,
+    record    VARCHAR NOT NULL,
+    reason    VARCHAR NOT NULL
+);
+
+CREATE INDEX membership_rejected_row_import_id_index ON membership_rejected_row (import_id);
+
+CREATE TABLE fileo_health_check
+(
+    id          SERIAL  NOT NULL PRIMARY KEY,
+    date        VARCHAR NOT NULL,
+    failed_step VARCHAR,
+    reason      VARCHAR,
+    snippet     VARCHAR
+);
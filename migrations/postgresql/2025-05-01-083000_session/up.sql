@@ -0,0 +1,8 @@
+CREATE TABLE session
+(
+    id              VARCHAR NOT NULL PRIMARY KEY,
+    kind            VARCHAR NOT NULL,
+    credentials     VARCHAR NOT NULL,
+    expiration_date VARCHAR NOT NULL
+);
+CREATE INDEX session_kind_expiration_date ON session (kind, expiration_date);
@@ -0,0 +1 @@
+ALTER TABLE import_history ADD COLUMN updated_count BIGINT NOT NULL DEFAULT 0;
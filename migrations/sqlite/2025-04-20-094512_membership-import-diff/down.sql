@@ -0,0 +1,5 @@
+DROP INDEX normalized_membership_number_start_date_index;
+DROP INDEX membership_change_import_id_index;
+
+DROP TABLE membership_change;
+DROP TABLE membership_import;
@@ -0,0 +1,25 @@
+CREATE TABLE membership_import
+(
+    id   INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    date VARCHAR NOT NULL
+);
+
+CREATE TABLE membership_change
+(
+    id                     INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    import_id              INTEGER NOT NULL REFERENCES membership_import (id) ON DELETE CASCADE,
+    kind                   VARCHAR NOT NULL,
+    membership_number      VARCHAR NOT NULL,
+    last_name              VARCHAR NOT NULL,
+    first_name             VARCHAR NOT NULL,
+    start_date             VARCHAR NOT NULL,
+    previous_email_address VARCHAR,
+    email_address          VARCHAR,
+    previous_end_date      VARCHAR,
+    end_date               VARCHAR,
+    previous_club          VARCHAR,
+    club                   VARCHAR
+);
+
+CREATE INDEX membership_change_import_id_index ON membership_change (import_id);
+CREATE INDEX normalized_membership_number_start_date_index ON membership (normalized_membership_number, start_date);
@@ -0,0 +1,5 @@
+DROP INDEX history_normalized_membership_number_index;
+DROP INDEX history_normalized_last_name_index;
+DROP INDEX history_normalized_first_name_index;
+
+DROP TABLE membership_history;
@@ -0,0 +1,54 @@
+CREATE TABLE membership_history
+(
+    id                              INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    import_id                       INTEGER NOT NULL REFERENCES membership_import (id),
+    last_name                       VARCHAR NOT NULL,
+    first_name                      VARCHAR NOT NULL,
+    birthdate                       VARCHAR,
+    membership_number               VARCHAR NOT NULL,
+    cell_number                     VARCHAR,
+    email_address                   VARCHAR NOT NULL,
+    start_date                      VARCHAR NOT NULL,
+    end_date                        VARCHAR NOT NULL,
+    club                            VARCHAR NOT NULL,
+    structure_code                  VARCHAR NOT NULL,
+    normalized_membership_number    VARCHAR NOT NULL,
+    normalized_last_name            VARCHAR NOT NULL,
+    normalized_first_name           VARCHAR NOT NULL,
+    normalized_last_name_first_name VARCHAR NOT NULL,
+    normalized_first_name_last_name VARCHAR NOT NULL
+);
+
+CREATE INDEX history_normalized_membership_number_index ON membership_history (normalized_membership_number);
+CREATE INDEX history_normalized_last_name_index ON membership_history (normalized_last_name);
+CREATE INDEX history_normalized_first_name_index ON membership_history (normalized_first_name);
+
+-- Memberships imported before the history existed are attached to the last known import
+INSERT INTO membership_import (date)
+SELECT date
+FROM last_update
+WHERE element = 'Memberships'
+  AND NOT EXISTS (SELECT 1 FROM membership_import);
+
+INSERT INTO membership_history (import_id, last_name, first_name, birthdate, membership_number, cell_number,
+                                email_address, start_date, end_date, club, structure_code,
+                                normalized_membership_number, normalized_last_name, normalized_first_name,
+                                normalized_last_name_first_name, normalized_first_name_last_name)
+SELECT (SELECT MAX(id) FROM membership_import),
+       last_name,
+       first_name,
+       birthdate,
+       membership_number,
+       cell_number,
+       email_address,
+       start_date,
+       end_date,
+       club,
+       structure_code,
+       normalized_membership_number,
+       normalized_last_name,
+       normalized_first_name,
+       normalized_last_name_first_name,
+       normalized_first_name_last_name
+FROM membership
+WHERE EXISTS (SELECT 1 FROM membership_import);
@@ -0,0 +1,3 @@
+DROP INDEX membership_rejected_row_import_id_index;
+
+DROP TABLE membership_rejected_row;
@@ -0,0 +1,10 @@
+CREATE TABLE membership_rejected_row
+(
+    id        INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    import_id INTEGER NOT NULL REFERENCES membership_import (id) ON DELETE CASCADE,
+    line      BIGINT  NOT NULL,
+    record    VARCHAR NOT NULL,
+    reason    VARCHAR NOT NULL
+);
+
+CREATE INDEX membership_rejected_row_import_id_index ON membership_rejected_row (import_id);
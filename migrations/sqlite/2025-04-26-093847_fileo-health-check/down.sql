@@ -0,0 +1 @@
+DROP TABLE fileo_health_check;
@@ -0,0 +1,8 @@
+CREATE TABLE fileo_health_check
+(
+    id          INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    date        VARCHAR NOT NULL,
+    failed_step VARCHAR,
+    reason      VARCHAR,
+    snippet     VARCHAR
+);
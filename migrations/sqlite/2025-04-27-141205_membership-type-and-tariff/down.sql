@@ -0,0 +1,5 @@
+ALTER TABLE membership DROP COLUMN membership_type;
+ALTER TABLE membership DROP COLUMN tariff;
+
+ALTER TABLE membership_history DROP COLUMN membership_type;
+ALTER TABLE membership_history DROP COLUMN tariff;
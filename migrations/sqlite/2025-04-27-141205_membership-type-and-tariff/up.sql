@@ -0,0 +1,5 @@
+ALTER TABLE membership ADD COLUMN membership_type VARCHAR;
+ALTER TABLE membership ADD COLUMN tariff VARCHAR;
+
+ALTER TABLE membership_history ADD COLUMN membership_type VARCHAR;
+ALTER TABLE membership_history ADD COLUMN tariff VARCHAR;
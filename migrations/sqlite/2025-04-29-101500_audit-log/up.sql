@@ -0,0 +1,10 @@
+CREATE TABLE audit_log
+(
+    id            INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    date          VARCHAR NOT NULL,
+    actor         VARCHAR NOT NULL,
+    action        VARCHAR NOT NULL,
+    members_count BIGINT  NOT NULL,
+    targets       VARCHAR NOT NULL
+);
+CREATE INDEX audit_log_date ON audit_log (date);
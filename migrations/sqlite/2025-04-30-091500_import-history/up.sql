@@ -0,0 +1,15 @@
+CREATE TABLE import_history
+(
+    id             INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    element        VARCHAR NOT NULL,
+    source         VARCHAR NOT NULL,
+    actor          VARCHAR,
+    start_date     VARCHAR NOT NULL,
+    end_date       VARCHAR NOT NULL,
+    deleted_count  BIGINT  NOT NULL,
+    inserted_count BIGINT  NOT NULL,
+    rejected_count BIGINT  NOT NULL,
+    outcome        VARCHAR NOT NULL,
+    error          VARCHAR
+);
+CREATE INDEX import_history_start_date ON import_history (start_date);
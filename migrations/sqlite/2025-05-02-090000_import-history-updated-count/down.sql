@@ -0,0 +1 @@
+ALTER TABLE import_history DROP COLUMN updated_count;
@@ -0,0 +1,111 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::database::error::DatabaseError;
+use chrono::{NaiveDate, Utc};
+use diesel::r2d2::{ConnectionManager, Pool};
+use dto::audit_log::{AuditAction, AuditLogEntry, AuditLogFilter};
+use std::str::FromStr;
+
+/// The actor of the actions the app performs by itself.
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// Record who performed an action on personal data, for accountability.
+/// As the action has already been performed, failing to record it is only logged.
+pub fn record_action(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    actor: &str,
+    action: AuditAction,
+    count: usize,
+    targets: Vec<String>,
+) {
+    let entry = AuditLogEntry::new(
+        Utc::now().naive_local(),
+        actor.to_owned(),
+        action,
+        count,
+        targets,
+    );
+    let result = pool
+        .get()
+        .map_err(DatabaseError::from)
+        .and_then(|mut connection| dao::audit_log::insert(&mut connection, &entry));
+    if let Err(error) = result {
+        error!("Can't record action in audit log [actor: {actor}, action: {action}]\n{error:#?}");
+    }
+}
+
+/// Build a filter from the criteria given as query parameters.
+/// Empty criteria are ignored, while an unknown action or an invalid date (YYYY-MM-DD) is an error.
+pub fn build_filter(
+    actor: Option<&str>,
+    action: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<AuditLogFilter, String> {
+    let non_empty = |value: Option<&str>| value.map(str::trim).filter(|value| !value.is_empty());
+    let parse_date = |date: &str| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|error| format!("{date}: {error}"))
+    };
+
+    Ok(AuditLogFilter::new(
+        non_empty(actor).map(str::to_owned),
+        non_empty(action).map(AuditAction::from_str).transpose()?,
+        non_empty(from).map(parse_date).transpose()?,
+        non_empty(to).map(parse_date).transpose()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    mod build_filter {
+        use crate::audit::build_filter;
+        use chrono::NaiveDate;
+        use dto::audit_log::{AuditAction, AuditLogFilter};
+
+        #[test]
+        fn success() {
+            assert_eq!(
+                Ok(AuditLogFilter::new(
+                    Some("jon".to_owned()),
+                    Some(AuditAction::LookUp),
+                    NaiveDate::from_ymd_opt(2025, 4, 1),
+                    None,
+                )),
+                build_filter(Some("jon"), Some("LookUp"), Some("2025-04-01"), Some(""))
+            );
+        }
+
+        #[test]
+        fn fail_when_invalid_criteria() {
+            assert!(build_filter(None, Some("Delete"), None, None).is_err());
+            assert!(build_filter(None, None, None, Some("01/04/2025")).is_err());
+        }
+    }
+
+    mod record_action {
+        use crate::audit::record_action;
+        use crate::database::{dao, with_temp_database};
+        use dto::audit_log::{AuditAction, AuditLogFilter};
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                record_action(
+                    &pool,
+                    "jon",
+                    AuditAction::UdaConfirmation,
+                    2,
+                    vec!["1".to_owned(), "2".to_owned()],
+                );
+
+                let mut connection = pool.get().unwrap();
+                let entries =
+                    dao::audit_log::retrieve(&mut connection, &AuditLogFilter::default()).unwrap();
+                assert_eq!(1, entries.len());
+                assert_eq!("jon", entries[0].actor());
+                assert_eq!(&AuditAction::UdaConfirmation, entries[0].action());
+                assert_eq!(&vec!["1".to_owned(), "2".to_owned()], entries[0].targets());
+            })
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::database::DatabaseConnection;
+use crate::database::Result;
+use crate::database::error::DatabaseError;
+use crate::database::error::DatabaseError::{BackupFailed, UnsupportedBackend};
+use crate::tools::env_args::retrieve_arg_value;
+use chrono::Utc;
+use derive_getters::Getters;
+use diesel::RunQueryDsl;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::sql_types::Text;
+use rocket::fairing::AdHoc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const BACKUP_DIRECTORY_ARG: &str = "--backup-directory";
+const BACKUP_INTERVAL_ARG: &str = "--backup-interval-hours";
+const BACKUP_RETENTION_ARG: &str = "--backup-retention";
+const DEFAULT_BACKUP_INTERVAL_IN_HOURS: u64 = 24;
+const DEFAULT_BACKUP_RETENTION: usize = 7;
+
+const BACKUP_FILE_PREFIX: &str = "backup-";
+const BACKUP_FILE_EXTENSION: &str = ".db";
+
+/// Write a consistent copy of the database into a new file, while the app keeps running.
+/// Only SQLite databases can be backed up this way.
+pub fn backup_database(connection: &mut DatabaseConnection, destination: &Path) -> Result<()> {
+    let DatabaseConnection::Sqlite(connection) = connection else {
+        return Err(UnsupportedBackend);
+    };
+    let destination = destination
+        .to_str()
+        .ok_or_else(|| BackupFailed(format!("Invalid path: {destination:?}")))?;
+
+    diesel::sql_query("VACUUM INTO ?")
+        .bind::<Text, _>(destination)
+        .execute(connection)?;
+
+    Ok(())
+}
+
+#[derive(Getters, Clone, Debug, PartialEq)]
+pub struct ScheduledBackupConfig {
+    directory: PathBuf,
+    interval: Duration,
+    retention: usize,
+}
+
+/// Build the scheduled backup config from the args.
+/// Return None if no backup directory has been provided, in which case the database isn't backed up automatically.
+pub fn retrieve_scheduled_backup_config() -> Option<ScheduledBackupConfig> {
+    let directory = retrieve_arg_value(BACKUP_DIRECTORY_ARG)?;
+    let interval_in_hours = retrieve_arg_value(BACKUP_INTERVAL_ARG)
+        .and_then(|interval| interval.parse::<u64>().ok())
+        .filter(|interval| *interval > 0)
+        .unwrap_or(DEFAULT_BACKUP_INTERVAL_IN_HOURS);
+    let retention = retrieve_arg_value(BACKUP_RETENTION_ARG)
+        .and_then(|retention| retention.parse::<usize>().ok())
+        .filter(|retention| *retention > 0)
+        .unwrap_or(DEFAULT_BACKUP_RETENTION);
+
+    Some(ScheduledBackupConfig {
+        directory: PathBuf::from(directory),
+        interval: Duration::from_secs(interval_in_hours * 3600),
+        retention,
+    })
+}
+
+/// Once the server is launched, periodically back the database up in the background.
+/// The first backup occurs right after the launch.
+pub fn scheduled_backup_fairing(config: Option<ScheduledBackupConfig>) -> AdHoc {
+    AdHoc::on_liftoff("Scheduled database backup", move |rocket| {
+        let config = config.clone();
+        Box::pin(async move {
+            let Some(config) = config else {
+                debug!("No backup directory provided, database won't be backed up automatically.");
+                return;
+            };
+            let Some(pool) = rocket.state::<Pool<ConnectionManager<DatabaseConnection>>>() else {
+                error!("Can't schedule database backup: missing state.");
+                return;
+            };
+            let pool = pool.clone();
+
+            rocket::tokio::spawn(async move {
+                let mut interval = rocket::tokio::time::interval(*config.interval());
+                loop {
+                    interval.tick().await;
+                    match run_scheduled_backup(&pool, &config) {
+                        Ok(backup) => info!("Database backed up [file: {backup:?}]"),
+                        Err(error) => error!("Scheduled database backup failed\n{error:#?}"),
+                    }
+                }
+            });
+        })
+    })
+}
+
+/// Back the database up into the backup directory, then delete the oldest backups.
+pub(crate) fn run_scheduled_backup(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    config: &ScheduledBackupConfig,
+) -> Result<PathBuf> {
+    fs::create_dir_all(config.directory()).map_err(|error| BackupFailed(error.to_string()))?;
+    let file_name = format!(
+        "{BACKUP_FILE_PREFIX}{}{BACKUP_FILE_EXTENSION}",
+        Utc::now().format("%Y%m%d-%H%M%S%.3f")
+    );
+    let backup = config.directory().join(file_name);
+
+    let mut connection = pool.get().map_err(DatabaseError::from)?;
+    backup_database(&mut connection, &backup)?;
+    rotate_backups(config.directory(), *config.retention())?;
+
+    Ok(backup)
+}
+
+/// Keep only the most recent backups of the directory.
+/// Other files are left untouched.
+fn rotate_backups(directory: &Path, retention: usize) -> Result<()> {
+    let mut backups = fs::read_dir(directory)
+        .map_err(|error| BackupFailed(error.to_string()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(BACKUP_FILE_PREFIX) && name.ends_with(BACKUP_FILE_EXTENSION)
+                })
+        })
+        .collect::<Vec<_>>();
+    // As file names contain the date, the oldest backups come first
+    backups.sort();
+
+    let backups_to_delete = backups.len().saturating_sub(retention);
+    for backup in &backups[..backups_to_delete] {
+        debug!("Deleting old backup [file: {backup:?}]");
+        fs::remove_file(backup).map_err(|error| BackupFailed(error.to_string()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_scheduled_backup_config {
+        use crate::database::backup::{
+            BACKUP_DIRECTORY_ARG, BACKUP_INTERVAL_ARG, BACKUP_RETENTION_ARG,
+            DEFAULT_BACKUP_INTERVAL_IN_HOURS, DEFAULT_BACKUP_RETENTION, ScheduledBackupConfig,
+            retrieve_scheduled_backup_config,
+        };
+        use crate::tools::env_args::with_env_args;
+        use std::path::PathBuf;
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            let config = with_env_args(
+                vec![
+                    format!("{BACKUP_DIRECTORY_ARG}=/var/backups"),
+                    format!("{BACKUP_INTERVAL_ARG}=6"),
+                    format!("{BACKUP_RETENTION_ARG}=3"),
+                ],
+                retrieve_scheduled_backup_config,
+            );
+
+            assert_eq!(
+                Some(ScheduledBackupConfig {
+                    directory: PathBuf::from("/var/backups"),
+                    interval: Duration::from_secs(6 * 3600),
+                    retention: 3,
+                }),
+                config
+            );
+        }
+
+        #[test]
+        fn success_with_defaults() {
+            let config = with_env_args(
+                vec![format!("{BACKUP_DIRECTORY_ARG}=/var/backups")],
+                retrieve_scheduled_backup_config,
+            )
+            .unwrap();
+
+            assert_eq!(
+                &Duration::from_secs(DEFAULT_BACKUP_INTERVAL_IN_HOURS * 3600),
+                config.interval()
+            );
+            assert_eq!(&DEFAULT_BACKUP_RETENTION, config.retention());
+        }
+
+        #[test]
+        fn none_without_directory() {
+            assert_eq!(
+                None,
+                with_env_args(vec![], retrieve_scheduled_backup_config)
+            );
+        }
+    }
+
+    mod run_scheduled_backup {
+        use crate::database::backup::{ScheduledBackupConfig, run_scheduled_backup};
+        use crate::database::with_temp_sqlite_database;
+        use crate::tools::test::tests::temp_dir;
+        use std::fs;
+        use std::time::Duration;
+
+        #[test]
+        fn success_with_rotation() {
+            with_temp_sqlite_database(|pool| {
+                let directory = temp_dir();
+                fs::write(directory.join("backup-20000101-000000.000.db"), "old").unwrap();
+                fs::write(directory.join("notes.txt"), "not a backup").unwrap();
+                let config = ScheduledBackupConfig {
+                    directory: directory.clone(),
+                    interval: Duration::from_secs(3600),
+                    retention: 1,
+                };
+
+                let backup = run_scheduled_backup(&pool, &config).unwrap();
+
+                let mut files = fs::read_dir(&directory)
+                    .unwrap()
+                    .map(|entry| entry.unwrap().path())
+                    .collect::<Vec<_>>();
+                files.sort();
+                assert_eq!(vec![backup, directory.join("notes.txt")], files);
+            })
+        }
+    }
+}
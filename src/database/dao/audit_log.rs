@@ -0,0 +1,155 @@
+use super::Result;
+use crate::database::DatabaseConnection;
+use crate::database::model::audit_log::{AuditLog, TARGETS_SEPARATOR};
+use chrono::Days;
+use diesel::prelude::*;
+use dto::audit_log::{AuditLogEntry, AuditLogFilter};
+
+/// Browsing the audit log only shows the most recent entries.
+const MAX_RETRIEVED_ENTRIES: i64 = 1000;
+
+/// Record an action performed by a user.
+pub fn insert(connection: &mut DatabaseConnection, entry: &AuditLogEntry) -> Result<()> {
+    use crate::database::schema::audit_log::*;
+
+    diesel::insert_into(table)
+        .values((
+            date.eq(entry.date().to_string()),
+            actor.eq(entry.actor()),
+            action.eq(entry.action().to_string()),
+            members_count.eq(*entry.count() as i64),
+            targets.eq(entry.targets().join(TARGETS_SEPARATOR)),
+        ))
+        .execute(connection)?;
+
+    Ok(())
+}
+
+/// Retrieve the entries matching the filter, the most recent first.
+pub fn retrieve(
+    connection: &mut DatabaseConnection,
+    filter: &AuditLogFilter,
+) -> Result<Vec<AuditLogEntry>> {
+    use crate::database::schema::audit_log::dsl::*;
+
+    let mut statement = audit_log
+        .order(id.desc())
+        .limit(MAX_RETRIEVED_ENTRIES)
+        .select(AuditLog::as_select())
+        .into_boxed();
+    if let Some(searched_actor) = filter.actor() {
+        statement = statement.filter(actor.eq(searched_actor));
+    }
+    if let Some(searched_action) = filter.action() {
+        statement = statement.filter(action.eq(searched_action.to_string()));
+    }
+    // Dates are stored as text, which sorts like dates
+    if let Some(from) = filter.from() {
+        statement = statement.filter(date.ge(from.to_string()));
+    }
+    if let Some(to) = filter.to().and_then(|to| to.checked_add_days(Days::new(1))) {
+        statement = statement.filter(date.lt(to.to_string()));
+    }
+
+    statement
+        .load(connection)?
+        .into_iter()
+        .map(AuditLogEntry::try_from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve {
+        use crate::database::dao::audit_log::{insert, retrieve};
+        use crate::database::with_temp_database;
+        use chrono::NaiveDate;
+        use dto::audit_log::{AuditAction, AuditLogEntry, AuditLogFilter};
+
+        fn create_entry(day: u32, actor: &str, action: AuditAction) -> AuditLogEntry {
+            AuditLogEntry::new(
+                NaiveDate::from_ymd_opt(2025, 4, day)
+                    .unwrap()
+                    .and_hms_opt(10, 30, 0)
+                    .unwrap(),
+                actor.to_owned(),
+                action,
+                2,
+                vec!["123456".to_owned(), "654321".to_owned()],
+            )
+        }
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let first_entry = create_entry(1, "jon", AuditAction::CsvCheck);
+                let second_entry = create_entry(2, "jonette", AuditAction::LookUp);
+                insert(&mut connection, &first_entry).unwrap();
+                insert(&mut connection, &second_entry).unwrap();
+
+                let result = retrieve(&mut connection, &AuditLogFilter::default()).unwrap();
+                assert_eq!(vec![second_entry, first_entry], result);
+            })
+        }
+
+        #[test]
+        fn success_with_filter() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let expected_entry = create_entry(2, "jon", AuditAction::Notification);
+                insert(
+                    &mut connection,
+                    &create_entry(1, "jon", AuditAction::Notification),
+                )
+                .unwrap();
+                insert(&mut connection, &expected_entry).unwrap();
+                insert(
+                    &mut connection,
+                    &create_entry(2, "jon", AuditAction::LookUp),
+                )
+                .unwrap();
+                insert(
+                    &mut connection,
+                    &create_entry(2, "jonette", AuditAction::Notification),
+                )
+                .unwrap();
+                insert(
+                    &mut connection,
+                    &create_entry(3, "jon", AuditAction::Notification),
+                )
+                .unwrap();
+
+                let filter = AuditLogFilter::new(
+                    Some("jon".to_owned()),
+                    Some(AuditAction::Notification),
+                    NaiveDate::from_ymd_opt(2025, 4, 2),
+                    NaiveDate::from_ymd_opt(2025, 4, 2),
+                );
+                let result = retrieve(&mut connection, &filter).unwrap();
+                assert_eq!(vec![expected_entry], result);
+            })
+        }
+
+        #[test]
+        fn success_without_targets() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let entry = AuditLogEntry::new(
+                    NaiveDate::from_ymd_opt(2025, 4, 1)
+                        .unwrap()
+                        .and_hms_opt(10, 30, 0)
+                        .unwrap(),
+                    "jon".to_owned(),
+                    AuditAction::CsvCheck,
+                    3,
+                    vec![],
+                );
+                insert(&mut connection, &entry).unwrap();
+
+                let result = retrieve(&mut connection, &AuditLogFilter::default()).unwrap();
+                assert_eq!(vec![entry], result);
+            })
+        }
+    }
+}
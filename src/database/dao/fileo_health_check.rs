@@ -0,0 +1,75 @@
+use super::Result;
+use crate::database::DatabaseConnection;
+use crate::database::model::fileo_health_check::FileoHealthCheck;
+use diesel::prelude::*;
+use dto::fileo_health::FileoHealthReport;
+
+/// Record the result of a Fileo health check.
+pub fn insert(connection: &mut DatabaseConnection, report: &FileoHealthReport) -> Result<()> {
+    use crate::database::schema::fileo_health_check::*;
+
+    diesel::insert_into(table)
+        .values((
+            date.eq(report.date().to_string()),
+            failed_step.eq(report.failed_step().map(|step| step.to_string())),
+            reason.eq(report.reason().clone()),
+            snippet.eq(report.snippet().clone()),
+        ))
+        .execute(connection)?;
+
+    Ok(())
+}
+
+/// Retrieve the result of the most recent Fileo health check, if any.
+pub fn retrieve_last(connection: &mut DatabaseConnection) -> Result<Option<FileoHealthReport>> {
+    use crate::database::schema::fileo_health_check::dsl::{fileo_health_check, id};
+
+    let last_check = fileo_health_check
+        .order(id.desc())
+        .select(FileoHealthCheck::as_select())
+        .first(connection)
+        .optional()?;
+
+    last_check.map(FileoHealthReport::try_from).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_last {
+        use crate::database::dao::fileo_health_check::{insert, retrieve_last};
+        use crate::database::with_temp_database;
+        use chrono::Utc;
+        use dto::fileo_health::{FileoFlowStep, FileoHealthReport};
+
+        #[test]
+        fn none_when_never_checked() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                assert_eq!(None, retrieve_last(&mut connection).unwrap());
+            })
+        }
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                insert(
+                    &mut connection,
+                    &FileoHealthReport::healthy(Utc::now().naive_local()),
+                )
+                .unwrap();
+                let report = FileoHealthReport::broken(
+                    Utc::now().naive_local(),
+                    FileoFlowStep::ExportLink,
+                    "No download link".to_owned(),
+                    Some("Export en cours".to_owned()),
+                );
+                insert(&mut connection, &report).unwrap();
+
+                assert_eq!(Some(report), retrieve_last(&mut connection).unwrap());
+            })
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::Result;
+use crate::database::DatabaseConnection;
+use crate::database::model::import_history::ImportHistory;
+use diesel::prelude::*;
+use dto::import_history::{ImportHistoryEntry, ImportedElement};
+
+/// Browsing the import history only shows the most recent imports.
+const MAX_RETRIEVED_ENTRIES: i64 = 200;
+
+/// Record an import, whatever its outcome.
+pub fn insert(connection: &mut DatabaseConnection, entry: &ImportHistoryEntry) -> Result<()> {
+    use crate::database::schema::import_history::*;
+
+    diesel::insert_into(table)
+        .values((
+            element.eq(entry.element().to_string()),
+            source.eq(entry.source().to_string()),
+            actor.eq(entry.actor()),
+            start_date.eq(entry.start().to_string()),
+            end_date.eq(entry.end().to_string()),
+            deleted_count.eq(*entry.counts().deleted() as i64),
+            inserted_count.eq(*entry.counts().inserted() as i64),
+            updated_count.eq(*entry.counts().updated() as i64),
+            rejected_count.eq(*entry.counts().rejected() as i64),
+            outcome.eq(entry.outcome().to_string()),
+            error.eq(entry.error()),
+        ))
+        .execute(connection)?;
+
+    Ok(())
+}
+
+/// Retrieve the imports of the given element, or of all elements, the most recent first.
+pub fn retrieve(
+    connection: &mut DatabaseConnection,
+    imported_element: Option<ImportedElement>,
+) -> Result<Vec<ImportHistoryEntry>> {
+    use crate::database::schema::import_history::dsl::*;
+
+    let mut statement = import_history
+        .order(id.desc())
+        .limit(MAX_RETRIEVED_ENTRIES)
+        .select(ImportHistory::as_select())
+        .into_boxed();
+    if let Some(imported_element) = imported_element {
+        statement = statement.filter(element.eq(imported_element.to_string()));
+    }
+
+    statement
+        .load(connection)?
+        .into_iter()
+        .map(ImportHistoryEntry::try_from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve {
+        use crate::database::dao::import_history::{insert, retrieve};
+        use crate::database::with_temp_database;
+        use chrono::NaiveDate;
+        use dto::import_history::{
+            ImportCounts, ImportHistoryEntry, ImportOutcome, ImportSource, ImportedElement,
+        };
+
+        fn create_entry(element: ImportedElement, outcome: ImportOutcome) -> ImportHistoryEntry {
+            let start = NaiveDate::from_ymd_opt(2025, 4, 30)
+                .unwrap()
+                .and_hms_milli_opt(10, 30, 0, 250)
+                .unwrap();
+            let end = NaiveDate::from_ymd_opt(2025, 4, 30)
+                .unwrap()
+                .and_hms_milli_opt(10, 30, 2, 0)
+                .unwrap();
+            let error = match outcome {
+                ImportOutcome::Success => None,
+                _ => Some("Something went wrong".to_owned()),
+            };
+            ImportHistoryEntry::new(
+                element,
+                ImportSource::Scraping,
+                Some("login".to_owned()),
+                start,
+                end,
+                ImportCounts::new(1, 2, 3, 4),
+                outcome,
+                error,
+            )
+        }
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let memberships_import =
+                    create_entry(ImportedElement::Memberships, ImportOutcome::Success);
+                let instances_import =
+                    create_entry(ImportedElement::UdaInstances, ImportOutcome::Failure);
+                insert(&mut connection, &memberships_import).unwrap();
+                insert(&mut connection, &instances_import).unwrap();
+
+                assert_eq!(
+                    vec![instances_import, memberships_import.clone()],
+                    retrieve(&mut connection, None).unwrap()
+                );
+                assert_eq!(
+                    vec![memberships_import],
+                    retrieve(&mut connection, Some(ImportedElement::Memberships)).unwrap()
+                );
+            })
+        }
+    }
+}
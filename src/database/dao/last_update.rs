@@ -1,4 +1,5 @@
 use super::Result;
+use crate::database::DatabaseConnection;
 use crate::database::error::DatabaseError::CantUpdateLastUpdated;
 use crate::database::model::last_update::LastUpdate;
 use crate::database::schema::last_update::dsl::last_update;
@@ -11,6 +12,9 @@ use std::fmt::{Display, Formatter};
 pub enum UpdatableElement {
     Memberships,
     UdaInstances,
+    MembershipsRefreshSuccess,
+    MembershipsRefreshFailure,
+    MembershipsRefreshAlert,
 }
 
 impl Display for UpdatableElement {
@@ -20,7 +24,7 @@ impl Display for UpdatableElement {
 }
 
 pub fn get_last_update(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
     updatable_element: &UpdatableElement,
 ) -> Result<Option<NaiveDateTime>> {
     let result = last_update
@@ -36,8 +40,8 @@ pub fn get_last_update(
     }
 }
 
-pub(super) fn update(
-    connection: &mut SqliteConnection,
+pub fn update(
+    connection: &mut DatabaseConnection,
     updatable_element: &UpdatableElement,
 ) -> Result<NaiveDateTime> {
     let update_date = Utc::now().naive_local();
@@ -75,7 +79,7 @@ mod tests {
     use diesel::prelude::*;
 
     fn test_update_element(
-        connection: &mut SqliteConnection,
+        connection: &mut DatabaseConnection,
         updatable_element: &UpdatableElement,
     ) -> NaiveDateTime {
         let update_time = update(connection, updatable_element).unwrap();
@@ -1,12 +1,29 @@
-use super::Result;
+use super::{MAX_QUERY_PARAMETERS, Result};
+use crate::database::DatabaseConnection;
 use crate::database::dao::last_update::UpdatableElement;
+use crate::database::encryption::{any_plain_text, encrypt, encrypt_plain_text};
+use crate::database::error::DatabaseError;
 use crate::database::model::membership::Membership;
 use crate::tools::normalize;
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use dto::membership_diff::{MembershipChange, MembershipsDiff};
+use std::collections::{HashMap, HashSet};
+
+/// Number of columns set when inserting a membership, i.e. all of them but the id.
+const INSERTED_COLUMNS: usize = 17;
+
+pub fn count(connection: &mut DatabaseConnection) -> Result<usize> {
+    let count = crate::database::schema::membership::dsl::membership
+        .count()
+        .get_result::<i64>(connection)?;
+
+    Ok(count as usize)
+}
 
 pub fn retrieve_memberships(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
 ) -> Result<Vec<dto::membership::Membership>> {
     let results = crate::database::schema::membership::dsl::membership
         .select(Membership::as_select())
@@ -24,14 +41,69 @@ pub fn retrieve_memberships(
     Ok(memberships)
 }
 
-fn delete_all(connection: &mut SqliteConnection) -> Result<usize> {
-    let count = diesel::delete(crate::database::schema::membership::table).execute(connection)?;
+fn delete_all_by_id(connection: &mut DatabaseConnection, ids: &[i32]) -> Result<usize> {
+    use crate::database::schema::membership::id;
+
+    let mut count = 0;
+    for chunk in ids.chunks(MAX_QUERY_PARAMETERS) {
+        count += diesel::delete(crate::database::schema::membership::table)
+            .filter(id.eq_any(chunk))
+            .execute(connection)?;
+    }
+
+    Ok(count)
+}
+
+/// Personal data is encrypted before being stored, if an encryption key has been provided.
+pub(super) fn encrypt_birthdate(
+    membership: &dto::membership::Membership,
+) -> Result<Option<String>> {
+    membership
+        .birthdate()
+        .map(|birthdate| encrypt(&birthdate.to_string()))
+        .transpose()
+}
+
+pub(super) fn encrypt_cell_number(
+    membership: &dto::membership::Membership,
+) -> Result<Option<String>> {
+    membership.cell_number().as_deref().map(encrypt).transpose()
+}
+
+/// Encrypt the personal data of the memberships stored in plain text.
+/// Return the number of updated memberships.
+pub(crate) fn encrypt_plain_text_values(connection: &mut DatabaseConnection) -> Result<usize> {
+    use crate::database::schema::membership::*;
+
+    let memberships = table
+        .select((id, birthdate, cell_number, email_address))
+        .load::<(i32, Option<String>, Option<String>, String)>(connection)?;
+
+    let mut count = 0;
+    for (membership_id, stored_birthdate, stored_cell_number, stored_email_address) in memberships {
+        if !any_plain_text([
+            stored_birthdate.as_deref(),
+            stored_cell_number.as_deref(),
+            Some(stored_email_address.as_str()),
+        ]) {
+            continue;
+        }
+
+        count += diesel::update(table)
+            .filter(id.eq(membership_id))
+            .set((
+                birthdate.eq(stored_birthdate.map(encrypt_plain_text).transpose()?),
+                cell_number.eq(stored_cell_number.map(encrypt_plain_text).transpose()?),
+                email_address.eq(encrypt_plain_text(stored_email_address)?),
+            ))
+            .execute(connection)?;
+    }
 
     Ok(count)
 }
 
 fn insert_all(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
     memberships: &[dto::membership::Membership],
 ) -> Result<usize> {
     use crate::database::schema::membership::*;
@@ -39,13 +111,13 @@ fn insert_all(
     let memberships = memberships
         .iter()
         .map(|membership| {
-            (
+            Ok((
                 last_name.eq(membership.name().clone()),
                 first_name.eq(membership.first_name().clone()),
-                birthdate.eq(membership.birthdate().map(|b| b.to_string())),
+                birthdate.eq(encrypt_birthdate(membership)?),
                 membership_number.eq(membership.membership_number().clone()),
-                cell_number.eq(membership.cell_number().clone()),
-                email_address.eq(membership.email_address().clone()),
+                cell_number.eq(encrypt_cell_number(membership)?),
+                email_address.eq(encrypt(membership.email_address())?),
                 start_date.eq(membership.start_date().to_string()),
                 end_date.eq(membership.end_date().to_string()),
                 club.eq(membership.club().clone()),
@@ -63,12 +135,13 @@ fn insert_all(
                     normalize(membership.first_name()),
                     normalize(membership.name()),
                 )),
-            )
+                membership_type.eq(membership.membership_type().clone()),
+                tariff.eq(membership.tariff().clone()),
+            ))
         })
-        .collect::<Vec<_>>();
-    // Limit of 32766 parameters in a query for SQLite > 3.32.0.
+        .collect::<Result<Vec<_>>>()?;
     // As each line has 17 parameters, we have a theoretic maximum of 32 766 / 17 = 1927,4.
-    let memberships = memberships.chunks(1927);
+    let memberships = memberships.chunks(MAX_QUERY_PARAMETERS / INSERTED_COLUMNS);
 
     let mut count = 0;
     for chunk in memberships {
@@ -77,131 +150,165 @@ fn insert_all(
             .execute(connection)?;
     }
 
-    super::last_update::update(connection, &UpdatableElement::Memberships)?;
+    Ok(count)
+}
+
+/// Overwrite all the fields of an already known membership.
+fn update(
+    connection: &mut DatabaseConnection,
+    membership_id: i32,
+    membership: &dto::membership::Membership,
+) -> Result<usize> {
+    use crate::database::schema::membership::*;
+
+    let count = diesel::update(crate::database::schema::membership::table)
+        .filter(id.eq(membership_id))
+        .set((
+            last_name.eq(membership.name().clone()),
+            first_name.eq(membership.first_name().clone()),
+            birthdate.eq(encrypt_birthdate(membership)?),
+            membership_number.eq(membership.membership_number().clone()),
+            cell_number.eq(encrypt_cell_number(membership)?),
+            email_address.eq(encrypt(membership.email_address())?),
+            end_date.eq(membership.end_date().to_string()),
+            club.eq(membership.club().clone()),
+            structure_code.eq(membership.structure_code().clone()),
+            normalized_last_name.eq(normalize(membership.name())),
+            normalized_first_name.eq(normalize(membership.first_name())),
+            normalized_last_name_first_name.eq(format!(
+                "{}{}",
+                normalize(membership.name()),
+                normalize(membership.first_name()),
+            )),
+            normalized_first_name_last_name.eq(format!(
+                "{}{}",
+                normalize(membership.first_name()),
+                normalize(membership.name()),
+            )),
+            membership_type.eq(membership.membership_type().clone()),
+            tariff.eq(membership.tariff().clone()),
+        ))
+        .execute(connection)?;
 
     Ok(count)
 }
 
-/// Delete all known memberships and replace them with new ones.
-/// Return the number of deleted memberships and the number of inserted memberships.
+/// Synchronize known memberships with new ones.
+/// Memberships are matched on their normalized membership number and their start date:
+/// unknown ones are inserted, known ones are updated and missing ones are deleted.
+/// The changes on email addresses, end dates and clubs are recorded along with the import,
+/// and every new version of a membership is archived, so that it can still be looked up later on.
+/// The whole operation is run within a transaction: if anything fails, known memberships are left untouched.
 pub fn replace_memberships(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
     memberships: &[dto::membership::Membership],
-) -> Result<(usize, usize)> {
-    let deleted_count = delete_all(connection)?;
-    let inserted_count = insert_all(connection, memberships)?;
-
-    Ok((deleted_count, inserted_count))
+) -> Result<MembershipsDiff> {
+    connection.transaction::<_, DatabaseError, _>(|connection| {
+        synchronize_memberships(connection, memberships)
+    })
 }
 
-pub(crate) mod find {
-    use super::super::Result;
-    use crate::database::model::membership::Membership;
-    use crate::database::schema::membership::{
-        end_date, normalized_first_name, normalized_first_name_last_name, normalized_last_name,
-        normalized_last_name_first_name, normalized_membership_number,
-    };
-    use crate::tools::normalize;
-    use diesel::dsl::{Asc, Desc};
-    use diesel::{
-        BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper,
-        SqliteConnection,
-    };
+fn synchronize_memberships(
+    connection: &mut DatabaseConnection,
+    memberships: &[dto::membership::Membership],
+) -> Result<MembershipsDiff> {
+    let mut known_memberships = crate::database::schema::membership::dsl::membership
+        .select(Membership::as_select())
+        .load(connection)?
+        .into_iter()
+        .map(|known_membership| (known_membership.key(), known_membership))
+        .collect::<HashMap<_, _>>();
+
+    let mut changes = Vec::new();
+    let mut memberships_to_insert = Vec::new();
+    let mut memberships_to_archive = Vec::new();
+    let mut imported_keys = HashSet::new();
+    for membership in memberships {
+        let key = (
+            normalize(membership.membership_number()),
+            membership.start_date().to_string(),
+        );
+        if !imported_keys.insert(key.clone()) {
+            debug!("Ignoring duplicated membership [membership: {membership:?}]");
+            continue;
+        }
+
+        match known_memberships.remove(&key) {
+            None => {
+                changes.push(MembershipChange::added(membership));
+                memberships_to_insert.push(membership.clone());
+                memberships_to_archive.push(membership.clone());
+            }
+            Some(known_membership) => {
+                let known_membership_id = known_membership.id();
+                let known_membership = dto::membership::Membership::try_from(known_membership)?;
+                if &known_membership != membership {
+                    update(connection, known_membership_id, membership)?;
+                    memberships_to_archive.push(membership.clone());
+                }
+                if let Some(change) = MembershipChange::between(&known_membership, membership) {
+                    changes.push(change);
+                }
+            }
+        }
+    }
 
-    fn by_num(
-        connection: &mut SqliteConnection,
-        membership_number: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<Membership>> {
-        let results = crate::database::schema::membership::dsl::membership
-            .filter(normalized_membership_number.eq(normalize(membership_number)))
-            .order(get_order())
-            .limit(limit.unwrap_or(i64::MAX))
-            .select(Membership::as_select())
-            .load(connection)?;
-
-        Ok(results)
+    let mut missing_memberships = known_memberships.into_values().collect::<Vec<_>>();
+    missing_memberships.sort_by_key(Membership::id);
+    let mut ids_to_delete = Vec::new();
+    for known_membership in missing_memberships {
+        ids_to_delete.push(known_membership.id());
+        let known_membership = dto::membership::Membership::try_from(known_membership)?;
+        changes.push(MembershipChange::removed(&known_membership));
     }
 
-    fn by_num_identity(
-        connection: &mut SqliteConnection,
-        membership_number: &str,
-        identity: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<Membership>> {
-        let normalized_identity = normalize(identity);
-        let results = crate::database::schema::membership::dsl::membership
-            .filter(normalized_membership_number.eq(normalize(membership_number)))
-            .filter(
-                normalized_last_name_first_name
-                    .eq(&normalized_identity)
-                    .or(normalized_first_name_last_name.eq(&normalized_identity)),
-            )
-            .order(get_order())
-            .limit(limit.unwrap_or(i64::MAX))
-            .select(Membership::as_select())
-            .load(connection)?;
+    delete_all_by_id(connection, &ids_to_delete)?;
+    insert_all(connection, &memberships_to_insert)?;
 
-        Ok(results)
-    }
+    let import_date = super::last_update::update(connection, &UpdatableElement::Memberships)?;
+    let import_id = super::membership_import::insert(connection, &import_date, &changes)?;
+    super::membership_history::insert_all(connection, import_id, &memberships_to_archive)?;
 
-    fn by_num_last_name_first_name(
-        connection: &mut SqliteConnection,
-        membership_number: &str,
-        last_name: &str,
-        first_name: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<Membership>> {
-        let results = crate::database::schema::membership::dsl::membership
-            .filter(normalized_membership_number.eq(normalize(membership_number)))
-            .filter(normalized_last_name.eq(normalize(last_name)))
-            .filter(normalized_first_name.eq(normalize(first_name)))
-            .order(get_order())
-            .limit(limit.unwrap_or(i64::MAX))
-            .select(Membership::as_select())
-            .load(connection)?;
-
-        Ok(results)
-    }
+    Ok(MembershipsDiff::new(import_date, changes))
+}
 
-    fn by_identity(
-        connection: &mut SqliteConnection,
-        identity: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<Membership>> {
-        let normalized_identity = normalize(identity);
-        let results = crate::database::schema::membership::dsl::membership
-            .filter(
-                normalized_last_name_first_name
-                    .eq(&normalized_identity)
-                    .or(normalized_first_name_last_name.eq(&normalized_identity)),
-            )
-            .order(get_order())
-            .limit(limit.unwrap_or(i64::MAX))
-            .select(Membership::as_select())
-            .load(connection)?;
+/// Erase the personal data (email address, cell number and birthdate)
+/// of the memberships that ended before the given date.
+/// Other fields are kept, for statistics.
+/// Return the membership numbers of the memberships that have been pseudonymized.
+pub fn pseudonymize_ended_before(
+    connection: &mut DatabaseConnection,
+    date: &NaiveDate,
+) -> Result<Vec<String>> {
+    use crate::database::schema::membership::*;
 
-        Ok(results)
-    }
+    let membership_numbers = diesel::update(crate::database::schema::membership::table)
+        .filter(end_date.lt(date.to_string()))
+        .filter(
+            email_address
+                .ne("")
+                .or(cell_number.is_not_null())
+                .or(birthdate.is_not_null()),
+        )
+        .set((
+            email_address.eq(""),
+            cell_number.eq(None::<String>),
+            birthdate.eq(None::<String>),
+        ))
+        .returning(membership_number)
+        .get_results(connection)?;
+
+    Ok(membership_numbers)
+}
 
-    fn by_last_name_first_name(
-        connection: &mut SqliteConnection,
-        last_name: &str,
-        first_name: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<Membership>> {
-        let results = crate::database::schema::membership::dsl::membership
-            .filter(normalized_last_name.eq(normalize(last_name)))
-            .filter(normalized_first_name.eq(normalize(first_name)))
-            .order(get_order())
-            .limit(limit.unwrap_or(i64::MAX))
-            .select(Membership::as_select())
-            .load(connection)?;
-
-        Ok(results)
-    }
+pub(crate) mod find {
+    use crate::database::schema::membership::{
+        end_date, normalized_last_name_first_name, normalized_membership_number,
+    };
+    use diesel::ExpressionMethods;
+    use diesel::dsl::{Asc, Desc};
 
-    fn get_order() -> (
+    pub(super) fn get_order() -> (
         Desc<end_date>,
         Asc<normalized_membership_number>,
         Asc<normalized_last_name_first_name>,
@@ -215,38 +322,117 @@ pub(crate) mod find {
 
     pub(crate) mod all {
         use super::super::Result;
+        use crate::database::DatabaseConnection;
+        use crate::database::dao::MAX_QUERY_PARAMETERS;
         use crate::database::dao::membership::find::get_order;
         use crate::database::model::membership::Membership;
         use crate::database::schema::membership::{
-            normalized_first_name, normalized_last_name, normalized_membership_number,
+            normalized_first_name, normalized_first_name_last_name, normalized_last_name,
+            normalized_last_name_first_name, normalized_membership_number,
         };
         use crate::tools::normalize;
         use diesel::{
-            ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper, SqliteConnection,
+            BoolExpressionMethods, EscapeExpressionMethods, ExpressionMethods, QueryDsl,
+            RunQueryDsl, SelectableHelper, TextExpressionMethods,
         };
-        use dto::member_to_look_up::MemberToLookUp;
-        use std::collections::BTreeSet;
+        use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
+        use std::collections::{BTreeMap, BTreeSet};
+
+        /// Memberships whose membership number is one of the given ones,
+        /// or which belong to someone whose names, in either order, make one of the given identities.
+        /// Both are expected to be normalized.
+        pub fn by_membership_numbers_or_identities(
+            connection: &mut DatabaseConnection,
+            membership_numbers: &BTreeSet<String>,
+            identities: &BTreeSet<String>,
+        ) -> Result<Vec<dto::membership::Membership>> {
+            // A membership may be found by several queries, so they are deduplicated by id
+            let mut results = BTreeMap::new();
+            let identities = identities.iter().collect::<Vec<_>>();
+            for chunk in identities.chunks(MAX_QUERY_PARAMETERS / 2) {
+                let chunk_results = crate::database::schema::membership::dsl::membership
+                    .filter(
+                        normalized_last_name_first_name
+                            .eq_any(chunk)
+                            .or(normalized_first_name_last_name.eq_any(chunk)),
+                    )
+                    .select(Membership::as_select())
+                    .load(connection)?;
+                results.extend(
+                    chunk_results
+                        .into_iter()
+                        .map(|result| (result.id(), result)),
+                );
+            }
+            // The other memberships of the people found by their names are wanted as well
+            let membership_numbers = membership_numbers
+                .iter()
+                .cloned()
+                .chain(results.values().map(|result| result.key().0))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            for chunk in membership_numbers.chunks(MAX_QUERY_PARAMETERS) {
+                let chunk_results = crate::database::schema::membership::dsl::membership
+                    .filter(normalized_membership_number.eq_any(chunk))
+                    .select(Membership::as_select())
+                    .load(connection)?;
+                results.extend(
+                    chunk_results
+                        .into_iter()
+                        .map(|result| (result.id(), result)),
+                );
+            }
+
+            results
+                .into_values()
+                .map(dto::membership::Membership::try_from)
+                .collect()
+        }
 
+        /// Exact lookup, or prefix lookup if asked for by the member to look up.
         pub fn by_member_to_lookup(
-            connection: &mut SqliteConnection,
+            connection: &mut DatabaseConnection,
             member_to_look_up: &MemberToLookUp,
         ) -> Result<BTreeSet<dto::membership::Membership>> {
+            let prefix = *member_to_look_up.mode() == LookUpMode::Prefix;
             let mut statement = crate::database::schema::membership::dsl::membership
                 .order(get_order())
                 .select(Membership::as_select())
                 .into_boxed();
 
             if let Some(membership_num) = member_to_look_up.membership_num() {
-                statement =
-                    statement.filter(normalized_membership_number.eq(normalize(membership_num)));
+                statement = if prefix {
+                    statement.filter(
+                        normalized_membership_number
+                            .like(prefix_pattern(membership_num))
+                            .escape('\\'),
+                    )
+                } else {
+                    statement.filter(normalized_membership_number.eq(normalize(membership_num)))
+                };
             }
             if let Some(searched_last_name) = member_to_look_up.last_name() {
-                statement =
-                    statement.filter(normalized_last_name.eq(normalize(searched_last_name)));
+                statement = if prefix {
+                    statement.filter(
+                        normalized_last_name
+                            .like(prefix_pattern(searched_last_name))
+                            .escape('\\'),
+                    )
+                } else {
+                    statement.filter(normalized_last_name.eq(normalize(searched_last_name)))
+                };
             }
             if let Some(searched_first_name) = member_to_look_up.first_name() {
-                statement =
-                    statement.filter(normalized_first_name.eq(normalize(searched_first_name)));
+                statement = if prefix {
+                    statement.filter(
+                        normalized_first_name
+                            .like(prefix_pattern(searched_first_name))
+                            .escape('\\'),
+                    )
+                } else {
+                    statement.filter(normalized_first_name.eq(normalize(searched_first_name)))
+                };
             }
 
             let results = statement.load(connection)?;
@@ -254,6 +440,15 @@ pub(crate) mod find {
             convert_to_dto(results)
         }
 
+        /// A LIKE pattern matching all values starting with the normalized value.
+        fn prefix_pattern(value: &str) -> String {
+            let escaped_value = normalize(value)
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("{escaped_value}%")
+        }
+
         fn convert_to_dto(
             results: Vec<Membership>,
         ) -> Result<BTreeSet<dto::membership::Membership>> {
@@ -268,72 +463,6 @@ pub(crate) mod find {
             })
         }
     }
-
-    pub(crate) mod first {
-        use super::super::Result;
-        use crate::database::model::membership::Membership;
-        use diesel::SqliteConnection;
-
-        pub fn by_num(
-            connection: &mut SqliteConnection,
-            membership_number: &str,
-        ) -> Result<Option<dto::membership::Membership>> {
-            let results = super::by_num(connection, membership_number, Some(1))?;
-            convert_to_dto(results)
-        }
-
-        pub fn by_num_identity(
-            connection: &mut SqliteConnection,
-            membership_number: &str,
-            identity: &str,
-        ) -> Result<Option<dto::membership::Membership>> {
-            let results = super::by_num_identity(connection, membership_number, identity, Some(1))?;
-            convert_to_dto(results)
-        }
-
-        pub fn by_num_last_name_first_name(
-            connection: &mut SqliteConnection,
-            membership_number: &str,
-            last_name: &str,
-            first_name: &str,
-        ) -> Result<Option<dto::membership::Membership>> {
-            let results = super::by_num_last_name_first_name(
-                connection,
-                membership_number,
-                last_name,
-                first_name,
-                Some(1),
-            )?;
-            convert_to_dto(results)
-        }
-
-        pub fn by_identity(
-            connection: &mut SqliteConnection,
-            identity: &str,
-        ) -> Result<Option<dto::membership::Membership>> {
-            let results = super::by_identity(connection, identity, Some(1))?;
-            convert_to_dto(results)
-        }
-
-        pub fn by_last_name_first_name(
-            connection: &mut SqliteConnection,
-            last_name: &str,
-            first_name: &str,
-        ) -> Result<Option<dto::membership::Membership>> {
-            let results =
-                super::by_last_name_first_name(connection, last_name, first_name, Some(1))?;
-
-            convert_to_dto(results)
-        }
-
-        fn convert_to_dto(results: Vec<Membership>) -> Result<Option<dto::membership::Membership>> {
-            if let Some(membership) = results.first().cloned() {
-                Ok(Some(dto::membership::Membership::try_from(membership)?))
-            } else {
-                Ok(None)
-            }
-        }
-    }
 }
 
 #[cfg(test)]
@@ -343,7 +472,7 @@ mod tests {
     use crate::tools::normalize;
     use diesel::prelude::*;
 
-    fn populate_db(connection: &mut SqliteConnection) -> Vec<dto::membership::Membership> {
+    fn populate_db(connection: &mut DatabaseConnection) -> Vec<dto::membership::Membership> {
         let expected_memberships = vec![jon_doe(), jonette_snow()];
         let memberships = expected_memberships
             .clone()
@@ -402,8 +531,8 @@ mod tests {
         }
     }
 
-    mod delete_all {
-        use crate::database::dao::membership::delete_all;
+    mod count {
+        use crate::database::dao::membership::count;
         use crate::database::dao::membership::tests::populate_db;
         use crate::database::with_temp_database;
 
@@ -413,24 +542,65 @@ mod tests {
                 let mut connection = pool.get().unwrap();
                 let expected_memberships = populate_db(&mut connection);
 
-                let result = delete_all(&mut connection).unwrap();
-                assert_eq!(expected_memberships.len(), result);
+                assert_eq!(expected_memberships.len(), count(&mut connection).unwrap());
+            })
+        }
+
+        #[test]
+        fn success_when_empty() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                assert_eq!(0, count(&mut connection).unwrap());
+            })
+        }
+    }
+
+    mod delete_all_by_id {
+        use crate::database::dao::membership::delete_all_by_id;
+        use crate::database::dao::membership::retrieve_memberships;
+        use crate::database::dao::membership::tests::populate_db;
+        use crate::database::model::membership::Membership;
+        use crate::database::with_temp_database;
+        use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let expected_memberships = populate_db(&mut connection);
+                let first_id = crate::database::schema::membership::dsl::membership
+                    .select(Membership::as_select())
+                    .first(&mut connection)
+                    .unwrap()
+                    .id();
+
+                let result = delete_all_by_id(&mut connection, &[first_id]).unwrap();
+                assert_eq!(1, result);
+                assert_eq!(
+                    expected_memberships[1..].to_vec(),
+                    retrieve_memberships(&mut connection).unwrap()
+                );
             })
         }
 
         #[test]
-        fn success_when_already_empty() {
+        fn success_when_no_id() {
             with_temp_database(|pool| {
                 let mut connection = pool.get().unwrap();
+                let expected_memberships = populate_db(&mut connection);
 
-                let result = delete_all(&mut connection).unwrap();
+                let result = delete_all_by_id(&mut connection, &[]).unwrap();
                 assert_eq!(0, result);
+                assert_eq!(
+                    expected_memberships,
+                    retrieve_memberships(&mut connection).unwrap()
+                );
             })
         }
     }
 
     mod insert_all {
-        use crate::database::dao::last_update::{UpdatableElement, get_last_update};
         use crate::database::dao::membership::insert_all;
         use crate::database::model::membership::Membership;
         use crate::database::with_temp_database;
@@ -460,9 +630,6 @@ mod tests {
                 };
 
                 assert_eq!(expected_memberships, memberships);
-                get_last_update(&mut connection, &UpdatableElement::Memberships)
-                    .unwrap()
-                    .unwrap(); // The last_update table should have been updated
             })
         }
 
@@ -491,6 +658,8 @@ mod tests {
                         Utc::now().date_naive(),
                         i.to_string(),
                         i.to_string(),
+                        None,
+                        None,
                     )
                 })
                 .collect::<Vec<_>>();
@@ -500,12 +669,16 @@ mod tests {
 
     mod replace_memberships {
         use crate::database::dao::last_update::{UpdatableElement, get_last_update};
-        use crate::database::dao::membership::replace_memberships;
         use crate::database::dao::membership::tests::populate_db;
-        use crate::database::model::membership::Membership;
+        use crate::database::dao::membership::{replace_memberships, retrieve_memberships};
+        use crate::database::dao::membership_import::retrieve_last_diff;
         use crate::database::with_temp_database;
-        use crate::membership::tests::{jon_doe_previous_membership, other_jon_doe};
-        use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+        use crate::membership::tests::{
+            jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
+        };
+        use chrono::NaiveDate;
+        use dto::membership::Membership;
+        use dto::membership_diff::{MembershipChange, MembershipChangeKind};
 
         #[test]
         fn success() {
@@ -516,28 +689,130 @@ mod tests {
 
                 let result = replace_memberships(&mut connection, &expected_memberships).unwrap();
                 assert_eq!(
-                    (initial_memberships.len(), expected_memberships.len()),
-                    result
+                    expected_memberships.len(),
+                    result.count(MembershipChangeKind::Added)
                 );
+                assert_eq!(
+                    initial_memberships.len(),
+                    result.count(MembershipChangeKind::Removed)
+                );
+                assert_eq!(0, result.count(MembershipChangeKind::Changed));
 
-                let results = crate::database::schema::membership::dsl::membership
-                    .select(Membership::as_select())
-                    .load(&mut connection)
-                    .unwrap();
-
-                let memberships = {
-                    let mut memberships = Vec::new();
-                    for result in results {
-                        memberships.push(dto::membership::Membership::try_from(result).unwrap());
-                    }
-
-                    memberships
-                };
-
+                let memberships = retrieve_memberships(&mut connection).unwrap();
                 assert_eq!(expected_memberships, memberships);
                 get_last_update(&mut connection, &UpdatableElement::Memberships)
                     .unwrap()
                     .unwrap(); // The last_update table should have been updated
+                assert_eq!(Some(result), retrieve_last_diff(&mut connection).unwrap());
+            })
+        }
+
+        #[test]
+        fn success_when_unchanged() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let initial_memberships = populate_db(&mut connection);
+
+                let result = replace_memberships(&mut connection, &initial_memberships).unwrap();
+                assert!(result.changes().is_empty());
+                assert_eq!(
+                    initial_memberships,
+                    retrieve_memberships(&mut connection).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn success_with_changes() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                populate_db(&mut connection);
+
+                let previous_jon_doe = jon_doe();
+                let renewed_jon_doe = Membership::new(
+                    previous_jon_doe.name().clone(),
+                    previous_jon_doe.first_name().clone(),
+                    *previous_jon_doe.birthdate(),
+                    previous_jon_doe.membership_number().clone(),
+                    previous_jon_doe.cell_number().clone(),
+                    "jon.doe@new-address.com".to_owned(),
+                    *previous_jon_doe.start_date(),
+                    NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+                    "Another club".to_owned(),
+                    previous_jon_doe.structure_code().clone(),
+                    None,
+                    None,
+                );
+                let new_memberships = vec![renewed_jon_doe.clone(), jonette_snow()];
+
+                let result = replace_memberships(&mut connection, &new_memberships).unwrap();
+                assert_eq!(
+                    &vec![MembershipChange::between(&previous_jon_doe, &renewed_jon_doe).unwrap()],
+                    result.changes()
+                );
+                assert_eq!(
+                    new_memberships,
+                    retrieve_memberships(&mut connection).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn success_when_duplicated() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                let result = replace_memberships(&mut connection, &[jon_doe(), jon_doe()]).unwrap();
+                assert_eq!(vec![MembershipChange::added(&jon_doe())], *result.changes());
+                assert_eq!(
+                    vec![jon_doe()],
+                    retrieve_memberships(&mut connection).unwrap()
+                );
+            })
+        }
+    }
+
+    mod pseudonymize_ended_before {
+        use crate::database::dao::membership::tests::populate_db;
+        use crate::database::dao::membership::{pseudonymize_ended_before, retrieve_memberships};
+        use crate::database::with_temp_database;
+        use crate::membership::tests::{jon_doe, jonette_snow};
+        use chrono::NaiveDate;
+        use dto::membership::Membership;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                populate_db(&mut connection);
+                let date = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+
+                let result = pseudonymize_ended_before(&mut connection, &date).unwrap();
+                assert_eq!(vec![jon_doe().membership_number().to_owned()], result);
+
+                let jon_doe = jon_doe();
+                let pseudonymized_jon_doe = Membership::new(
+                    jon_doe.name().to_owned(),
+                    jon_doe.first_name().to_owned(),
+                    None,
+                    jon_doe.membership_number().to_owned(),
+                    None,
+                    String::new(),
+                    *jon_doe.start_date(),
+                    *jon_doe.end_date(),
+                    jon_doe.club().to_owned(),
+                    jon_doe.structure_code().to_owned(),
+                    None,
+                    None,
+                );
+                assert_eq!(
+                    vec![pseudonymized_jon_doe, jonette_snow()],
+                    retrieve_memberships(&mut connection).unwrap()
+                );
+
+                // Already pseudonymized memberships are left untouched
+                let result = pseudonymize_ended_before(&mut connection, &date).unwrap();
+                assert!(result.is_empty());
             })
         }
     }
@@ -551,7 +826,7 @@ mod tests {
                 use crate::membership::tests::{
                     jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
                 };
-                use dto::member_to_look_up::MemberToLookUp;
+                use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
                 use std::collections::BTreeSet;
 
                 #[test]
@@ -574,6 +849,8 @@ mod tests {
                             Some(jon_doe().membership_number().to_owned()),
                             Some(jon_doe().name().to_owned()),
                             Some(jon_doe().first_name().to_owned()),
+                            None,
+                            LookUpMode::Exact,
                         );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
@@ -604,6 +881,8 @@ mod tests {
                             Some(jon_doe().membership_number().to_owned()),
                             Some(jon_doe().name().to_owned()),
                             None,
+                            None,
+                            LookUpMode::Exact,
                         );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
@@ -634,6 +913,8 @@ mod tests {
                             Some(jon_doe().membership_number().to_owned()),
                             None,
                             Some(jon_doe().first_name().to_owned()),
+                            None,
+                            LookUpMode::Exact,
                         );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
@@ -664,6 +945,8 @@ mod tests {
                             None,
                             Some(jon_doe().name().to_owned()),
                             Some(jon_doe().first_name().to_owned()),
+                            None,
+                            LookUpMode::Exact,
                         );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
@@ -698,6 +981,8 @@ mod tests {
                             Some(jon_doe().membership_number().to_owned()),
                             None,
                             None,
+                            None,
+                            LookUpMode::Exact,
                         );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
@@ -724,8 +1009,13 @@ mod tests {
                         )
                         .unwrap();
 
-                        let member_to_look_up =
-                            MemberToLookUp::new(None, Some(jon_doe().name().to_owned()), None);
+                        let member_to_look_up = MemberToLookUp::new(
+                            None,
+                            Some(jon_doe().name().to_owned()),
+                            None,
+                            None,
+                            LookUpMode::Exact,
+                        );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
                         assert_eq!(
@@ -759,6 +1049,8 @@ mod tests {
                             None,
                             None,
                             Some(jon_doe().first_name().to_owned()),
+                            None,
+                            LookUpMode::Exact,
                         );
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
@@ -789,7 +1081,8 @@ mod tests {
                         )
                         .unwrap();
 
-                        let member_to_look_up = MemberToLookUp::new(None, None, None);
+                        let member_to_look_up =
+                            MemberToLookUp::new(None, None, None, None, LookUpMode::Exact);
                         let result =
                             by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
                         assert_eq!(
@@ -803,584 +1096,388 @@ mod tests {
                         );
                     })
                 }
-            }
-        }
-
-        mod first {
-            mod by_num {
-                use crate::database::dao::membership::find::first::by_num;
-                use crate::database::dao::membership::insert_all;
-                use crate::database::with_temp_database;
-                use chrono::{Months, Utc};
 
                 #[test]
-                fn find_the_only_one() {
+                fn by_prefix() {
                     with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let membership = dto::membership::Membership::new(
-                            "Doe".to_owned(),
-                            "Jon".to_owned(),
-                            None,
-                            num.clone(),
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
                         let mut connection = pool.get().unwrap();
 
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
-
-                        let result = by_num(&mut connection, &num).unwrap().unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
-
-                #[test]
-                fn find_the_last_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let membership = dto::membership::Membership::new(
-                            "Doe".to_owned(),
-                            "Jon".to_owned(),
-                            None,
-                            num.clone(),
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
+                        insert_all(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
 
-                        let old_membership = dto::membership::Membership::new(
-                            "Doe".to_owned(),
-                            "Jon".to_owned(),
-                            None,
-                            num.clone(),
+                        let member_to_look_up = MemberToLookUp::new(
                             None,
-                            "address@test.com".to_owned(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone(), old_membership]).unwrap();
-
-                        let result = by_num(&mut connection, &num).unwrap().unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
-
-                #[test]
-                fn none_matching() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[]).unwrap();
-
-                        let result = by_num(&mut connection, &num).unwrap();
-                        assert_eq!(None, result);
-                    });
-                }
-            }
-
-            mod by_num_identity {
-                use crate::database::dao::membership::find::first::by_num_identity;
-                use crate::database::dao::membership::insert_all;
-                use crate::database::with_temp_database;
-                use chrono::{Months, Utc};
-
-                #[test]
-                fn find_the_only_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &first_name, &last_name);
-                        let membership = dto::membership::Membership::new(
-                            last_name,
-                            first_name,
                             None,
-                            num.clone(),
+                            Some("Jon".to_owned()),
                             None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
+                            LookUpMode::Prefix,
                         );
-
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
-
-                        let result = by_num_identity(&mut connection, &num, &identity)
-                            .unwrap()
-                            .unwrap();
-                        assert_eq!(membership, result);
-                    });
+                        let result =
+                            by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
+                        assert_eq!(BTreeSet::from([jon_doe(), jonette_snow()]), result);
+                    })
                 }
 
                 #[test]
-                fn find_the_only_one_by_reversed_identity() {
+                fn by_prefix_with_wildcard() {
                     with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &last_name, &first_name);
-                        let membership = dto::membership::Membership::new(
-                            last_name,
-                            first_name,
-                            None,
-                            num.clone(),
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
                         let mut connection = pool.get().unwrap();
 
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
-
-                        let result = by_num_identity(&mut connection, &num, &identity)
-                            .unwrap()
-                            .unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
+                        insert_all(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
 
-                #[test]
-                fn find_the_last_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &first_name, &last_name);
-                        let membership = dto::membership::Membership::new(
-                            first_name.clone(),
-                            last_name.clone(),
-                            None,
-                            num.clone(),
+                        let member_to_look_up = MemberToLookUp::new(
                             None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
-                        let old_membership = dto::membership::Membership::new(
-                            first_name.clone(),
-                            last_name.clone(),
                             None,
-                            num.clone(),
+                            Some("J%".to_owned()),
                             None,
-                            "address@test.com".to_owned(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
+                            LookUpMode::Prefix,
                         );
-
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone(), old_membership]).unwrap();
-
-                        let result = by_num_identity(&mut connection, &num, &identity)
-                            .unwrap()
-                            .unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
-
-                #[test]
-                fn none_matching() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &first_name, &last_name);
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[]).unwrap();
-
-                        let result = by_num_identity(&mut connection, &num, &identity).unwrap();
-                        assert_eq!(None, result);
-                    });
+                        let result =
+                            by_member_to_lookup(&mut connection, &member_to_look_up).unwrap();
+                        assert!(result.is_empty());
+                    })
                 }
             }
 
-            mod by_num_last_name_first_name {
-                use crate::database::dao::membership::find::first::by_num_last_name_first_name;
+            mod by_membership_numbers_or_identities {
+                use crate::database::dao::membership::find::all::by_membership_numbers_or_identities;
                 use crate::database::dao::membership::insert_all;
                 use crate::database::with_temp_database;
-                use chrono::{Months, Utc};
+                use crate::membership::tests::{
+                    jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
+                };
+                use chrono::Months;
+                use std::collections::BTreeSet;
 
                 #[test]
-                fn find_the_only_one() {
+                fn success() {
                     with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
-                            None,
-                            num.clone(),
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
                         let mut connection = pool.get().unwrap();
+                        insert_all(
+                            &mut connection,
+                            &[
+                                jon_doe(),
+                                jon_doe_previous_membership(),
+                                other_jon_doe(),
+                                jonette_snow(),
+                            ],
+                        )
+                        .unwrap();
 
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
-
-                        let result = by_num_last_name_first_name(
+                        let result = by_membership_numbers_or_identities(
                             &mut connection,
-                            &num,
-                            &last_name,
-                            &first_name,
+                            &BTreeSet::from(["654321".to_owned(), "42".to_owned()]),
+                            &BTreeSet::from(["jondoe".to_owned()]),
                         )
-                        .unwrap()
                         .unwrap();
-                        assert_eq!(membership, result);
-                    });
+                        assert_eq!(
+                            BTreeSet::from([
+                                jon_doe(),
+                                jon_doe_previous_membership(),
+                                other_jon_doe(),
+                                jonette_snow()
+                            ]),
+                            result.into_iter().collect::<BTreeSet<_>>()
+                        );
+                    })
                 }
 
                 #[test]
-                fn find_the_last_one() {
+                fn all_memberships_of_people_found_by_names() {
                     with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
+                        let mut connection = pool.get().unwrap();
+                        let renamed_membership = dto::membership::Membership::new(
+                            "Doe-Snow".to_owned(),
+                            "Jon".to_owned(),
                             None,
-                            num.clone(),
+                            jon_doe().membership_number().to_owned(),
                             None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
-                        let old_membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
+                            "".to_owned(),
+                            *jon_doe().end_date(),
+                            jon_doe()
+                                .end_date()
+                                .checked_add_months(Months::new(12))
+                                .unwrap(),
+                            "".to_owned(),
+                            "".to_owned(),
                             None,
-                            num.clone(),
                             None,
-                            "address@test.com".to_owned(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
                         );
+                        insert_all(
+                            &mut connection,
+                            &[jon_doe(), renamed_membership.clone(), jonette_snow()],
+                        )
+                        .unwrap();
 
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone(), old_membership]).unwrap();
-
-                        let result = by_num_last_name_first_name(
+                        let result = by_membership_numbers_or_identities(
                             &mut connection,
-                            &num,
-                            &last_name,
-                            &first_name,
+                            &BTreeSet::new(),
+                            &BTreeSet::from(["doejon".to_owned()]),
                         )
-                        .unwrap()
                         .unwrap();
-                        assert_eq!(membership, result);
-                    });
+                        assert_eq!(
+                            BTreeSet::from([jon_doe(), renamed_membership]),
+                            result.into_iter().collect::<BTreeSet<_>>()
+                        );
+                    })
                 }
 
                 #[test]
-                fn none_matching() {
+                fn none_when_nothing_searched() {
                     with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
                         let mut connection = pool.get().unwrap();
+                        insert_all(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
 
-                        insert_all(&mut connection, &[]).unwrap();
-
-                        let result = by_num_last_name_first_name(
+                        let result = by_membership_numbers_or_identities(
                             &mut connection,
-                            &num,
-                            &last_name,
-                            &first_name,
+                            &BTreeSet::new(),
+                            &BTreeSet::new(),
                         )
                         .unwrap();
-                        assert_eq!(None, result);
-                    });
+                        assert!(result.is_empty());
+                    })
                 }
             }
+        }
+    }
 
-            mod by_identity {
-                use crate::database::dao::membership::find::first::by_identity;
-                use crate::database::dao::membership::insert_all;
-                use crate::database::with_temp_database;
-                use chrono::{Months, Utc};
-
-                #[test]
-                fn find_the_only_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &first_name, &last_name);
-                        let membership = dto::membership::Membership::new(
-                            last_name,
-                            first_name,
-                            None,
-                            num,
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
-                        let mut connection = pool.get().unwrap();
+    /// The memberships index replaced queries filtering and sorting memberships in the database.
+    /// These queries are kept here, to make sure the index still finds the same memberships.
+    mod membership_index {
+        use crate::database::DatabaseConnection;
+        use crate::database::dao::membership::find::get_order;
+        use crate::database::dao::membership::{insert_all, retrieve_memberships};
+        use crate::database::model::membership::Membership;
+        use crate::database::schema::membership::{
+            normalized_first_name, normalized_first_name_last_name, normalized_last_name,
+            normalized_last_name_first_name, normalized_membership_number,
+        };
+        use crate::database::with_temp_database;
+        use crate::membership::index::MembershipIndex;
+        use crate::membership::tests::{
+            jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
+        };
+        use crate::tools::normalize;
+        use chrono::NaiveDate;
+        use diesel::{
+            BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper,
+        };
 
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
+        const MEMBERSHIP_NUMBERS: [&str; 6] = ["123456", "0123456", "654321", "897654", "42", ""];
+        const NAMES: [(&str, &str); 6] = [
+            ("Doe", "Jon"),
+            ("doe", "JON"),
+            ("Jon", "Doe"),
+            ("Snow", "Jonette"),
+            ("Do", "eJon"),
+            ("Smith", "John"),
+        ];
+        const IDENTITIES: [&str; 5] = [
+            "Doe Jon",
+            "jon-doe",
+            "Jonette Snow",
+            "Snöw Jonette",
+            "Smith",
+        ];
+
+        fn memberships() -> Vec<dto::membership::Membership> {
+            let membership = |name: &str, first_name: &str, number: &str, end_date: NaiveDate| {
+                dto::membership::Membership::new(
+                    name.to_owned(),
+                    first_name.to_owned(),
+                    None,
+                    number.to_owned(),
+                    None,
+                    "address@test.com".to_owned(),
+                    end_date.checked_sub_days(chrono::Days::new(365)).unwrap(),
+                    end_date,
+                    "club".to_owned(),
+                    "A12345".to_owned(),
+                    None,
+                    None,
+                )
+            };
+            vec![
+                jon_doe_previous_membership(),
+                jonette_snow(),
+                other_jon_doe(),
+                jon_doe(),
+                membership(
+                    "Jon",
+                    "Doe",
+                    "42",
+                    NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+                ),
+                membership(
+                    "Do",
+                    "Ejon",
+                    "123456",
+                    NaiveDate::from_ymd_opt(2022, 9, 30).unwrap(),
+                ),
+                membership(
+                    "Snöw",
+                    "Jonette",
+                    "0654321",
+                    NaiveDate::from_ymd_opt(2024, 9, 30).unwrap(),
+                ),
+            ]
+        }
 
-                        let result = by_identity(&mut connection, &identity).unwrap().unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
+        fn to_dto(results: Vec<Membership>) -> Vec<dto::membership::Membership> {
+            results
+                .into_iter()
+                .map(|result| dto::membership::Membership::try_from(result).unwrap())
+                .collect()
+        }
 
-                #[test]
-                fn find_the_only_one_by_reversed_identity() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &last_name, &first_name);
-                        let membership = dto::membership::Membership::new(
-                            last_name,
-                            first_name,
-                            None,
-                            num,
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
+        fn by_num(
+            connection: &mut DatabaseConnection,
+            membership_number: &str,
+        ) -> Vec<dto::membership::Membership> {
+            to_dto(
+                crate::database::schema::membership::dsl::membership
+                    .filter(normalized_membership_number.eq(normalize(membership_number)))
+                    .order(get_order())
+                    .select(Membership::as_select())
+                    .load(connection)
+                    .unwrap(),
+            )
+        }
 
-                        let mut connection = pool.get().unwrap();
+        fn by_num_identity(
+            connection: &mut DatabaseConnection,
+            membership_number: &str,
+            identity: &str,
+        ) -> Vec<dto::membership::Membership> {
+            let normalized_identity = normalize(identity);
+            to_dto(
+                crate::database::schema::membership::dsl::membership
+                    .filter(normalized_membership_number.eq(normalize(membership_number)))
+                    .filter(
+                        normalized_last_name_first_name
+                            .eq(&normalized_identity)
+                            .or(normalized_first_name_last_name.eq(&normalized_identity)),
+                    )
+                    .order(get_order())
+                    .select(Membership::as_select())
+                    .load(connection)
+                    .unwrap(),
+            )
+        }
 
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
+        fn by_num_last_name_first_name(
+            connection: &mut DatabaseConnection,
+            membership_number: &str,
+            last_name: &str,
+            first_name: &str,
+        ) -> Vec<dto::membership::Membership> {
+            to_dto(
+                crate::database::schema::membership::dsl::membership
+                    .filter(normalized_membership_number.eq(normalize(membership_number)))
+                    .filter(normalized_last_name.eq(normalize(last_name)))
+                    .filter(normalized_first_name.eq(normalize(first_name)))
+                    .order(get_order())
+                    .select(Membership::as_select())
+                    .load(connection)
+                    .unwrap(),
+            )
+        }
 
-                        let result = by_identity(&mut connection, &identity).unwrap().unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
+        fn by_identity(
+            connection: &mut DatabaseConnection,
+            identity: &str,
+        ) -> Vec<dto::membership::Membership> {
+            let normalized_identity = normalize(identity);
+            to_dto(
+                crate::database::schema::membership::dsl::membership
+                    .filter(
+                        normalized_last_name_first_name
+                            .eq(&normalized_identity)
+                            .or(normalized_first_name_last_name.eq(&normalized_identity)),
+                    )
+                    .order(get_order())
+                    .select(Membership::as_select())
+                    .load(connection)
+                    .unwrap(),
+            )
+        }
 
-                #[test]
-                fn find_the_last_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &first_name, &last_name);
-                        let membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
-                            None,
-                            num.clone(),
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
+        fn by_last_name_first_name(
+            connection: &mut DatabaseConnection,
+            last_name: &str,
+            first_name: &str,
+        ) -> Vec<dto::membership::Membership> {
+            to_dto(
+                crate::database::schema::membership::dsl::membership
+                    .filter(normalized_last_name.eq(normalize(last_name)))
+                    .filter(normalized_first_name.eq(normalize(first_name)))
+                    .order(get_order())
+                    .select(Membership::as_select())
+                    .load(connection)
+                    .unwrap(),
+            )
+        }
 
-                        let old_membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
-                            None,
-                            num,
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
+        #[test]
+        fn same_results_as_database_queries() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                insert_all(&mut connection, &memberships()).unwrap();
+                let index = MembershipIndex::new(retrieve_memberships(&mut connection).unwrap());
+
+                for membership_number in MEMBERSHIP_NUMBERS {
+                    assert_eq!(
+                        by_num(&mut connection, membership_number).first(),
+                        index.first_by_num(membership_number),
+                        "{membership_number}"
+                    );
+                    for (last_name, first_name) in NAMES {
+                        assert_eq!(
+                            by_num_last_name_first_name(
+                                &mut connection,
+                                membership_number,
+                                last_name,
+                                first_name
+                            )
+                            .first(),
+                            index.first_by_num_last_name_first_name(
+                                membership_number,
+                                last_name,
+                                first_name
+                            ),
+                            "{membership_number} {last_name} {first_name}"
                         );
-
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone(), old_membership]).unwrap();
-
-                        let result = by_identity(&mut connection, &identity).unwrap().unwrap();
-                        assert_eq!(membership, result);
-                    });
-                }
-
-                #[test]
-                fn none_matching() {
-                    with_temp_database(|pool| {
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let identity = format!("{}{}", &first_name, &last_name);
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[]).unwrap();
-
-                        let result = by_identity(&mut connection, &identity).unwrap();
-                        assert_eq!(None, result);
-                    });
-                }
-            }
-
-            mod by_last_name_first_name {
-                use crate::database::dao::membership::find::first::by_last_name_first_name;
-                use crate::database::dao::membership::insert_all;
-                use crate::database::with_temp_database;
-                use chrono::{Months, Utc};
-
-                #[test]
-                fn find_the_only_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
-                            None,
-                            num,
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
+                    }
+                    for identity in IDENTITIES {
+                        assert_eq!(
+                            by_num_identity(&mut connection, membership_number, identity).first(),
+                            index.first_by_num_identity(membership_number, identity),
+                            "{membership_number} {identity}"
                         );
-
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone()]).unwrap();
-
-                        let result =
-                            by_last_name_first_name(&mut connection, &last_name, &first_name)
-                                .unwrap()
-                                .unwrap();
-                        assert_eq!(membership, result);
-                    });
+                    }
                 }
-
-                #[test]
-                fn find_the_last_one() {
-                    with_temp_database(|pool| {
-                        let num = "123456".to_owned();
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
-                            None,
-                            num.clone(),
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now().date_naive(),
-                            Utc::now().date_naive(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
-                        let old_membership = dto::membership::Membership::new(
-                            last_name.clone(),
-                            first_name.clone(),
-                            None,
-                            num,
-                            None,
-                            "address@test.com".to_owned(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            Utc::now()
-                                .date_naive()
-                                .checked_sub_months(Months::new(12))
-                                .unwrap(),
-                            "club".to_owned(),
-                            "A12345".to_owned(),
-                        );
-
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[membership.clone(), old_membership]).unwrap();
-
-                        let result =
-                            by_last_name_first_name(&mut connection, &last_name, &first_name)
-                                .unwrap()
-                                .unwrap();
-                        assert_eq!(membership, result);
-                    });
+                for (last_name, first_name) in NAMES {
+                    assert_eq!(
+                        by_last_name_first_name(&mut connection, last_name, first_name),
+                        index
+                            .by_last_name_first_name(last_name, first_name)
+                            .into_iter()
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                        "{last_name} {first_name}"
+                    );
                 }
-
-                #[test]
-                fn none_matching() {
-                    with_temp_database(|pool| {
-                        let first_name = "Jon".to_owned();
-                        let last_name = "Doe".to_owned();
-                        let mut connection = pool.get().unwrap();
-
-                        insert_all(&mut connection, &[]).unwrap();
-
-                        let result =
-                            by_last_name_first_name(&mut connection, &last_name, &first_name)
-                                .unwrap();
-                        assert_eq!(None, result);
-                    });
+                for identity in IDENTITIES {
+                    assert_eq!(
+                        by_identity(&mut connection, identity),
+                        index
+                            .by_identity(identity)
+                            .into_iter()
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                        "{identity}"
+                    );
                 }
-            }
+            });
         }
     }
 }
@@ -0,0 +1,257 @@
+use super::membership::{encrypt_birthdate, encrypt_cell_number};
+use super::{MAX_QUERY_PARAMETERS, Result};
+use crate::database::DatabaseConnection;
+use crate::database::encryption::{any_plain_text, encrypt, encrypt_plain_text};
+use crate::database::model::membership_history::MembershipHistory;
+use crate::tools::normalize;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use dto::member_to_look_up::MemberToLookUp;
+use std::collections::{BTreeSet, HashSet};
+
+/// Archive new versions of memberships, as they have been imported by the given import.
+pub(super) fn insert_all(
+    connection: &mut DatabaseConnection,
+    recorded_import_id: i32,
+    memberships: &[dto::membership::Membership],
+) -> Result<usize> {
+    use crate::database::schema::membership_history::*;
+
+    let memberships = memberships
+        .iter()
+        .map(|membership| {
+            Ok((
+                import_id.eq(recorded_import_id),
+                last_name.eq(membership.name().clone()),
+                first_name.eq(membership.first_name().clone()),
+                birthdate.eq(encrypt_birthdate(membership)?),
+                membership_number.eq(membership.membership_number().clone()),
+                cell_number.eq(encrypt_cell_number(membership)?),
+                email_address.eq(encrypt(membership.email_address())?),
+                start_date.eq(membership.start_date().to_string()),
+                end_date.eq(membership.end_date().to_string()),
+                club.eq(membership.club().clone()),
+                structure_code.eq(membership.structure_code().clone()),
+                normalized_membership_number.eq(normalize(membership.membership_number())),
+                normalized_last_name.eq(normalize(membership.name())),
+                normalized_first_name.eq(normalize(membership.first_name())),
+                normalized_last_name_first_name.eq(format!(
+                    "{}{}",
+                    normalize(membership.name()),
+                    normalize(membership.first_name()),
+                )),
+                normalized_first_name_last_name.eq(format!(
+                    "{}{}",
+                    normalize(membership.first_name()),
+                    normalize(membership.name()),
+                )),
+                membership_type.eq(membership.membership_type().clone()),
+                tariff.eq(membership.tariff().clone()),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // As each line has 18 parameters, we have a theoretic maximum of 32 766 / 18 = 1820,3.
+    let mut count = 0;
+    for chunk in memberships.chunks(MAX_QUERY_PARAMETERS / 18) {
+        count += diesel::insert_into(crate::database::schema::membership_history::table)
+            .values(chunk)
+            .execute(connection)?;
+    }
+
+    Ok(count)
+}
+
+/// Encrypt the personal data of the archived memberships stored in plain text.
+/// Return the number of updated archived memberships.
+pub(crate) fn encrypt_plain_text_values(connection: &mut DatabaseConnection) -> Result<usize> {
+    use crate::database::schema::membership_history::*;
+
+    let memberships = table
+        .select((id, birthdate, cell_number, email_address))
+        .load::<(i32, Option<String>, Option<String>, String)>(connection)?;
+
+    let mut count = 0;
+    for (membership_id, stored_birthdate, stored_cell_number, stored_email_address) in memberships {
+        if !any_plain_text([
+            stored_birthdate.as_deref(),
+            stored_cell_number.as_deref(),
+            Some(stored_email_address.as_str()),
+        ]) {
+            continue;
+        }
+
+        count += diesel::update(table)
+            .filter(id.eq(membership_id))
+            .set((
+                birthdate.eq(stored_birthdate.map(encrypt_plain_text).transpose()?),
+                cell_number.eq(stored_cell_number.map(encrypt_plain_text).transpose()?),
+                email_address.eq(encrypt_plain_text(stored_email_address)?),
+            ))
+            .execute(connection)?;
+    }
+
+    Ok(count)
+}
+
+/// Look up all archived memberships matching given information that were valid on given date.
+/// When a membership has been imported several times with different values,
+/// only its most recent version is considered.
+pub fn find_valid_on(
+    connection: &mut DatabaseConnection,
+    member_to_look_up: &MemberToLookUp,
+    date: &NaiveDate,
+) -> Result<BTreeSet<dto::membership::Membership>> {
+    use crate::database::schema::membership_history::*;
+
+    let mut statement = crate::database::schema::membership_history::dsl::membership_history
+        .order(id.desc())
+        .select(MembershipHistory::as_select())
+        .into_boxed();
+
+    if let Some(membership_num) = member_to_look_up.membership_num() {
+        statement = statement.filter(normalized_membership_number.eq(normalize(membership_num)));
+    }
+    if let Some(searched_last_name) = member_to_look_up.last_name() {
+        statement = statement.filter(normalized_last_name.eq(normalize(searched_last_name)));
+    }
+    if let Some(searched_first_name) = member_to_look_up.first_name() {
+        statement = statement.filter(normalized_first_name.eq(normalize(searched_first_name)));
+    }
+
+    let results = statement.load(connection)?;
+
+    let mut known_keys = HashSet::new();
+    let mut memberships = BTreeSet::new();
+    for result in results {
+        if !known_keys.insert(result.key()) {
+            continue; // A more recent version has already been considered
+        }
+
+        let membership = dto::membership::Membership::try_from(result)?;
+        if membership.start_date() <= date && date <= membership.end_date() {
+            memberships.insert(membership);
+        }
+    }
+
+    Ok(memberships)
+}
+
+/// Erase the personal data (email address, cell number and birthdate)
+/// of the archived memberships that ended before the given date.
+/// Other fields are kept, for statistics.
+/// Return the membership numbers of the archived memberships that have been pseudonymized.
+pub fn pseudonymize_ended_before(
+    connection: &mut DatabaseConnection,
+    date: &NaiveDate,
+) -> Result<Vec<String>> {
+    use crate::database::schema::membership_history::*;
+
+    let membership_numbers = diesel::update(crate::database::schema::membership_history::table)
+        .filter(end_date.lt(date.to_string()))
+        .filter(
+            email_address
+                .ne("")
+                .or(cell_number.is_not_null())
+                .or(birthdate.is_not_null()),
+        )
+        .set((
+            email_address.eq(""),
+            cell_number.eq(None::<String>),
+            birthdate.eq(None::<String>),
+        ))
+        .returning(membership_number)
+        .get_results(connection)?;
+
+    Ok(membership_numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    mod find_valid_on {
+        use crate::database::dao::membership::replace_memberships;
+        use crate::database::dao::membership_history::find_valid_on;
+        use crate::database::with_temp_database;
+        use crate::membership::tests::{jon_doe, jon_doe_previous_membership, jonette_snow};
+        use chrono::NaiveDate;
+        use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
+        use dto::membership::Membership;
+        use std::collections::BTreeSet;
+
+        #[test]
+        fn success_when_membership_no_longer_imported() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe_previous_membership()]).unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
+
+                let member_to_look_up = MemberToLookUp::new(
+                    Some(jon_doe().membership_number().to_owned()),
+                    None,
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
+                let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+                let result = find_valid_on(&mut connection, &member_to_look_up, &date).unwrap();
+
+                assert_eq!(BTreeSet::from([jon_doe_previous_membership()]), result);
+            })
+        }
+
+        #[test]
+        fn success_with_most_recent_version() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe()]).unwrap();
+                let jon_doe = jon_doe();
+                let extended_jon_doe = Membership::new(
+                    jon_doe.name().clone(),
+                    jon_doe.first_name().clone(),
+                    *jon_doe.birthdate(),
+                    jon_doe.membership_number().clone(),
+                    jon_doe.cell_number().clone(),
+                    jon_doe.email_address().clone(),
+                    *jon_doe.start_date(),
+                    NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+                    jon_doe.club().clone(),
+                    jon_doe.structure_code().clone(),
+                    None,
+                    None,
+                );
+                replace_memberships(&mut connection, &[extended_jon_doe.clone()]).unwrap();
+
+                let member_to_look_up = MemberToLookUp::new(
+                    None,
+                    Some(jon_doe.name().to_owned()),
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
+                let date = NaiveDate::from_ymd_opt(2025, 11, 1).unwrap();
+                let result = find_valid_on(&mut connection, &member_to_look_up, &date).unwrap();
+
+                assert_eq!(BTreeSet::from([extended_jon_doe]), result);
+            })
+        }
+
+        #[test]
+        fn empty_when_no_membership_was_valid() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
+
+                let member_to_look_up = MemberToLookUp::new(
+                    Some(jonette_snow().membership_number().to_owned()),
+                    None,
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
+                let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+                let result = find_valid_on(&mut connection, &member_to_look_up, &date).unwrap();
+
+                assert_eq!(BTreeSet::<Membership>::new(), result);
+            })
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use super::{MAX_QUERY_PARAMETERS, Result};
+use crate::database::DatabaseConnection;
+use crate::database::encryption::{any_plain_text, encrypt, encrypt_plain_text, is_plain_text};
+use crate::database::model::membership_change::MembershipChange;
+use crate::database::model::membership_import::MembershipImport;
+use crate::database::model::membership_rejected_row::MembershipRejectedRow;
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use dto::import_report::{ImportReport, RejectedRow};
+use dto::membership_diff::MembershipsDiff;
+
+/// Record a memberships import along with all the changes it brought.
+/// Return the id of the newly recorded import.
+pub(super) fn insert(
+    connection: &mut DatabaseConnection,
+    import_date: &NaiveDateTime,
+    changes: &[dto::membership_diff::MembershipChange],
+) -> Result<i32> {
+    use crate::database::schema::membership_change::*;
+
+    let recorded_import_id = diesel::insert_into(crate::database::schema::membership_import::table)
+        .values(crate::database::schema::membership_import::date.eq(import_date.to_string()))
+        .returning(crate::database::schema::membership_import::id)
+        .get_result::<i32>(connection)?;
+
+    let changes = changes
+        .iter()
+        .map(|change| {
+            Ok((
+                import_id.eq(recorded_import_id),
+                kind.eq(change.kind().to_string()),
+                membership_number.eq(change.membership_number().clone()),
+                last_name.eq(change.name().clone()),
+                first_name.eq(change.first_name().clone()),
+                start_date.eq(change.start_date().to_string()),
+                previous_email_address.eq(change
+                    .previous_email_address()
+                    .as_deref()
+                    .map(encrypt)
+                    .transpose()?),
+                email_address.eq(change.email_address().as_deref().map(encrypt).transpose()?),
+                previous_end_date.eq(change.previous_end_date().map(|date| date.to_string())),
+                end_date.eq(change.end_date().map(|date| date.to_string())),
+                previous_club.eq(change.previous_club().clone()),
+                club.eq(change.club().clone()),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // As each line has 12 parameters, we have a theoretic maximum of 32 766 / 12 = 2730,5.
+    for chunk in changes.chunks(MAX_QUERY_PARAMETERS / 12) {
+        diesel::insert_into(crate::database::schema::membership_change::table)
+            .values(chunk)
+            .execute(connection)?;
+    }
+
+    Ok(recorded_import_id)
+}
+
+/// Attach the rows that couldn't be read to the most recent memberships import.
+/// Should be called right after said import has been recorded, in the same transaction.
+pub fn insert_rejected_rows(
+    connection: &mut DatabaseConnection,
+    rejected_rows: &[RejectedRow],
+) -> Result<usize> {
+    use crate::database::schema::membership_rejected_row::*;
+
+    let Some(last_import) = retrieve_last_import(connection)? else {
+        return Ok(0);
+    };
+
+    let rejected_rows = rejected_rows
+        .iter()
+        .map(|rejected_row| {
+            Ok((
+                import_id.eq(last_import.id()),
+                line.eq(*rejected_row.line() as i64),
+                // Raw records hold personal data, such as email addresses and birthdates
+                record.eq(encrypt(rejected_row.record())?),
+                reason.eq(rejected_row.reason().clone()),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // As each line has 4 parameters, we have a theoretic maximum of 32 766 / 4 = 8191,5.
+    let mut count = 0;
+    for chunk in rejected_rows.chunks(MAX_QUERY_PARAMETERS / 4) {
+        count += diesel::insert_into(crate::database::schema::membership_rejected_row::table)
+            .values(chunk)
+            .execute(connection)?;
+    }
+
+    Ok(count)
+}
+
+/// Retrieve the rows that couldn't be read during the most recent memberships import, if any.
+pub fn retrieve_last_report(connection: &mut DatabaseConnection) -> Result<Option<ImportReport>> {
+    let Some(last_import) = retrieve_last_import(connection)? else {
+        return Ok(None);
+    };
+
+    let rejected_rows =
+        crate::database::schema::membership_rejected_row::dsl::membership_rejected_row
+            .filter(
+                crate::database::schema::membership_rejected_row::import_id.eq(last_import.id()),
+            )
+            .order(crate::database::schema::membership_rejected_row::line.asc())
+            .select(MembershipRejectedRow::as_select())
+            .load(connection)?
+            .into_iter()
+            .map(RejectedRow::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(ImportReport::new(rejected_rows)))
+}
+
+fn retrieve_last_import(connection: &mut DatabaseConnection) -> Result<Option<MembershipImport>> {
+    use crate::database::schema::membership_import::dsl::{id, membership_import};
+
+    Ok(membership_import
+        .order(id.desc())
+        .select(MembershipImport::as_select())
+        .first(connection)
+        .optional()?)
+}
+
+/// Retrieve the changes brought by the most recent memberships import, if any.
+pub fn retrieve_last_diff(connection: &mut DatabaseConnection) -> Result<Option<MembershipsDiff>> {
+    let Some(last_import) = retrieve_last_import(connection)? else {
+        return Ok(None);
+    };
+
+    let results = crate::database::schema::membership_change::dsl::membership_change
+        .filter(crate::database::schema::membership_change::import_id.eq(last_import.id()))
+        .order(crate::database::schema::membership_change::id.asc())
+        .select(MembershipChange::as_select())
+        .load(connection)?;
+
+    let changes = {
+        let mut changes = Vec::new();
+        for result in results {
+            changes.push(dto::membership_diff::MembershipChange::try_from(result)?);
+        }
+
+        changes
+    };
+
+    Ok(Some(MembershipsDiff::new(last_import.date()?, changes)))
+}
+
+/// Erase the email addresses of the changes and the raw rejected rows
+/// recorded along with the imports made before the given date.
+/// Other fields are kept, so that import reports can still be read.
+/// Return the number of pseudonymized rows.
+pub fn pseudonymize_imported_before(
+    connection: &mut DatabaseConnection,
+    date: &NaiveDate,
+) -> Result<usize> {
+    let import_ids = crate::database::schema::membership_import::table
+        .filter(crate::database::schema::membership_import::date.lt(date.to_string()))
+        .select(crate::database::schema::membership_import::id)
+        .load::<i32>(connection)?;
+
+    let mut count = 0;
+    for chunk in import_ids.chunks(MAX_QUERY_PARAMETERS) {
+        {
+            use crate::database::schema::membership_change::*;
+
+            count += diesel::update(table)
+                .filter(import_id.eq_any(chunk))
+                .filter(
+                    previous_email_address
+                        .is_not_null()
+                        .or(email_address.is_not_null()),
+                )
+                .set((
+                    previous_email_address.eq(None::<String>),
+                    email_address.eq(None::<String>),
+                ))
+                .execute(connection)?;
+        }
+        {
+            use crate::database::schema::membership_rejected_row::*;
+
+            count += diesel::update(table)
+                .filter(import_id.eq_any(chunk))
+                .filter(record.ne(""))
+                .set(record.eq(""))
+                .execute(connection)?;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Encrypt the email addresses of the changes and the rejected rows stored in plain text.
+/// Return the number of updated rows.
+pub(crate) fn encrypt_plain_text_values(connection: &mut DatabaseConnection) -> Result<usize> {
+    let mut count = 0;
+
+    {
+        use crate::database::schema::membership_change::*;
+
+        let changes = table
+            .select((id, previous_email_address, email_address))
+            .load::<(i32, Option<String>, Option<String>)>(connection)?;
+        for (change_id, stored_previous_email_address, stored_email_address) in changes {
+            if !any_plain_text([
+                stored_previous_email_address.as_deref(),
+                stored_email_address.as_deref(),
+            ]) {
+                continue;
+            }
+
+            count += diesel::update(table)
+                .filter(id.eq(change_id))
+                .set((
+                    previous_email_address.eq(stored_previous_email_address
+                        .map(encrypt_plain_text)
+                        .transpose()?),
+                    email_address.eq(stored_email_address.map(encrypt_plain_text).transpose()?),
+                ))
+                .execute(connection)?;
+        }
+    }
+
+    {
+        use crate::database::schema::membership_rejected_row::*;
+
+        let rejected_rows = table
+            .select((id, record))
+            .load::<(i32, String)>(connection)?;
+        for (rejected_row_id, stored_record) in rejected_rows {
+            if !is_plain_text(&stored_record) {
+                continue;
+            }
+
+            count += diesel::update(table)
+                .filter(id.eq(rejected_row_id))
+                .set(record.eq(encrypt_plain_text(stored_record)?))
+                .execute(connection)?;
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_last_diff {
+        use crate::database::dao::membership_import::{insert, retrieve_last_diff};
+        use crate::database::with_temp_database;
+        use crate::membership::tests::{jon_doe, jonette_snow};
+        use chrono::Utc;
+        use dto::membership_diff::{MembershipChange, MembershipsDiff};
+
+        #[test]
+        fn none_when_never_imported() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                assert_eq!(None, retrieve_last_diff(&mut connection).unwrap());
+            })
+        }
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                let first_date = Utc::now().naive_local();
+                let first_changes = vec![MembershipChange::added(&jon_doe())];
+                insert(&mut connection, &first_date, &first_changes).unwrap();
+
+                let second_date = Utc::now().naive_local();
+                let second_changes = vec![
+                    MembershipChange::removed(&jon_doe()),
+                    MembershipChange::added(&jonette_snow()),
+                ];
+                insert(&mut connection, &second_date, &second_changes).unwrap();
+
+                assert_eq!(
+                    Some(MembershipsDiff::new(second_date, second_changes)),
+                    retrieve_last_diff(&mut connection).unwrap()
+                );
+            })
+        }
+    }
+
+    mod retrieve_last_report {
+        use crate::database::dao::membership_import::{
+            insert, insert_rejected_rows, retrieve_last_report,
+        };
+        use crate::database::with_temp_database;
+        use chrono::Utc;
+        use dto::import_report::{ImportReport, RejectedRow};
+
+        #[test]
+        fn none_when_never_imported() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                assert_eq!(None, retrieve_last_report(&mut connection).unwrap());
+            })
+        }
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                insert(&mut connection, &Utc::now().naive_local(), &[]).unwrap();
+                let first_rows = vec![RejectedRow::new(
+                    2,
+                    "Doe;Jon".to_owned(),
+                    "missing field".to_owned(),
+                )];
+                insert_rejected_rows(&mut connection, &first_rows).unwrap();
+
+                insert(&mut connection, &Utc::now().naive_local(), &[]).unwrap();
+                let second_rows = vec![
+                    RejectedRow::new(3, "Snow".to_owned(), "missing field".to_owned()),
+                    RejectedRow::new(5, "Doe;Jon;1".to_owned(), "invalid date".to_owned()),
+                ];
+                insert_rejected_rows(&mut connection, &second_rows).unwrap();
+
+                assert_eq!(
+                    Some(ImportReport::new(second_rows)),
+                    retrieve_last_report(&mut connection).unwrap()
+                );
+            })
+        }
+    }
+}
@@ -1,7 +1,17 @@
 use crate::database::error::DatabaseError;
 
+pub(crate) mod audit_log;
+pub(crate) mod fileo_health_check;
+pub(crate) mod import_history;
 pub(crate) mod last_update;
 pub(crate) mod membership;
+pub(crate) mod membership_history;
+pub(crate) mod membership_import;
+pub(crate) mod session;
 pub(crate) mod uda_instance;
 
 type Result<T, E = DatabaseError> = std::result::Result<T, E>;
+
+/// Maximum number of parameters in a single query, for all supported backends:
+/// 32766 for SQLite > 3.32.0 and 65535 for PostgreSQL.
+const MAX_QUERY_PARAMETERS: usize = 32766;
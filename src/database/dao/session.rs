@@ -0,0 +1,168 @@
+use super::Result;
+use crate::database::DatabaseConnection;
+use crate::database::error::DatabaseError;
+use crate::database::schema::session::dsl::session;
+use crate::database::schema::session::*;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+/// Store the credentials of a session, replacing any previous session with the same id.
+pub fn insert(
+    connection: &mut DatabaseConnection,
+    session_kind: &str,
+    session_id: &str,
+    encrypted_credentials: &str,
+    expiration: &NaiveDateTime,
+) -> Result<()> {
+    connection.transaction::<_, DatabaseError, _>(|connection| {
+        diesel::delete(session.filter(id.eq(session_id))).execute(connection)?;
+        diesel::insert_into(session)
+            .values((
+                id.eq(session_id),
+                kind.eq(session_kind),
+                credentials.eq(encrypted_credentials),
+                expiration_date.eq(expiration.to_string()),
+            ))
+            .execute(connection)?;
+        Ok(())
+    })
+}
+
+/// Retrieve the credentials of a session, unless it has expired.
+pub fn find(
+    connection: &mut DatabaseConnection,
+    session_kind: &str,
+    session_id: &str,
+    now: &NaiveDateTime,
+) -> Result<Option<String>> {
+    // Dates are stored as text, which sorts like dates
+    let result = session
+        .filter(id.eq(session_id))
+        .filter(kind.eq(session_kind))
+        .filter(expiration_date.gt(now.to_string()))
+        .select(credentials)
+        .first::<String>(connection)
+        .optional()?;
+
+    Ok(result)
+}
+
+/// Delete the expired sessions, then the oldest sessions of the kind beyond the capacity.
+pub fn delete_expired_and_excess(
+    connection: &mut DatabaseConnection,
+    session_kind: &str,
+    now: &NaiveDateTime,
+    capacity: usize,
+) -> Result<usize> {
+    let mut count =
+        diesel::delete(session.filter(expiration_date.le(now.to_string()))).execute(connection)?;
+
+    let excess_ids = session
+        .filter(kind.eq(session_kind))
+        .order(expiration_date.desc())
+        .select(id)
+        .load::<String>(connection)?
+        .into_iter()
+        .skip(capacity)
+        .collect::<Vec<_>>();
+    if !excess_ids.is_empty() {
+        count += diesel::delete(session.filter(id.eq_any(&excess_ids))).execute(connection)?;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn date(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    mod find {
+        use crate::database::dao::session::tests::date;
+        use crate::database::dao::session::{find, insert};
+        use crate::database::with_temp_database;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                insert(&mut connection, "Fileo", "id", "credentials", &date(12)).unwrap();
+
+                assert_eq!(
+                    Some("credentials".to_owned()),
+                    find(&mut connection, "Fileo", "id", &date(10)).unwrap()
+                );
+                assert_eq!(None, find(&mut connection, "UDA", "id", &date(10)).unwrap());
+            })
+        }
+
+        #[test]
+        fn none_when_expired() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                insert(&mut connection, "Fileo", "id", "credentials", &date(12)).unwrap();
+
+                assert_eq!(
+                    None,
+                    find(&mut connection, "Fileo", "id", &date(13)).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn success_when_replaced() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                insert(&mut connection, "Fileo", "id", "old", &date(12)).unwrap();
+                insert(&mut connection, "Fileo", "id", "new", &date(14)).unwrap();
+
+                assert_eq!(
+                    Some("new".to_owned()),
+                    find(&mut connection, "Fileo", "id", &date(13)).unwrap()
+                );
+            })
+        }
+    }
+
+    mod delete_expired_and_excess {
+        use crate::database::dao::session::tests::date;
+        use crate::database::dao::session::{delete_expired_and_excess, find, insert};
+        use crate::database::with_temp_database;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                insert(&mut connection, "Fileo", "expired", "credentials", &date(9)).unwrap();
+                insert(&mut connection, "Fileo", "oldest", "credentials", &date(11)).unwrap();
+                insert(&mut connection, "Fileo", "newest", "credentials", &date(12)).unwrap();
+                insert(&mut connection, "UDA", "other", "credentials", &date(11)).unwrap();
+
+                let count = delete_expired_and_excess(&mut connection, "Fileo", &date(10), 1);
+                assert_eq!(2, count.unwrap());
+
+                assert!(
+                    find(&mut connection, "Fileo", "newest", &date(10))
+                        .unwrap()
+                        .is_some()
+                );
+                assert!(
+                    find(&mut connection, "Fileo", "oldest", &date(10))
+                        .unwrap()
+                        .is_none()
+                );
+                assert!(
+                    find(&mut connection, "UDA", "other", &date(10))
+                        .unwrap()
+                        .is_some()
+                );
+            })
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use super::Result;
+use crate::database::DatabaseConnection;
 use crate::database::dao::last_update::UpdatableElement;
 use crate::database::model::uda_instance::UdaInstance;
 use crate::database::schema::uda_instance::{name, slug, url};
 use diesel::prelude::*;
 
-pub fn retrieve_all(connection: &mut SqliteConnection) -> Result<Vec<dto::uda_instance::Instance>> {
+pub fn retrieve_all(
+    connection: &mut DatabaseConnection,
+) -> Result<Vec<dto::uda_instance::Instance>> {
     let results = crate::database::schema::uda_instance::dsl::uda_instance
         .select(UdaInstance::as_select())
         .load(connection)?;
@@ -15,14 +18,14 @@ pub fn retrieve_all(connection: &mut SqliteConnection) -> Result<Vec<dto::uda_in
         .collect())
 }
 
-fn delete_all(connection: &mut SqliteConnection) -> Result<usize> {
+fn delete_all(connection: &mut DatabaseConnection) -> Result<usize> {
     let count = diesel::delete(crate::database::schema::uda_instance::table).execute(connection)?;
 
     Ok(count)
 }
 
 fn insert_all(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
     uda_instances: &[dto::uda_instance::Instance],
 ) -> Result<usize> {
     let uda_instances = uda_instances
@@ -46,7 +49,7 @@ fn insert_all(
 }
 
 pub fn replace_all(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
     uda_instances: &[dto::uda_instance::Instance],
 ) -> Result<(usize, usize)> {
     let deleted_count = delete_all(connection)?;
@@ -81,7 +84,7 @@ mod tests {
         ]
     }
 
-    fn populate_db(connection: &mut SqliteConnection) -> Vec<Instance> {
+    fn populate_db(connection: &mut DatabaseConnection) -> Vec<Instance> {
         let expected_instances = get_test_instances();
         let instances = expected_instances
             .clone()
@@ -0,0 +1,422 @@
+use crate::database::error::DatabaseError;
+use crate::database::error::DatabaseError::{
+    EncryptionFailed, InvalidEncryptionKey, MissingEncryptionKey,
+};
+use crate::database::{DatabaseConnection, Result, dao};
+use crate::tools::env_args::retrieve_arg_value;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use diesel::Connection;
+#[cfg(not(test))]
+use std::sync::OnceLock;
+
+const ENCRYPTION_KEY_ARG: &str = "--database-encryption-key";
+/// Marks encrypted values, so that values stored before the encryption was enabled can still be read.
+const ENCRYPTED_VALUE_PREFIX: &str = "enc:";
+const NONCE_LENGTH: usize = 12;
+
+#[cfg(not(test))]
+static CIPHER: OnceLock<Option<Aes256Gcm>> = OnceLock::new();
+
+/// Check the encryption key passed at startup, so that the app doesn't start with an invalid key.
+pub(super) fn init_encryption() -> Result<()> {
+    let cipher = build_cipher()?;
+    if cipher.is_none() {
+        warn!("No database encryption key provided, personal data will be stored in plain text.");
+    }
+    #[cfg(not(test))]
+    let _ = CIPHER.set(cipher);
+    Ok(())
+}
+
+/// The key is expected to be 32 bytes, encoded in base64.
+fn build_cipher() -> Result<Option<Aes256Gcm>> {
+    let Some(key) = retrieve_arg_value(ENCRYPTION_KEY_ARG) else {
+        return Ok(None);
+    };
+    let key = BASE64_STANDARD
+        .decode(key.trim())
+        .map_err(|error| InvalidEncryptionKey(error.to_string()))?;
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|_| InvalidEncryptionKey(format!("Expected 32 bytes, found {}", key.len())))?;
+    Ok(Some(cipher))
+}
+
+#[cfg(not(test))]
+fn cipher() -> Result<Option<Aes256Gcm>> {
+    match CIPHER.get() {
+        Some(cipher) => Ok(cipher.clone()),
+        None => build_cipher(),
+    }
+}
+
+#[cfg(test)]
+fn cipher() -> Result<Option<Aes256Gcm>> {
+    build_cipher()
+}
+
+/// Whether a valid encryption key has been provided, so that sensitive data can be stored.
+pub(crate) fn is_encryption_enabled() -> bool {
+    matches!(cipher(), Ok(Some(_)))
+}
+
+/// Encrypt a personal data field with the key passed at startup.
+/// Without key, or if the value is empty, the value is stored as is.
+pub(crate) fn encrypt(value: &str) -> Result<String> {
+    let Some(cipher) = cipher()? else {
+        return Ok(value.to_owned());
+    };
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|error| EncryptionFailed(error.to_string()))?;
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+
+    Ok(format!(
+        "{ENCRYPTED_VALUE_PREFIX}{}",
+        BASE64_STANDARD.encode(payload)
+    ))
+}
+
+/// Whether a value has been stored before an encryption key was provided.
+pub(crate) fn is_plain_text(value: &str) -> bool {
+    !value.is_empty() && !value.starts_with(ENCRYPTED_VALUE_PREFIX)
+}
+
+/// Whether any of the given stored values has to be encrypted by [encrypt_plain_text].
+pub(crate) fn any_plain_text<'a>(values: impl IntoIterator<Item = Option<&'a str>>) -> bool {
+    values.into_iter().flatten().any(is_plain_text)
+}
+
+/// Encrypt a value stored in plain text. Encrypted values are returned as is.
+pub(crate) fn encrypt_plain_text(value: String) -> Result<String> {
+    if is_plain_text(&value) {
+        encrypt(&value)
+    } else {
+        Ok(value)
+    }
+}
+
+/// Encrypt the personal data stored before an encryption key was provided,
+/// as rows which don't change are never rewritten by imports.
+/// Once everything has been encrypted, nothing is updated anymore.
+pub(super) fn encrypt_plain_text_values(connection: &mut DatabaseConnection) -> Result<()> {
+    if !is_encryption_enabled() {
+        return Ok(());
+    }
+
+    let count = connection.transaction::<_, DatabaseError, _>(|connection| {
+        Ok(dao::membership::encrypt_plain_text_values(connection)?
+            + dao::membership_history::encrypt_plain_text_values(connection)?
+            + dao::membership_import::encrypt_plain_text_values(connection)?)
+    })?;
+    if count > 0 {
+        info!("{count} rows stored in plain text have been encrypted.");
+    }
+    Ok(())
+}
+
+/// Decrypt a field encrypted by [encrypt].
+/// Values stored in plain text are returned as is.
+pub(crate) fn decrypt(value: String) -> Result<String> {
+    let Some(payload) = value.strip_prefix(ENCRYPTED_VALUE_PREFIX) else {
+        return Ok(value);
+    };
+    let cipher = cipher()?.ok_or(MissingEncryptionKey)?;
+
+    let payload = BASE64_STANDARD
+        .decode(payload)
+        .map_err(|error| EncryptionFailed(error.to_string()))?;
+    if payload.len() < NONCE_LENGTH {
+        return Err(EncryptionFailed("Truncated value".to_owned()));
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| EncryptionFailed("Wrong key or corrupted value".to_owned()))?;
+
+    String::from_utf8(plaintext).map_err(|error| EncryptionFailed(error.to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::database::encryption::ENCRYPTION_KEY_ARG;
+    use dto::import_report::RejectedRow;
+
+    /// An arg providing a valid encryption key.
+    pub(crate) fn encryption_key_arg() -> String {
+        format!("{ENCRYPTION_KEY_ARG}=MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=")
+    }
+
+    /// Rows rejected by an import, holding personal data.
+    fn rejected_rows() -> Vec<RejectedRow> {
+        vec![RejectedRow::new(
+            2,
+            "123456;Doe;Jon;jon.doe@address.com;01/02/1980".to_owned(),
+            "Invalid end date".to_owned(),
+        )]
+    }
+
+    mod encrypt {
+        use crate::database::encryption::tests::encryption_key_arg;
+        use crate::database::encryption::{ENCRYPTED_VALUE_PREFIX, decrypt, encrypt};
+        use crate::tools::env_args::with_env_args;
+
+        #[test]
+        fn success() {
+            with_env_args(vec![encryption_key_arg()], || {
+                let encrypted = encrypt("jon.doe@address.com").unwrap();
+                assert!(encrypted.starts_with(ENCRYPTED_VALUE_PREFIX));
+                assert!(!encrypted.contains("jon.doe"));
+                assert_ne!(encrypted, encrypt("jon.doe@address.com").unwrap());
+
+                assert_eq!("jon.doe@address.com", decrypt(encrypted).unwrap());
+            })
+        }
+
+        #[test]
+        fn keep_empty_value() {
+            with_env_args(vec![encryption_key_arg()], || {
+                assert_eq!("", encrypt("").unwrap());
+            })
+        }
+
+        #[test]
+        fn plain_text_without_key() {
+            assert_eq!(
+                "jon.doe@address.com",
+                encrypt("jon.doe@address.com").unwrap()
+            );
+        }
+
+        #[test]
+        fn fail_when_invalid_key() {
+            with_env_args(
+                vec!["--database-encryption-key=c2hvcnQ=".to_owned()],
+                || assert!(encrypt("jon.doe@address.com").is_err()),
+            )
+        }
+    }
+
+    mod decrypt {
+        use crate::database::encryption::tests::encryption_key_arg;
+        use crate::database::encryption::{decrypt, encrypt};
+        use crate::database::error::DatabaseError::{EncryptionFailed, MissingEncryptionKey};
+        use crate::tools::env_args::with_env_args;
+
+        #[test]
+        fn success_when_plain_text() {
+            with_env_args(vec![encryption_key_arg()], || {
+                assert_eq!(
+                    "jon.doe@address.com",
+                    decrypt("jon.doe@address.com".to_owned()).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn fail_without_key() {
+            let encrypted = with_env_args(vec![encryption_key_arg()], || {
+                encrypt("0123456789").unwrap()
+            });
+            assert_eq!(Err(MissingEncryptionKey), decrypt(encrypted));
+        }
+
+        #[test]
+        fn fail_with_wrong_key() {
+            let encrypted = with_env_args(vec![encryption_key_arg()], || {
+                encrypt("0123456789").unwrap()
+            });
+            let result = with_env_args(
+                vec![
+                    "--database-encryption-key=ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA="
+                        .to_owned(),
+                ],
+                || decrypt(encrypted),
+            );
+            assert!(matches!(result, Err(EncryptionFailed(_))));
+        }
+    }
+
+    mod stored_memberships {
+        use crate::database::dao::membership::{replace_memberships, retrieve_memberships};
+        use crate::database::encryption::tests::encryption_key_arg;
+        use crate::database::schema::membership::dsl::membership;
+        use crate::database::schema::membership::email_address;
+        use crate::database::with_temp_database;
+        use crate::membership::tests::{jon_doe, jonette_snow};
+        use crate::tools::env_args::with_env_args;
+        use diesel::prelude::*;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                with_env_args(vec![encryption_key_arg()], || {
+                    let mut connection = pool.get().unwrap();
+                    replace_memberships(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
+
+                    let stored_email_addresses = membership
+                        .select(email_address)
+                        .load::<String>(&mut connection)
+                        .unwrap();
+                    assert!(
+                        stored_email_addresses
+                            .iter()
+                            .all(|address| !address.contains('@'))
+                    );
+
+                    assert_eq!(
+                        vec![jon_doe(), jonette_snow()],
+                        retrieve_memberships(&mut connection).unwrap()
+                    );
+                })
+            })
+        }
+    }
+
+    mod stored_imports {
+        use crate::database::dao::membership::replace_memberships;
+        use crate::database::dao::membership_import::{
+            insert_rejected_rows, retrieve_last_diff, retrieve_last_report,
+        };
+        use crate::database::encryption::tests::{encryption_key_arg, rejected_rows};
+        use crate::database::schema::membership_change::dsl::membership_change;
+        use crate::database::schema::membership_rejected_row::dsl::membership_rejected_row;
+        use crate::database::schema::{membership_change, membership_rejected_row};
+        use crate::database::with_temp_database;
+        use crate::membership::tests::jon_doe;
+        use crate::tools::env_args::with_env_args;
+        use diesel::prelude::*;
+        use dto::import_report::ImportReport;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                with_env_args(vec![encryption_key_arg()], || {
+                    let mut connection = pool.get().unwrap();
+                    let diff = replace_memberships(&mut connection, &[jon_doe()]).unwrap();
+                    insert_rejected_rows(&mut connection, &rejected_rows()).unwrap();
+
+                    let stored_email_addresses = membership_change
+                        .select(membership_change::email_address)
+                        .load::<Option<String>>(&mut connection)
+                        .unwrap();
+                    assert!(
+                        stored_email_addresses
+                            .iter()
+                            .flatten()
+                            .all(|address| !address.contains('@'))
+                    );
+                    let stored_records = membership_rejected_row
+                        .select(membership_rejected_row::record)
+                        .load::<String>(&mut connection)
+                        .unwrap();
+                    assert!(stored_records.iter().all(|record| !record.contains('@')));
+
+                    assert_eq!(Some(diff), retrieve_last_diff(&mut connection).unwrap());
+                    assert_eq!(
+                        Some(ImportReport::new(rejected_rows())),
+                        retrieve_last_report(&mut connection).unwrap()
+                    );
+                })
+            })
+        }
+    }
+
+    mod encrypt_plain_text_values {
+        use crate::database::dao::membership::{replace_memberships, retrieve_memberships};
+        use crate::database::dao::membership_import::{
+            insert_rejected_rows, retrieve_last_diff, retrieve_last_report,
+        };
+        use crate::database::encryption::encrypt_plain_text_values;
+        use crate::database::encryption::tests::{encryption_key_arg, rejected_rows};
+        use crate::database::schema::membership::dsl::membership;
+        use crate::database::schema::membership_change::dsl::membership_change;
+        use crate::database::schema::membership_history::dsl::membership_history;
+        use crate::database::schema::membership_rejected_row::dsl::membership_rejected_row;
+        use crate::database::schema::{
+            membership_change, membership_history, membership_rejected_row,
+        };
+        use crate::database::with_temp_database;
+        use crate::membership::tests::{jon_doe, jonette_snow};
+        use crate::tools::env_args::with_env_args;
+        use diesel::prelude::*;
+        use dto::import_report::ImportReport;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let diff =
+                    replace_memberships(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
+                insert_rejected_rows(&mut connection, &rejected_rows()).unwrap();
+
+                with_env_args(vec![encryption_key_arg()], || {
+                    encrypt_plain_text_values(&mut connection).unwrap();
+
+                    let mut stored_values = membership
+                        .select(crate::database::schema::membership::email_address)
+                        .load::<String>(&mut connection)
+                        .unwrap();
+                    stored_values.extend(
+                        membership_history
+                            .select(membership_history::email_address)
+                            .load::<String>(&mut connection)
+                            .unwrap(),
+                    );
+                    stored_values.extend(
+                        membership_change
+                            .select(membership_change::email_address)
+                            .load::<Option<String>>(&mut connection)
+                            .unwrap()
+                            .into_iter()
+                            .flatten(),
+                    );
+                    stored_values.extend(
+                        membership_rejected_row
+                            .select(membership_rejected_row::record)
+                            .load::<String>(&mut connection)
+                            .unwrap(),
+                    );
+                    assert!(stored_values.iter().all(|value| !value.contains('@')));
+
+                    assert_eq!(
+                        vec![jon_doe(), jonette_snow()],
+                        retrieve_memberships(&mut connection).unwrap()
+                    );
+                    assert_eq!(Some(diff), retrieve_last_diff(&mut connection).unwrap());
+                    assert_eq!(
+                        Some(ImportReport::new(rejected_rows())),
+                        retrieve_last_report(&mut connection).unwrap()
+                    );
+                })
+            })
+        }
+
+        #[test]
+        fn nothing_without_key() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe()]).unwrap();
+
+                encrypt_plain_text_values(&mut connection).unwrap();
+
+                let stored_email_addresses = membership
+                    .select(crate::database::schema::membership::email_address)
+                    .load::<String>(&mut connection)
+                    .unwrap();
+                assert_eq!(
+                    vec![jon_doe().email_address().clone()],
+                    stored_email_addresses
+                );
+            })
+        }
+    }
+}
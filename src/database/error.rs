@@ -21,6 +21,18 @@ pub enum DatabaseError {
     CantUpdateLastUpdated(String),
     #[error("Last update should be known at this point.")]
     UnknownLastUpdate,
+    #[error("This operation is only supported with a SQLite database.")]
+    UnsupportedBackend,
+    #[error("Can't back the database up or restore it.")]
+    BackupFailed(String),
+    #[error("The backup doesn't match any known schema version.")]
+    IncompatibleBackup(String),
+    #[error("The database encryption key is invalid.")]
+    InvalidEncryptionKey(String),
+    #[error("Some data is encrypted, but no database encryption key has been provided.")]
+    MissingEncryptionKey,
+    #[error("Can't encrypt or decrypt a value.")]
+    EncryptionFailed(String),
 }
 
 impl From<Box<dyn Error + Send + Sync + 'static>> for DatabaseError {
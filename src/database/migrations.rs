@@ -1,14 +1,21 @@
+use crate::database::DatabaseConnection;
 use crate::database::error::DatabaseError;
 use crate::error::Result;
-use diesel::sqlite::Sqlite;
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 
-pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+pub const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/sqlite");
+pub const POSTGRESQL_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/postgresql");
 
-pub(crate) fn run_migrations(
-    connection: &mut impl MigrationHarness<Sqlite>,
-) -> Result<(), DatabaseError> {
-    connection.run_pending_migrations(MIGRATIONS)?;
+/// Run the migrations written for the backend of the connection.
+pub(crate) fn run_migrations(connection: &mut DatabaseConnection) -> Result<(), DatabaseError> {
+    match connection {
+        DatabaseConnection::Postgresql(connection) => {
+            connection.run_pending_migrations(POSTGRESQL_MIGRATIONS)?
+        }
+        DatabaseConnection::Sqlite(connection) => {
+            connection.run_pending_migrations(SQLITE_MIGRATIONS)?
+        }
+    };
 
     Ok(())
 }
@@ -0,0 +1,47 @@
+use crate::database::error::DatabaseError;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use dto::audit_log::{AuditAction, AuditLogEntry};
+use std::str::FromStr;
+
+/// Targets are stored in a single column, one per line.
+pub(crate) const TARGETS_SEPARATOR: &str = "\n";
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = crate::database::schema::audit_log)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct AuditLog {
+    id: i32,
+    date: String,
+    actor: String,
+    action: String,
+    members_count: i64,
+    targets: String,
+}
+
+impl TryFrom<AuditLog> for AuditLogEntry {
+    type Error = DatabaseError;
+
+    fn try_from(value: AuditLog) -> Result<Self, Self::Error> {
+        let date = NaiveDateTime::parse_from_str(&value.date, "%Y-%m-%d %H:%M:%S%.f")?;
+        let action =
+            AuditAction::from_str(&value.action).map_err(DatabaseError::ConversionError)?;
+        let targets = if value.targets.is_empty() {
+            vec![]
+        } else {
+            value
+                .targets
+                .split(TARGETS_SEPARATOR)
+                .map(str::to_owned)
+                .collect()
+        };
+
+        Ok(AuditLogEntry::new(
+            date,
+            value.actor,
+            action,
+            value.members_count as usize,
+            targets,
+        ))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::database::error::DatabaseError;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use dto::fileo_health::{FileoFlowStep, FileoHealthReport};
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = crate::database::schema::fileo_health_check)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct FileoHealthCheck {
+    id: i32,
+    date: String,
+    failed_step: Option<String>,
+    reason: Option<String>,
+    snippet: Option<String>,
+}
+
+impl TryFrom<FileoHealthCheck> for FileoHealthReport {
+    type Error = DatabaseError;
+
+    fn try_from(value: FileoHealthCheck) -> Result<Self, Self::Error> {
+        let date = NaiveDateTime::parse_from_str(&value.date, "%Y-%m-%d %H:%M:%S%.f")?;
+        let failed_step = match value.failed_step.as_deref() {
+            None => None,
+            Some("Login") => Some(FileoFlowStep::Login),
+            Some("Filter") => Some(FileoFlowStep::Filter),
+            Some("ExportLink") => Some(FileoFlowStep::ExportLink),
+            Some("Download") => Some(FileoFlowStep::Download),
+            Some("CsvHeader") => Some(FileoFlowStep::CsvHeader),
+            Some(step) => Err(DatabaseError::ConversionError(format!(
+                "Unknown Fileo flow step: {step}"
+            )))?,
+        };
+
+        Ok(FileoHealthReport::new(
+            date,
+            failed_step,
+            value.reason,
+            value.snippet,
+        ))
+    }
+}
@@ -0,0 +1,56 @@
+use crate::database::error::DatabaseError;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use dto::import_history::{
+    ImportCounts, ImportHistoryEntry, ImportOutcome, ImportSource, ImportedElement,
+};
+use std::str::FromStr;
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = crate::database::schema::import_history)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct ImportHistory {
+    id: i32,
+    element: String,
+    source: String,
+    actor: Option<String>,
+    start_date: String,
+    end_date: String,
+    deleted_count: i64,
+    inserted_count: i64,
+    updated_count: i64,
+    rejected_count: i64,
+    outcome: String,
+    error: Option<String>,
+}
+
+impl TryFrom<ImportHistory> for ImportHistoryEntry {
+    type Error = DatabaseError;
+
+    fn try_from(value: ImportHistory) -> Result<Self, Self::Error> {
+        let element =
+            ImportedElement::from_str(&value.element).map_err(DatabaseError::ConversionError)?;
+        let source =
+            ImportSource::from_str(&value.source).map_err(DatabaseError::ConversionError)?;
+        let outcome =
+            ImportOutcome::from_str(&value.outcome).map_err(DatabaseError::ConversionError)?;
+        let start = NaiveDateTime::parse_from_str(&value.start_date, "%Y-%m-%d %H:%M:%S%.f")?;
+        let end = NaiveDateTime::parse_from_str(&value.end_date, "%Y-%m-%d %H:%M:%S%.f")?;
+
+        Ok(ImportHistoryEntry::new(
+            element,
+            source,
+            value.actor,
+            start,
+            end,
+            ImportCounts::new(
+                value.deleted_count as usize,
+                value.inserted_count as usize,
+                value.updated_count as usize,
+                value.rejected_count as usize,
+            ),
+            outcome,
+            value.error,
+        ))
+    }
+}
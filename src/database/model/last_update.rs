@@ -4,7 +4,7 @@ use diesel::{Insertable, Queryable, Selectable};
 
 #[derive(Queryable, Selectable, Insertable, Debug, PartialEq)]
 #[diesel(table_name = crate::database::schema::last_update)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
 pub(crate) struct LastUpdate {
     element: String,
     date: String,
@@ -1,3 +1,4 @@
+use crate::database::encryption::decrypt;
 use crate::database::error::DatabaseError;
 use chrono::NaiveDate;
 use diesel::prelude::*;
@@ -5,7 +6,7 @@ use std::str::FromStr;
 
 #[derive(Queryable, Selectable, Insertable, Debug, Clone)]
 #[diesel(table_name = crate::database::schema::membership)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
 pub(crate) struct Membership {
     id: i32,
     last_name: String,
@@ -23,6 +24,22 @@ pub(crate) struct Membership {
     normalized_first_name: String,
     normalized_last_name_first_name: String,
     normalized_first_name_last_name: String,
+    membership_type: Option<String>,
+    tariff: Option<String>,
+}
+
+impl Membership {
+    pub(crate) fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Memberships are identified by their normalized number and their start date.
+    pub(crate) fn key(&self) -> (String, String) {
+        (
+            self.normalized_membership_number.clone(),
+            self.start_date.clone(),
+        )
+    }
 }
 
 impl TryFrom<Membership> for dto::membership::Membership {
@@ -30,9 +47,11 @@ impl TryFrom<Membership> for dto::membership::Membership {
 
     fn try_from(value: Membership) -> Result<Self, Self::Error> {
         let birthdate = match value.birthdate {
-            Some(birthdate) => Some(NaiveDate::from_str(&birthdate)?),
+            Some(birthdate) => Some(NaiveDate::from_str(&decrypt(birthdate)?)?),
             None => None,
         };
+        let cell_number = value.cell_number.map(decrypt).transpose()?;
+        let email_address = decrypt(value.email_address)?;
         let start_date = NaiveDate::from_str(&value.start_date)?;
         let end_date = NaiveDate::from_str(&value.end_date)?;
         Ok(dto::membership::Membership::new(
@@ -40,12 +59,14 @@ impl TryFrom<Membership> for dto::membership::Membership {
             value.first_name,
             birthdate,
             value.membership_number,
-            value.cell_number,
-            value.email_address,
+            cell_number,
+            email_address,
             start_date,
             end_date,
             value.club,
             value.structure_code,
+            value.membership_type,
+            value.tariff,
         ))
     }
 }
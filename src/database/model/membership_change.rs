@@ -0,0 +1,63 @@
+use crate::database::encryption::decrypt;
+use crate::database::error::DatabaseError;
+use chrono::NaiveDate;
+use diesel::{Insertable, Queryable, Selectable};
+use dto::membership_diff::MembershipChangeKind;
+use std::str::FromStr;
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = crate::database::schema::membership_change)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct MembershipChange {
+    id: i32,
+    import_id: i32,
+    kind: String,
+    membership_number: String,
+    last_name: String,
+    first_name: String,
+    start_date: String,
+    previous_email_address: Option<String>,
+    email_address: Option<String>,
+    previous_end_date: Option<String>,
+    end_date: Option<String>,
+    previous_club: Option<String>,
+    club: Option<String>,
+}
+
+impl TryFrom<MembershipChange> for dto::membership_diff::MembershipChange {
+    type Error = DatabaseError;
+
+    fn try_from(value: MembershipChange) -> Result<Self, Self::Error> {
+        let kind = match value.kind.as_str() {
+            "Added" => MembershipChangeKind::Added,
+            "Removed" => MembershipChangeKind::Removed,
+            "Changed" => MembershipChangeKind::Changed,
+            kind => Err(DatabaseError::ConversionError(format!(
+                "Unknown membership change kind: {kind}"
+            )))?,
+        };
+        let start_date = NaiveDate::from_str(&value.start_date)?;
+        let previous_end_date = match value.previous_end_date {
+            Some(previous_end_date) => Some(NaiveDate::from_str(&previous_end_date)?),
+            None => None,
+        };
+        let end_date = match value.end_date {
+            Some(end_date) => Some(NaiveDate::from_str(&end_date)?),
+            None => None,
+        };
+
+        Ok(dto::membership_diff::MembershipChange::new(
+            kind,
+            value.membership_number,
+            value.last_name,
+            value.first_name,
+            start_date,
+            value.previous_email_address.map(decrypt).transpose()?,
+            value.email_address.map(decrypt).transpose()?,
+            previous_end_date,
+            end_date,
+            value.previous_club,
+            value.club,
+        ))
+    }
+}
@@ -0,0 +1,70 @@
+use crate::database::encryption::decrypt;
+use crate::database::error::DatabaseError;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use std::str::FromStr;
+
+/// A version of a membership, as it was imported at some point.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = crate::database::schema::membership_history)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct MembershipHistory {
+    id: i32,
+    import_id: i32,
+    last_name: String,
+    first_name: String,
+    birthdate: Option<String>,
+    membership_number: String,
+    cell_number: Option<String>,
+    email_address: String,
+    start_date: String,
+    end_date: String,
+    club: String,
+    structure_code: String,
+    normalized_membership_number: String,
+    normalized_last_name: String,
+    normalized_first_name: String,
+    normalized_last_name_first_name: String,
+    normalized_first_name_last_name: String,
+    membership_type: Option<String>,
+    tariff: Option<String>,
+}
+
+impl MembershipHistory {
+    /// Memberships are identified by their normalized number and their start date.
+    pub(crate) fn key(&self) -> (String, String) {
+        (
+            self.normalized_membership_number.clone(),
+            self.start_date.clone(),
+        )
+    }
+}
+
+impl TryFrom<MembershipHistory> for dto::membership::Membership {
+    type Error = DatabaseError;
+
+    fn try_from(value: MembershipHistory) -> Result<Self, Self::Error> {
+        let birthdate = match value.birthdate {
+            Some(birthdate) => Some(NaiveDate::from_str(&decrypt(birthdate)?)?),
+            None => None,
+        };
+        let cell_number = value.cell_number.map(decrypt).transpose()?;
+        let email_address = decrypt(value.email_address)?;
+        let start_date = NaiveDate::from_str(&value.start_date)?;
+        let end_date = NaiveDate::from_str(&value.end_date)?;
+        Ok(dto::membership::Membership::new(
+            value.last_name,
+            value.first_name,
+            birthdate,
+            value.membership_number,
+            cell_number,
+            email_address,
+            start_date,
+            end_date,
+            value.club,
+            value.structure_code,
+            value.membership_type,
+            value.tariff,
+        ))
+    }
+}
@@ -0,0 +1,22 @@
+use crate::database::error::DatabaseError;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+
+#[derive(Queryable, Selectable, Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::database::schema::membership_import)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct MembershipImport {
+    id: i32,
+    date: String,
+}
+
+impl MembershipImport {
+    pub(crate) fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub(crate) fn date(&self) -> Result<NaiveDateTime, DatabaseError> {
+        NaiveDateTime::parse_from_str(&self.date, "%Y-%m-%d %H:%M:%S%.f")
+            .map_err(DatabaseError::from)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::database::encryption::decrypt;
+use crate::database::error::DatabaseError;
+use diesel::{Insertable, Queryable, Selectable};
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = crate::database::schema::membership_rejected_row)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
+pub(crate) struct MembershipRejectedRow {
+    id: i32,
+    import_id: i32,
+    line: i64,
+    record: String,
+    reason: String,
+}
+
+impl TryFrom<MembershipRejectedRow> for dto::import_report::RejectedRow {
+    type Error = DatabaseError;
+
+    fn try_from(value: MembershipRejectedRow) -> Result<Self, Self::Error> {
+        Ok(dto::import_report::RejectedRow::new(
+            value.line as u64,
+            decrypt(value.record)?,
+            value.reason,
+        ))
+    }
+}
@@ -1,3 +1,10 @@
+pub(super) mod audit_log;
+pub(super) mod fileo_health_check;
+pub(super) mod import_history;
 pub(super) mod last_update;
 pub(super) mod membership;
+pub(super) mod membership_change;
+pub(super) mod membership_history;
+pub(super) mod membership_import;
+pub(super) mod membership_rejected_row;
 pub(super) mod uda_instance;
@@ -2,7 +2,7 @@ use diesel::{Insertable, Queryable, Selectable};
 
 #[derive(Queryable, Selectable, Insertable, Debug, PartialEq)]
 #[diesel(table_name = crate::database::schema::uda_instance)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite, diesel::pg::Pg))]
 pub(crate) struct UdaInstance {
     id: i32,
     slug: String,
@@ -0,0 +1,177 @@
+use crate::database::Result;
+use crate::database::error::DatabaseError::{
+    BackupFailed, IncompatibleBackup, MissingDatabaseUrl, UnsupportedBackend,
+};
+use crate::database::is_postgresql_url;
+use crate::database::migrations::SQLITE_MIGRATIONS;
+use crate::tools::env_args::{retrieve_arg_value, retrieve_expected_arg_value};
+use chrono::Utc;
+use diesel::migration::MigrationSource;
+use diesel::sqlite::Sqlite;
+use diesel::{Connection, SqliteConnection};
+use diesel_migrations::MigrationHarness;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RESTORE_ARG: &str = "--restore-database";
+
+/// If asked for with the `--restore-database` arg, replace the SQLite database with the given backup.
+/// This should be done before any connection to the database is opened.
+pub fn restore_database_if_requested() -> Result<()> {
+    let Some(backup) = retrieve_arg_value(RESTORE_ARG) else {
+        return Ok(());
+    };
+    let database_url = retrieve_expected_arg_value("--database-url", MissingDatabaseUrl)?;
+    if is_postgresql_url(&database_url) {
+        return Err(UnsupportedBackend);
+    }
+
+    restore_database(Path::new(&backup), Path::new(&database_url))
+}
+
+/// Swap the database with a copy of the backup, once the backup has been validated.
+/// The previous database is kept alongside, in case the restore should be undone.
+fn restore_database(backup: &Path, database: &Path) -> Result<()> {
+    // Checking the backup writes into it, so the copy is checked instead
+    let restored_database = with_suffix(database, ".restoring");
+    fs::copy(backup, &restored_database).map_err(|error| BackupFailed(error.to_string()))?;
+    if let Err(error) = check_backup(&restored_database) {
+        let _ = fs::remove_file(&restored_database);
+        return Err(error);
+    }
+
+    if database.exists() {
+        let suffix = format!(".before-restore-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+        let previous_database = with_suffix(database, &suffix);
+        fs::rename(database, &previous_database)
+            .map_err(|error| BackupFailed(error.to_string()))?;
+        info!("Previous database has been kept [file: {previous_database:?}]");
+    }
+    fs::rename(&restored_database, database).map_err(|error| BackupFailed(error.to_string()))?;
+    info!("Database has been restored [backup: {backup:?}]");
+
+    Ok(())
+}
+
+/// The backup should be a SQLite database of this app, and all its migrations should be known.
+/// Known migrations it lacks will run as usual, once the database has been restored.
+fn check_backup(backup: &Path) -> Result<()> {
+    let backup = backup
+        .to_str()
+        .ok_or_else(|| BackupFailed(format!("Invalid path: {backup:?}")))?;
+    let mut connection = SqliteConnection::establish(backup)
+        .map_err(|error| IncompatibleBackup(error.to_string()))?;
+    let applied_migrations = connection
+        .applied_migrations()
+        .map_err(|error| IncompatibleBackup(error.to_string()))?;
+    if applied_migrations.is_empty() {
+        return Err(IncompatibleBackup(
+            "No migration has been applied".to_owned(),
+        ));
+    }
+
+    let known_migrations = MigrationSource::<Sqlite>::migrations(&SQLITE_MIGRATIONS)?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect::<Vec<_>>();
+    let unknown_migrations = applied_migrations
+        .iter()
+        .map(ToString::to_string)
+        .filter(|version| !known_migrations.contains(version))
+        .collect::<Vec<_>>();
+    if unknown_migrations.is_empty() {
+        Ok(())
+    } else {
+        Err(IncompatibleBackup(format!(
+            "Unknown migrations: {}",
+            unknown_migrations.join(", ")
+        )))
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    mod restore_database {
+        use crate::database::backup::backup_database;
+        use crate::database::error::DatabaseError;
+        use crate::database::migrations::SQLITE_MIGRATIONS;
+        use crate::database::restore::restore_database;
+        use crate::database::with_temp_sqlite_database;
+        use crate::tools::test::tests::temp_dir;
+        use diesel::{Connection, RunQueryDsl, SqliteConnection};
+        use diesel_migrations::MigrationHarness;
+        use std::fs;
+        use std::path::PathBuf;
+
+        fn create_backup() -> PathBuf {
+            let backup = temp_dir().join("backup.db");
+            with_temp_sqlite_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                backup_database(&mut connection, &backup).unwrap();
+            });
+            backup
+        }
+
+        #[test]
+        fn success() {
+            let backup = create_backup();
+            let directory = temp_dir();
+            let database = directory.join("database.db");
+            fs::write(&database, "previous database").unwrap();
+
+            restore_database(&backup, &database).unwrap();
+
+            let files = fs::read_dir(&directory)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect::<Vec<_>>();
+            assert_eq!(2, files.len());
+            let previous_database = files.iter().find(|file| **file != database).unwrap();
+            assert_eq!(
+                "previous database",
+                fs::read_to_string(previous_database).unwrap()
+            );
+            let mut connection = SqliteConnection::establish(database.to_str().unwrap()).unwrap();
+            assert!(!connection.has_pending_migration(SQLITE_MIGRATIONS).unwrap());
+        }
+
+        #[test]
+        fn fail_when_unknown_migration() {
+            let backup = create_backup();
+            let mut connection = SqliteConnection::establish(backup.to_str().unwrap()).unwrap();
+            diesel::sql_query(
+                "INSERT INTO __diesel_schema_migrations (version) VALUES ('29990101000000')",
+            )
+            .execute(&mut connection)
+            .unwrap();
+            let directory = temp_dir();
+            let database = directory.join("database.db");
+            fs::write(&database, "previous database").unwrap();
+
+            let result = restore_database(&backup, &database);
+
+            assert!(matches!(result, Err(DatabaseError::IncompatibleBackup(_))));
+            assert_eq!(1, fs::read_dir(&directory).unwrap().count());
+            assert_eq!("previous database", fs::read_to_string(&database).unwrap());
+        }
+
+        #[test]
+        fn fail_when_not_a_database() {
+            let backup = temp_dir().join("backup.db");
+            fs::write(&backup, "not a database").unwrap();
+            let database = temp_dir().join("database.db");
+
+            let result = restore_database(&backup, &database);
+
+            assert!(matches!(result, Err(DatabaseError::IncompatibleBackup(_))));
+            assert!(!database.exists());
+        }
+    }
+}
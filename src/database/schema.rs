@@ -1,5 +1,43 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_log (id) {
+        id -> Integer,
+        date -> Text,
+        actor -> Text,
+        action -> Text,
+        members_count -> BigInt,
+        targets -> Text,
+    }
+}
+
+diesel::table! {
+    fileo_health_check (id) {
+        id -> Integer,
+        date -> Text,
+        failed_step -> Nullable<Text>,
+        reason -> Nullable<Text>,
+        snippet -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    import_history (id) {
+        id -> Integer,
+        element -> Text,
+        source -> Text,
+        actor -> Nullable<Text>,
+        start_date -> Text,
+        end_date -> Text,
+        deleted_count -> BigInt,
+        inserted_count -> BigInt,
+        rejected_count -> BigInt,
+        outcome -> Text,
+        error -> Nullable<Text>,
+        updated_count -> BigInt,
+    }
+}
+
 diesel::table! {
     last_update (element) {
         element -> Text,
@@ -25,6 +63,76 @@ diesel::table! {
         normalized_first_name_last_name -> Text,
         cell_number -> Nullable<Text>,
         start_date -> Text,
+        membership_type -> Nullable<Text>,
+        tariff -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    membership_change (id) {
+        id -> Integer,
+        import_id -> Integer,
+        kind -> Text,
+        membership_number -> Text,
+        last_name -> Text,
+        first_name -> Text,
+        start_date -> Text,
+        previous_email_address -> Nullable<Text>,
+        email_address -> Nullable<Text>,
+        previous_end_date -> Nullable<Text>,
+        end_date -> Nullable<Text>,
+        previous_club -> Nullable<Text>,
+        club -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    membership_history (id) {
+        id -> Integer,
+        import_id -> Integer,
+        last_name -> Text,
+        first_name -> Text,
+        birthdate -> Nullable<Text>,
+        membership_number -> Text,
+        cell_number -> Nullable<Text>,
+        email_address -> Text,
+        start_date -> Text,
+        end_date -> Text,
+        club -> Text,
+        structure_code -> Text,
+        normalized_membership_number -> Text,
+        normalized_last_name -> Text,
+        normalized_first_name -> Text,
+        normalized_last_name_first_name -> Text,
+        normalized_first_name_last_name -> Text,
+        membership_type -> Nullable<Text>,
+        tariff -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    membership_import (id) {
+        id -> Integer,
+        date -> Text,
+    }
+}
+
+diesel::table! {
+    membership_rejected_row (id) {
+        id -> Integer,
+        import_id -> Integer,
+        line -> BigInt,
+        record -> Text,
+        reason -> Text,
+    }
+}
+
+diesel::table! {
+    session (id) {
+        id -> Text,
+        kind -> Text,
+        credentials -> Text,
+        expiration_date -> Text,
     }
 }
 
@@ -37,4 +145,20 @@ diesel::table! {
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(last_update, membership, uda_instance,);
+diesel::joinable!(membership_change -> membership_import (import_id));
+diesel::joinable!(membership_history -> membership_import (import_id));
+diesel::joinable!(membership_rejected_row -> membership_import (import_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    audit_log,
+    fileo_health_check,
+    import_history,
+    last_update,
+    membership,
+    membership_change,
+    membership_history,
+    membership_import,
+    membership_rejected_row,
+    session,
+    uda_instance,
+);
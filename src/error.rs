@@ -1,5 +1,6 @@
 use crate::database::error::DatabaseError;
 use crate::fileo::error::FileoError;
+use crate::membership::error::MembershipError;
 use crate::notification::error::NotificationError;
 use crate::web::error::WebError;
 use thiserror::Error;
@@ -15,6 +16,8 @@ pub enum ApplicationError {
     Web(#[from] WebError),
     #[error("Error while working with Fileo.")]
     Fileo(#[from] FileoError),
+    #[error("Error while handling memberships.")]
+    Membership(#[from] MembershipError),
     #[error("Error while working with UDA.")]
     Uda(#[from] UdaError),
     #[error("Error while notifying of incoming events.")]
@@ -25,18 +25,16 @@ mod tests {
     use crate::web::credentials_storage::CredentialsStorage;
     use rocket::http::{Cookie, Status};
     use rocket::local::asynchronous::Client;
-    use std::sync::Mutex;
 
     #[async_test]
     async fn should_fileo_request_succeed() {
         let credentials =
             FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-        let mut credentials_storage = CredentialsStorage::default();
+        let credentials_storage = CredentialsStorage::default();
         let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
         credentials_storage.store(uuid.clone(), credentials.clone());
-        let credentials_storage_mutex = Mutex::new(credentials_storage);
 
-        let rocket = rocket::build().manage(credentials_storage_mutex);
+        let rocket = rocket::build().manage(credentials_storage);
         let client = Client::tracked(rocket).await.unwrap();
         let cookie = Cookie::new(AUTHENTICATION_COOKIE, uuid);
         let request = client.get("http://localhost").cookie(cookie.clone());
@@ -50,9 +48,8 @@ mod tests {
     async fn should_fileo_request_fail_when_no_matching_credentials() {
         let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
         let credentials_uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
-        let credentials_storage_mutex = Mutex::new(credentials_storage);
 
-        let rocket = rocket::build().manage(credentials_storage_mutex);
+        let rocket = rocket::build().manage(credentials_storage);
         let client = Client::tracked(rocket).await.unwrap();
         let cookie = Cookie::new(AUTHENTICATION_COOKIE, credentials_uuid);
         let request = client.get("http://localhost").cookie(cookie);
@@ -65,9 +62,8 @@ mod tests {
     #[async_test]
     async fn should_fileo_request_fail_when_no_header() {
         let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
-        let credentials_storage_mutex = Mutex::new(credentials_storage);
 
-        let rocket = rocket::build().manage(credentials_storage_mutex);
+        let rocket = rocket::build().manage(credentials_storage);
         let client = Client::tracked(rocket).await.unwrap();
         let request = client.get("http://localhost");
 
@@ -18,7 +18,6 @@ impl Debug for FileoCredentials {
     }
 }
 
-#[cfg(test)]
 impl FileoCredentials {
     pub fn new(login: String, password: String) -> Self {
         Self { login, password }
@@ -7,14 +7,17 @@ use crate::fileo::error::FileoError::{
     CantLoadListOnServer, CantRetrieveDownloadLink, MalformedMembershipsDownloadResponse,
     NoDownloadLink,
 };
+use crate::fileo::export_filters::FileoExportFilters;
 use crate::fileo::imported_membership::ImportedMembership;
 use crate::membership::config::MembershipsProviderConfig;
-use crate::tools::web::build_client;
+use crate::tools::web::{build_client, send_with_retries};
 use crate::tools::{log_error_and_return, log_message, log_message_and_return};
 use crate::web::error::WebError::{
     CantReadPageContent, ConnectionFailed, LackOfPermissions, NotFound, WrongCredentials,
 };
+use chrono::NaiveDate;
 use csv::Reader;
+use dto::import_report::{ImportReport, RejectedRow};
 use dto::membership::Membership;
 use encoding::all::ISO_8859_1;
 use encoding::{DecoderTrap, Encoding};
@@ -26,21 +29,32 @@ use rocket::http::ContentType;
 
 /// Download the memberships list from Fileo and write it to the disk.
 /// Require valid credentials to log in to Fileo.
+/// Along with the memberships, return a report of the rows that couldn't be read.
 pub async fn download_memberships_list(
     memberships_provider_config: &MembershipsProviderConfig,
     credentials: &FileoCredentials,
-) -> Result<Vec<Membership>> {
+) -> Result<(Vec<Membership>, ImportReport)> {
     let host = memberships_provider_config.host();
     let download_link_regex = memberships_provider_config.download_link_regex();
 
     let client = build_client()?;
     login_to_fileo(&client, host, credentials).await?;
-    load_list_into_server_session(&client, host).await?;
+    let export_filters = memberships_provider_config.export_filters();
+    load_list_into_server_session(&client, host, export_filters).await?;
     let download_url = retrieve_download_link(&client, host, download_link_regex).await?;
     let file_content = download_list(&client, &download_url).await?;
     Ok(parse_file(&file_content))
 }
 
+/// Read a memberships list exported from Fileo by hand.
+/// As for a downloaded list, the file is expected to be ISO-8859-1 encoded.
+pub fn read_memberships_export(file_content: &[u8]) -> Result<(Vec<Membership>, ImportReport)> {
+    let file_content = ISO_8859_1
+        .decode(file_content, DecoderTrap::Strict)
+        .map_err(FileoError::from)?;
+    Ok(parse_file(&file_content))
+}
+
 // region Requests
 pub async fn login_to_fileo(
     client: &Client,
@@ -48,10 +62,12 @@ pub async fn login_to_fileo(
     credentials: &FileoCredentials,
 ) -> Result<()> {
     let request = prepare_request_for_connection(client, domain, credentials);
-    let response = request.send().await.map_err(log_message_and_return(
-        "Connection failed...",
-        ConnectionFailed,
-    ))?;
+    let response = send_with_retries(request)
+        .await
+        .map_err(log_message_and_return(
+            "Connection failed...",
+            ConnectionFailed,
+        ))?;
     let status = response.status();
     if !status.is_success() {
         error!("Connection failed because of status {status}...");
@@ -63,6 +79,11 @@ pub async fn login_to_fileo(
         ConnectionFailed,
     ))?;
 
+    check_login_page(&text)
+}
+
+/// Check whether the page returned after a login attempt shows the expected profile.
+pub(super) fn check_login_page(text: &str) -> Result<()> {
     if text.contains("L'identifiant et le mot de passe ne correspondent pas")
         || text.contains("Le champ 'Identifiant' est obligatoire")
         || text.contains("Le champ 'Mot de passe' est obligatoire")
@@ -77,12 +98,19 @@ pub async fn login_to_fileo(
     }
 }
 
-async fn load_list_into_server_session(client: &Client, domain: &str) -> Result<()> {
-    let request = prepare_request_for_loading_list_into_server_session(client, domain);
-    let response = request.send().await.map_err(log_message_and_return(
-        "The server couldn't load the list.",
-        CantLoadListOnServer,
-    ))?;
+async fn load_list_into_server_session(
+    client: &Client,
+    domain: &str,
+    export_filters: &FileoExportFilters,
+) -> Result<()> {
+    let request =
+        prepare_request_for_loading_list_into_server_session(client, domain, export_filters);
+    let response = send_with_retries(request)
+        .await
+        .map_err(log_message_and_return(
+            "The server couldn't load the list.",
+            CantLoadListOnServer,
+        ))?;
     let status = response.status();
     if status.is_success() || status.is_redirection() {
         debug!("List loaded on server.");
@@ -99,10 +127,12 @@ async fn retrieve_download_link(
     download_link_regex: &Regex,
 ) -> Result<String> {
     let request = prepare_request_for_retrieving_download_link(client, host);
-    let response = request.send().await.map_err(log_message_and_return(
-        "Can't export list.",
-        CantRetrieveDownloadLink,
-    ))?;
+    let response = send_with_retries(request)
+        .await
+        .map_err(log_message_and_return(
+            "Can't export list.",
+            CantRetrieveDownloadLink,
+        ))?;
 
     let status = response.status();
     if !status.is_success() && !status.is_redirection() {
@@ -113,15 +143,22 @@ async fn retrieve_download_link(
         .text()
         .await
         .map_err(log_error_and_return(CantReadPageContent))?;
-    let regex = download_link_regex;
-    let file_url = regex.find(&page_content).ok_or(NoDownloadLink)?.as_str();
+    extract_download_link(&page_content, download_link_regex)
+}
+
+pub(super) fn extract_download_link(
+    page_content: &str,
+    download_link_regex: &Regex,
+) -> Result<String> {
+    let file_url = download_link_regex
+        .find(page_content)
+        .ok_or(NoDownloadLink)?
+        .as_str();
     Ok(file_url.to_owned())
 }
 
-async fn download_list(client: &Client, file_url: &str) -> Result<String> {
-    let response = client
-        .get(file_url)
-        .send()
+pub(super) async fn download_list(client: &Client, file_url: &str) -> Result<String> {
+    let response = send_with_retries(client.get(file_url))
         .await
         .map_err(log_message_and_return("Can't download list.", NotFound))?;
 
@@ -141,7 +178,7 @@ async fn download_list(client: &Client, file_url: &str) -> Result<String> {
 // endregion
 
 // region Requests preparation
-fn prepare_request_for_connection(
+pub(super) fn prepare_request_for_connection(
     client: &Client,
     domain: &str,
     credentials: &FileoCredentials,
@@ -160,11 +197,27 @@ fn prepare_request_for_connection(
         .body(body)
 }
 
-fn prepare_request_for_loading_list_into_server_session(
+pub(super) fn prepare_request_for_loading_list_into_server_session(
     client: &Client,
     domain: &str,
+    export_filters: &FileoExportFilters,
 ) -> RequestBuilder {
     let url = format!("{domain}/page.php?P=bo/extranet/adhesion/annuaire/index");
+    let format_date = |date: &Option<NaiveDate>| {
+        date.map(|date| date.format("%d/%m/%Y").to_string())
+            .unwrap_or_default()
+    };
+    let start_date = format_date(export_filters.start_date());
+    let end_date = format_date(export_filters.end_date());
+    let include_sub_structures = if *export_filters.include_sub_structures() {
+        "1"
+    } else {
+        "0"
+    };
+    let membership_type = export_filters
+        .membership_type()
+        .as_deref()
+        .unwrap_or_default();
     let arguments = [
         ("Action", "adherent_filtrer"),
         ("requestForm", "formFiltrer"),
@@ -176,8 +229,8 @@ fn prepare_request_for_loading_list_into_server_session(
         ("affich_text_numLicence", ""),
         ("affich_text_dateCreationFrom", ""),
         ("affich_text_dateCreationTo", ""),
-        ("affich_text_dateDebut", ""),
-        ("affich_text_dateFin", ""),
+        ("affich_text_dateDebut", start_date.as_str()),
+        ("affich_text_dateFin", end_date.as_str()),
         ("affich_text_dateSaisieDebut", ""),
         ("affich_text_dateSaisieFin", ""),
         ("affich_radio_statut", ""),
@@ -185,9 +238,12 @@ fn prepare_request_for_loading_list_into_server_session(
         ("affich_select_departementStructure", ""),
         ("affich_select_code", "2"),
         ("affich_text_code", ""),
-        ("affich_fixed_instanceId", "2012"),
-        ("affich_radio_structFille", "1"),
-        ("affich_select_typeAdhesion", ""),
+        (
+            "affich_fixed_instanceId",
+            export_filters.instance_id().as_str(),
+        ),
+        ("affich_radio_structFille", include_sub_structures),
+        ("affich_select_typeAdhesion", membership_type),
         ("affich_select_tarif", ""),
         ("affich_select_regle", ""),
         ("affich_select_nomGroupe", "3"),
@@ -200,7 +256,10 @@ fn prepare_request_for_loading_list_into_server_session(
         .body(body)
 }
 
-fn prepare_request_for_retrieving_download_link(client: &Client, domain: &str) -> RequestBuilder {
+pub(super) fn prepare_request_for_retrieving_download_link(
+    client: &Client,
+    domain: &str,
+) -> RequestBuilder {
     let url = format!("{domain}/includer.php?inc=ajax/adherent/adherent_export");
     let arguments = [
         ("requestForm", "formExport"),
@@ -218,6 +277,8 @@ fn prepare_request_for_retrieving_download_link(client: &Client, domain: &str) -
         ("option_checkbox_champs[dateAdhesionFin]", "dateAdhesionFin"),
         ("option_checkbox_champs[instanceNom]", "instanceNom"),
         ("option_checkbox_champs[instanceCode]", "instanceCode"),
+        ("option_checkbox_champs[typeAdhesion]", "typeAdhesion"),
+        ("option_checkbox_champs[tarif]", "tarif"),
         ("generation", "2"),
     ];
     let body = format_arguments_into_body(&arguments);
@@ -238,28 +299,60 @@ fn format_arguments_into_body(args: &[(&str, &str)]) -> String {
         .join("&")
 }
 
-fn parse_file(file_content: &str) -> Vec<Membership> {
+fn parse_file(file_content: &str) -> (Vec<Membership>, ImportReport) {
     let reader = BufReader::new(file_content.as_bytes());
+    // Rows with an unexpected number of fields are rejected while loading memberships,
+    // so that they can be reported along with their content.
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(b';')
+        .flexible(true)
         .from_reader(reader);
     load_memberships(&mut reader)
 }
 
-fn load_memberships<T>(reader: &mut Reader<T>) -> Vec<Membership>
+fn load_memberships<T>(reader: &mut Reader<T>) -> (Vec<Membership>, ImportReport)
 where
     T: std::io::Read,
 {
-    reader
-        .deserialize()
-        .filter_map(|result: Result<ImportedMembership, _>| match result {
-            Ok(membership) => Some(membership.into()),
+    let headers = reader.headers().ok().cloned();
+    let mut memberships = vec![];
+    let mut rejected_rows = vec![];
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
             Err(e) => {
+                let line = e.position().map(|position| position.line()).unwrap_or(0);
+                rejected_rows.push(RejectedRow::new(line, String::new(), e.to_string()));
                 log_message("Error while reading membership")(e);
-                None
+                continue;
             }
-        })
-        .collect::<Vec<_>>()
+        };
+
+        let line = record
+            .position()
+            .map(|position| position.line())
+            .unwrap_or(0);
+        let raw_record = record.iter().collect::<Vec<_>>().join(";");
+        let expected_fields = headers
+            .as_ref()
+            .map_or(record.len(), |headers| headers.len());
+        if expected_fields != record.len() {
+            let reason = format!("expected {expected_fields} fields, found {}", record.len());
+            error!("Error while reading membership\n{reason}");
+            rejected_rows.push(RejectedRow::new(line, raw_record, reason));
+            continue;
+        }
+
+        match record.deserialize::<ImportedMembership>(headers.as_ref()) {
+            Ok(membership) => memberships.push(membership.into()),
+            Err(e) => {
+                rejected_rows.push(RejectedRow::new(line, raw_record, e.to_string()));
+                log_message("Error while reading membership")(e);
+            }
+        }
+    }
+
+    (memberships, ImportReport::new(rejected_rows))
 }
 
 #[cfg(test)]
@@ -283,6 +376,7 @@ mod tests {
         let config = MembershipsProviderConfig::new(
             mock_server.uri(),
             Regex::new(&format!("{}/download\\.csv", mock_server.uri())).unwrap(),
+            FileoExportFilters::default(),
         );
         let credentials =
             FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
@@ -330,7 +424,10 @@ mod tests {
         let result = download_memberships_list(&config, &credentials)
             .await
             .unwrap();
-        assert_eq!(vec![get_expected_membership()], result);
+        assert_eq!(
+            (vec![get_expected_membership()], ImportReport::default()),
+            result
+        );
     }
 
     #[test]
@@ -479,7 +576,12 @@ mod tests {
 
         let client = build_client().unwrap();
 
-        let result = load_list_into_server_session(&client, &mock_server.uri()).await;
+        let result = load_list_into_server_session(
+            &client,
+            &mock_server.uri(),
+            &FileoExportFilters::default(),
+        )
+        .await;
         assert!(result.is_ok());
     }
 
@@ -500,7 +602,12 @@ mod tests {
 
         let client = build_client().unwrap();
 
-        let result = load_list_into_server_session(&client, &mock_server.uri()).await;
+        let result = load_list_into_server_session(
+            &client,
+            &mock_server.uri(),
+            &FileoExportFilters::default(),
+        )
+        .await;
         assert!(result.is_err_and(|e| matches!(e, Fileo(CantLoadListOnServer))));
     }
 
@@ -643,7 +750,11 @@ mod tests {
 
         let expected_body = "Action=adherent_filtrer&requestForm=formFiltrer&affich_select_nom=3&affich_text_nom&affich_select_prenom=3&affich_text_prenom&affich_select_majeur&affich_text_numLicence&affich_text_dateCreationFrom&affich_text_dateCreationTo&affich_text_dateDebut&affich_text_dateFin&affich_text_dateSaisieDebut&affich_text_dateSaisieFin&affich_radio_statut&affich_select_regionStructure&affich_select_departementStructure&affich_select_code=2&affich_text_code&affich_fixed_instanceId=2012&affich_radio_structFille=1&affich_select_typeAdhesion&affich_select_tarif&affich_select_regle&affich_select_nomGroupe=3&affich_text_nomGroupe";
 
-        let result = prepare_request_for_loading_list_into_server_session(&client, domain);
+        let result = prepare_request_for_loading_list_into_server_session(
+            &client,
+            domain,
+            &FileoExportFilters::default(),
+        );
 
         let result_request = result.build();
         assert!(result_request.is_ok());
@@ -663,6 +774,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_prepare_request_for_loading_list_into_server_session_with_filters() {
+        let client = build_client().unwrap();
+        let domain = "http://localhost:27001";
+        let export_filters = FileoExportFilters::new(
+            "1234".to_owned(),
+            false,
+            NaiveDate::from_ymd_opt(2024, 9, 1),
+            NaiveDate::from_ymd_opt(2025, 8, 31),
+            Some("5".to_owned()),
+        );
+
+        let expected_body = "Action=adherent_filtrer&requestForm=formFiltrer&affich_select_nom=3&affich_text_nom&affich_select_prenom=3&affich_text_prenom&affich_select_majeur&affich_text_numLicence&affich_text_dateCreationFrom&affich_text_dateCreationTo&affich_text_dateDebut=01/09/2024&affich_text_dateFin=31/08/2025&affich_text_dateSaisieDebut&affich_text_dateSaisieFin&affich_radio_statut&affich_select_regionStructure&affich_select_departementStructure&affich_select_code=2&affich_text_code&affich_fixed_instanceId=1234&affich_radio_structFille=0&affich_select_typeAdhesion=5&affich_select_tarif&affich_select_regle&affich_select_nomGroupe=3&affich_text_nomGroupe";
+
+        let result =
+            prepare_request_for_loading_list_into_server_session(&client, domain, &export_filters);
+
+        let request = result.build().unwrap();
+        assert_eq!(
+            expected_body,
+            String::from_utf8_lossy(request.body().unwrap().as_bytes().unwrap())
+        );
+    }
+
     #[test]
     fn should_prepare_request_for_retrieving_download_link() {
         let client = build_client().unwrap();
@@ -709,6 +844,27 @@ mod tests {
         );
     }
 
+    mod read_memberships_export {
+        use crate::fileo::download::read_memberships_export;
+        use dto::import_report::ImportReport;
+        use dto::membership::tests::{get_expected_membership, get_membership_as_csv};
+        use encoding::all::ISO_8859_1;
+        use encoding::{EncoderTrap, Encoding};
+
+        #[test]
+        fn success() {
+            let file_content = ISO_8859_1
+                .encode(&get_membership_as_csv(), EncoderTrap::Strict)
+                .unwrap();
+
+            let result = read_memberships_export(&file_content).unwrap();
+            assert_eq!(
+                (vec![get_expected_membership()], ImportReport::default()),
+                result
+            );
+        }
+    }
+
     mod load_memberships {
         use crate::fileo::download::load_memberships;
         use dto::membership::tests::{
@@ -723,9 +879,11 @@ mod tests {
 
             let mut reader = csv::ReaderBuilder::new()
                 .delimiter(b';')
+                .flexible(true)
                 .from_reader(BufReader::new(entry.as_bytes()));
-            let members = load_memberships(&mut reader);
+            let (members, report) = load_memberships(&mut reader);
             assert_eq!(vec![expected_member], members);
+            assert!(report.rejected_rows().is_empty());
         }
 
         #[test]
@@ -733,9 +891,14 @@ mod tests {
             let entry = get_malformed_membership_as_csv();
             let mut reader = csv::ReaderBuilder::new()
                 .delimiter(b';')
+                .flexible(true)
                 .from_reader(BufReader::new(entry.as_bytes()));
-            let members = load_memberships(&mut reader);
+            let (members, report) = load_memberships(&mut reader);
             assert!(members.is_empty(), "`members` is not empty.");
+            assert_eq!(1, report.rejected_rows().len());
+            let rejected_row = report.rejected_rows().first().unwrap();
+            assert_eq!(2, *rejected_row.line());
+            assert_eq!(entry.lines().nth(1).unwrap(), rejected_row.record());
         }
     }
 }
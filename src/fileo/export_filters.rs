@@ -0,0 +1,131 @@
+use crate::tools::env_args::retrieve_arg_value;
+use chrono::NaiveDate;
+use derive_getters::Getters;
+
+const INSTANCE_ID_ARG: &str = "--fileo-instance-id";
+const INCLUDE_SUB_STRUCTURES_ARG: &str = "--fileo-include-sub-structures";
+const MEMBERSHIP_START_DATE_ARG: &str = "--fileo-membership-start-date";
+const MEMBERSHIP_END_DATE_ARG: &str = "--fileo-membership-end-date";
+const MEMBERSHIP_TYPE_ARG: &str = "--fileo-membership-type";
+
+/// The instance of the Commission Nationale Monocycle in Fileo.
+const DEFAULT_INSTANCE_ID: &str = "2012";
+
+/// Filters applied to the Fileo memberships directory before exporting it.
+#[derive(Getters, Clone, Debug, PartialEq)]
+pub struct FileoExportFilters {
+    instance_id: String,
+    include_sub_structures: bool,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    membership_type: Option<String>,
+}
+
+impl FileoExportFilters {
+    pub fn new(
+        instance_id: String,
+        include_sub_structures: bool,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+        membership_type: Option<String>,
+    ) -> Self {
+        Self {
+            instance_id,
+            include_sub_structures,
+            start_date,
+            end_date,
+            membership_type,
+        }
+    }
+}
+
+impl Default for FileoExportFilters {
+    fn default() -> Self {
+        Self::new(DEFAULT_INSTANCE_ID.to_owned(), true, None, None, None)
+    }
+}
+
+/// Build the export filters from the args passed to the app.
+/// Missing or invalid args fall back to their default value.
+pub fn retrieve_fileo_export_filters() -> FileoExportFilters {
+    let default = FileoExportFilters::default();
+    let instance_id = retrieve_arg_value(INSTANCE_ID_ARG).unwrap_or(default.instance_id);
+    let include_sub_structures = retrieve_arg_value(INCLUDE_SUB_STRUCTURES_ARG)
+        .and_then(|include| include.parse::<bool>().ok())
+        .unwrap_or(default.include_sub_structures);
+    let start_date = retrieve_date(MEMBERSHIP_START_DATE_ARG);
+    let end_date = retrieve_date(MEMBERSHIP_END_DATE_ARG);
+    let membership_type = retrieve_arg_value(MEMBERSHIP_TYPE_ARG);
+
+    FileoExportFilters::new(
+        instance_id,
+        include_sub_structures,
+        start_date,
+        end_date,
+        membership_type,
+    )
+}
+
+fn retrieve_date(arg_name: &str) -> Option<NaiveDate> {
+    let value = retrieve_arg_value(arg_name)?;
+    match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        Ok(date) => Some(date),
+        Err(error) => {
+            warn!("Ignoring invalid date `{value}` for {arg_name}: {error}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_fileo_export_filters {
+        use crate::fileo::export_filters::{
+            FileoExportFilters, INCLUDE_SUB_STRUCTURES_ARG, INSTANCE_ID_ARG,
+            MEMBERSHIP_END_DATE_ARG, MEMBERSHIP_START_DATE_ARG, MEMBERSHIP_TYPE_ARG,
+            retrieve_fileo_export_filters,
+        };
+        use crate::tools::env_args::with_env_args;
+        use chrono::NaiveDate;
+
+        #[test]
+        fn success() {
+            let args = vec![
+                format!("{INSTANCE_ID_ARG}=1234"),
+                format!("{INCLUDE_SUB_STRUCTURES_ARG}=false"),
+                format!("{MEMBERSHIP_START_DATE_ARG}=2024-09-01"),
+                format!("{MEMBERSHIP_END_DATE_ARG}=2025-08-31"),
+                format!("{MEMBERSHIP_TYPE_ARG}=5"),
+            ];
+
+            let filters = with_env_args(args, retrieve_fileo_export_filters);
+            assert_eq!(
+                FileoExportFilters::new(
+                    "1234".to_owned(),
+                    false,
+                    NaiveDate::from_ymd_opt(2024, 9, 1),
+                    NaiveDate::from_ymd_opt(2025, 8, 31),
+                    Some("5".to_owned()),
+                ),
+                filters
+            );
+        }
+
+        #[test]
+        fn default_when_missing() {
+            let filters = with_env_args(vec![], retrieve_fileo_export_filters);
+            assert_eq!(FileoExportFilters::default(), filters);
+        }
+
+        #[test]
+        fn ignore_invalid_values() {
+            let args = vec![
+                format!("{INCLUDE_SUB_STRUCTURES_ARG}=maybe"),
+                format!("{MEMBERSHIP_START_DATE_ARG}=01/09/2024"),
+            ];
+
+            let filters = with_env_args(args, retrieve_fileo_export_filters);
+            assert_eq!(FileoExportFilters::default(), filters);
+        }
+    }
+}
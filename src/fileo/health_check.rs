@@ -0,0 +1,314 @@
+use crate::error::Result;
+use crate::fileo::credentials::FileoCredentials;
+use crate::fileo::download::{
+    check_login_page, download_list, extract_download_link, prepare_request_for_connection,
+    prepare_request_for_loading_list_into_server_session,
+    prepare_request_for_retrieving_download_link,
+};
+use crate::fileo::imported_membership::EXPECTED_HEADERS;
+use crate::membership::config::MembershipsProviderConfig;
+use crate::tools::web::{build_client, send_with_retries};
+use chrono::Utc;
+use dto::fileo_health::{FileoFlowStep, FileoHealthReport};
+use regex::Regex;
+use reqwest::{Client, RequestBuilder, StatusCode};
+
+const MAX_SNIPPET_LENGTH: usize = 1000;
+
+/// Why a step broke: the step itself, the reason and the unexpected content, if any.
+type StepFailure = (FileoFlowStep, String, Option<String>);
+
+/// Run the whole Fileo flow, from the login to the download of the memberships list,
+/// without importing anything.
+/// If a step doesn't behave as expected, which most likely means Fileo has changed,
+/// then the report tells which one, along with a sanitized snippet of what has been received.
+pub async fn check_fileo_health(
+    memberships_provider_config: &MembershipsProviderConfig,
+    credentials: &FileoCredentials,
+) -> Result<FileoHealthReport> {
+    let client = build_client()?;
+    let result = run_flow(&client, memberships_provider_config, credentials).await;
+    let now = Utc::now().naive_local();
+
+    Ok(match result {
+        Ok(()) => FileoHealthReport::healthy(now),
+        Err((step, reason, content)) => {
+            warn!("Fileo health check failed [step: {step}, reason: {reason}]");
+            let snippet = content.map(|content| sanitize_snippet(&content, credentials));
+            FileoHealthReport::broken(now, step, reason, snippet)
+        }
+    })
+}
+
+async fn run_flow(
+    client: &Client,
+    memberships_provider_config: &MembershipsProviderConfig,
+    credentials: &FileoCredentials,
+) -> std::result::Result<(), StepFailure> {
+    let host = memberships_provider_config.host();
+
+    let request = prepare_request_for_connection(client, host, credentials);
+    let (status, page_content) = send(request, FileoFlowStep::Login).await?;
+    if !status.is_success() {
+        return Err(unexpected_status(
+            FileoFlowStep::Login,
+            status,
+            page_content,
+        ));
+    }
+    check_login_page(&page_content).map_err(|error| {
+        (
+            FileoFlowStep::Login,
+            format!("{error:?}"),
+            Some(page_content),
+        )
+    })?;
+
+    let request = prepare_request_for_loading_list_into_server_session(
+        client,
+        host,
+        memberships_provider_config.export_filters(),
+    );
+    let (status, page_content) = send(request, FileoFlowStep::Filter).await?;
+    if !status.is_success() && !status.is_redirection() {
+        return Err(unexpected_status(
+            FileoFlowStep::Filter,
+            status,
+            page_content,
+        ));
+    }
+
+    let request = prepare_request_for_retrieving_download_link(client, host);
+    let (status, page_content) = send(request, FileoFlowStep::ExportLink).await?;
+    if !status.is_success() && !status.is_redirection() {
+        return Err(unexpected_status(
+            FileoFlowStep::ExportLink,
+            status,
+            page_content,
+        ));
+    }
+    let download_url = extract_download_link(
+        &page_content,
+        memberships_provider_config.download_link_regex(),
+    )
+    .map_err(|error| {
+        (
+            FileoFlowStep::ExportLink,
+            format!("{error:?}"),
+            Some(page_content),
+        )
+    })?;
+
+    let file_content = download_list(client, &download_url)
+        .await
+        .map_err(|error| (FileoFlowStep::Download, format!("{error:?}"), None))?;
+
+    check_headers(&file_content)
+}
+
+async fn send(
+    request: RequestBuilder,
+    step: FileoFlowStep,
+) -> std::result::Result<(StatusCode, String), StepFailure> {
+    let response = send_with_retries(request)
+        .await
+        .map_err(|error| (step, error.to_string(), None))?;
+    let status = response.status();
+    let page_content = response
+        .text()
+        .await
+        .map_err(|error| (step, error.to_string(), None))?;
+    Ok((status, page_content))
+}
+
+fn unexpected_status(step: FileoFlowStep, status: StatusCode, page_content: String) -> StepFailure {
+    (
+        step,
+        format!("Unexpected status {status}"),
+        Some(page_content),
+    )
+}
+
+/// Check that all the columns [ImportedMembership](crate::fileo::imported_membership::ImportedMembership)
+/// relies on are in the header of the file.
+fn check_headers(file_content: &str) -> std::result::Result<(), StepFailure> {
+    let header = file_content.lines().next().unwrap_or_default();
+    let columns = header.split(';').map(str::trim).collect::<Vec<_>>();
+    let missing_columns = EXPECTED_HEADERS
+        .iter()
+        .filter(|expected_column| !columns.contains(expected_column))
+        .copied()
+        .collect::<Vec<_>>();
+
+    if missing_columns.is_empty() {
+        Ok(())
+    } else {
+        Err((
+            FileoFlowStep::CsvHeader,
+            format!("Missing columns: {}", missing_columns.join(", ")),
+            Some(header.to_owned()),
+        ))
+    }
+}
+
+/// Keep only the text of the content, without scripts nor credentials, and truncate it.
+fn sanitize_snippet(content: &str, credentials: &FileoCredentials) -> String {
+    let scripts_regex = Regex::new(r"(?is)<(script|style)[^>]*>.*?</(script|style)>").unwrap();
+    let tags_regex = Regex::new(r"(?s)<[^>]*>").unwrap();
+
+    let content = scripts_regex.replace_all(content, " ");
+    let content = tags_regex.replace_all(&content, " ");
+    let mut content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    for secret in [credentials.login(), credentials.password()] {
+        if !secret.is_empty() {
+            content = content.replace(secret.as_str(), "[MASKED]");
+        }
+    }
+
+    content.chars().take(MAX_SNIPPET_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding::all::ISO_8859_1;
+    use encoding::{EncoderTrap, Encoding};
+    use wiremock::matchers::{body_string_contains, method, path, query_param_contains};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup_flow(mock_server: &MockServer, login_page: &str, csv_header: &str) {
+        let download_link = format!("{}/download.csv", mock_server.uri());
+        Mock::given(method("POST"))
+            .and(path("/page.php"))
+            .and(body_string_contains("Action=connect_user"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(login_page))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/page.php"))
+            .and(query_param_contains(
+                "P",
+                "bo/extranet/adhesion/annuaire/index",
+            ))
+            .and(body_string_contains("Action=adherent_filtrer"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/includer.php"))
+            .and(query_param_contains("inc", "ajax/adherent/adherent_export"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("<p>Here is the download link: {download_link}</p>"),
+                "text/html",
+            ))
+            .mount(mock_server)
+            .await;
+        let csv_header = ISO_8859_1.encode(csv_header, EncoderTrap::Strict).unwrap();
+        Mock::given(method("GET"))
+            .and(path("/download.csv"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(csv_header, "text/csv"))
+            .mount(mock_server)
+            .await;
+    }
+
+    mod check_fileo_health {
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::fileo::export_filters::FileoExportFilters;
+        use crate::fileo::health_check::check_fileo_health;
+        use crate::fileo::health_check::tests::setup_flow;
+        use crate::fileo::imported_membership::EXPECTED_HEADERS;
+        use crate::membership::config::MembershipsProviderConfig;
+        use dto::fileo_health::FileoFlowStep;
+        use regex::Regex;
+        use wiremock::MockServer;
+
+        const LOGIN_PAGE: &str =
+            "Profil Commission thématique - UNSLL - Commission Nationale Monocycle";
+
+        fn create_config(uri: &str) -> MembershipsProviderConfig {
+            MembershipsProviderConfig::new(
+                uri.to_owned(),
+                Regex::new(&format!("{}/download\\.csv", uri)).unwrap(),
+                FileoExportFilters::default(),
+            )
+        }
+
+        fn create_credentials() -> FileoCredentials {
+            FileoCredentials::new("test_login".to_owned(), "test_password".to_owned())
+        }
+
+        #[async_test]
+        async fn healthy() {
+            let mock_server = MockServer::start().await;
+            setup_flow(&mock_server, LOGIN_PAGE, &EXPECTED_HEADERS.join(";")).await;
+
+            let report =
+                check_fileo_health(&create_config(&mock_server.uri()), &create_credentials())
+                    .await
+                    .unwrap();
+            assert!(report.is_healthy());
+        }
+
+        #[async_test]
+        async fn broken_when_login_page_changed() {
+            let mock_server = MockServer::start().await;
+            setup_flow(
+                &mock_server,
+                "<html><script>let a = 1;</script><h1>Bienvenue test_login</h1></html>",
+                &EXPECTED_HEADERS.join(";"),
+            )
+            .await;
+
+            let report =
+                check_fileo_health(&create_config(&mock_server.uri()), &create_credentials())
+                    .await
+                    .unwrap();
+            assert_eq!(&Some(FileoFlowStep::Login), report.failed_step());
+            assert_eq!(&Some("Bienvenue [MASKED]".to_owned()), report.snippet());
+        }
+
+        #[async_test]
+        async fn broken_when_csv_header_changed() {
+            let mock_server = MockServer::start().await;
+            let header = EXPECTED_HEADERS
+                .iter()
+                .map(|column| column.replace("Email", "Courriel"))
+                .collect::<Vec<_>>()
+                .join(";");
+            setup_flow(&mock_server, LOGIN_PAGE, &header).await;
+
+            let report =
+                check_fileo_health(&create_config(&mock_server.uri()), &create_credentials())
+                    .await
+                    .unwrap();
+            assert_eq!(&Some(FileoFlowStep::CsvHeader), report.failed_step());
+            assert_eq!(&Some("Missing columns: Email".to_owned()), report.reason());
+        }
+    }
+
+    mod sanitize_snippet {
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::fileo::health_check::{MAX_SNIPPET_LENGTH, sanitize_snippet};
+
+        #[test]
+        fn success() {
+            let credentials = FileoCredentials::new("login".to_owned(), "secret".to_owned());
+            let content = "<html><head><style>p { color: red; }</style></head>\n<body><p>Mot de passe   secret</p></body></html>";
+
+            assert_eq!(
+                "Mot de passe [MASKED]",
+                sanitize_snippet(content, &credentials)
+            );
+        }
+
+        #[test]
+        fn truncate_long_content() {
+            let credentials = FileoCredentials::new("login".to_owned(), "secret".to_owned());
+            let content = "é".repeat(MAX_SNIPPET_LENGTH * 2);
+
+            assert_eq!(
+                MAX_SNIPPET_LENGTH,
+                sanitize_snippet(&content, &credentials).chars().count()
+            );
+        }
+    }
+}
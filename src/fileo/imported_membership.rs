@@ -3,6 +3,23 @@ use derive_getters::Getters;
 use dto::membership::Membership;
 use rocket::serde::Deserialize;
 
+/// The columns of the Fileo export, as expected by [ImportedMembership].
+/// Should be kept in sync with the aliases below.
+pub(crate) const EXPECTED_HEADERS: [&str; 12] = [
+    "Nom d'usage",
+    "Prénom",
+    "Date de Naissance",
+    "Numéro d'adhérent",
+    "Téléphone portable",
+    "Email",
+    "Date Début d'adhésion",
+    "Date Fin d'adhésion",
+    "Nom de structure",
+    "Code de structure",
+    "Type d'adhésion",
+    "Tarif",
+];
+
 /// A membership as retrieved from Fileo.
 /// As all fields are in French and are sometimes formatted in a strange way,
 /// it is required to add a few annotations.
@@ -40,6 +57,19 @@ pub struct ImportedMembership {
     club: String,
     #[serde(alias = "Code de structure")]
     structure_code: String,
+    // Older exports don't have these columns, hence the default value
+    #[serde(
+        alias = "Type d'adhésion",
+        default,
+        deserialize_with = "optional_string_format::deserialize"
+    )]
+    membership_type: Option<String>,
+    #[serde(
+        alias = "Tarif",
+        default,
+        deserialize_with = "optional_string_format::deserialize"
+    )]
+    tariff: Option<String>,
 }
 
 impl From<ImportedMembership> for Membership {
@@ -55,6 +85,8 @@ impl From<ImportedMembership> for Membership {
             membership.end_date,
             membership.club,
             membership.structure_code,
+            membership.membership_type,
+            membership.tariff,
         )
     }
 }
@@ -126,8 +158,10 @@ pub mod tests {
             end_date: NaiveDate::from_ymd_opt(2025, 10, 11).unwrap(),
             club: "Best Club".to_owned(),
             structure_code: "A12345".to_owned(),
+            membership_type: Some("Compétition".to_owned()),
+            tariff: Some("Adulte".to_owned()),
         };
-        let json = r#"{"Nom d'usage":"Doe","Prénom":"John","Date de Naissance":"11-10-2000","Numéro d'adhérent":"42","Téléphone portable":"+33 6 12 34 56 78","Email":"john.doe@yopmail.com","Date Début d'adhésion":"11-10-2024","Date Fin d'adhésion":"11-10-2025","Nom de structure":"Best Club","Code de structure":"A12345"}"#;
+        let json = r#"{"Nom d'usage":"Doe","Prénom":"John","Date de Naissance":"11-10-2000","Numéro d'adhérent":"42","Téléphone portable":"+33 6 12 34 56 78","Email":"john.doe@yopmail.com","Date Début d'adhésion":"11-10-2024","Date Fin d'adhésion":"11-10-2025","Nom de structure":"Best Club","Code de structure":"A12345","Type d'adhésion":"Compétition","Tarif":"Adulte"}"#;
         let result = json::from_str(json);
 
         assert!(result.is_ok());
@@ -147,6 +181,8 @@ pub mod tests {
             end_date: NaiveDate::from_ymd_opt(2025, 10, 11).unwrap(),
             club: "Best Club".to_owned(),
             structure_code: "A12345".to_owned(),
+            membership_type: None,
+            tariff: None,
         };
         let json = r#"{"Nom d'usage":"Doe","Prénom":"John","Date de Naissance":"","Numéro d'adhérent":"42","Téléphone portable":"","Email":"john.doe@yopmail.com","Date Début d'adhésion":"11-10-2024","Date Fin d'adhésion":"11-10-2025","Nom de structure":"Best Club","Code de structure":"A12345"}"#;
         let result = json::from_str(json);
@@ -154,4 +190,26 @@ pub mod tests {
         assert!(result.is_ok());
         assert_eq!(membership, result.unwrap())
     }
+
+    #[test]
+    fn should_deserialize_with_expected_headers() {
+        let headers = csv::StringRecord::from(super::EXPECTED_HEADERS.to_vec());
+        let record = csv::StringRecord::from(vec![
+            "Doe",
+            "John",
+            "11-10-2000",
+            "42",
+            "",
+            "john.doe@yopmail.com",
+            "11-10-2024",
+            "11-10-2025",
+            "Best Club",
+            "A12345",
+            "Loisir",
+            "Enfant",
+        ]);
+
+        let result = record.deserialize::<ImportedMembership>(Some(&headers));
+        assert!(result.is_ok());
+    }
 }
@@ -2,4 +2,7 @@ pub(crate) mod authentication;
 pub(crate) mod credentials;
 pub(crate) mod download;
 pub mod error;
+pub(crate) mod export_filters;
+pub(crate) mod health_check;
 pub(crate) mod imported_membership;
+pub(crate) mod scheduled_refresh;
@@ -0,0 +1,509 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao::last_update::{UpdatableElement, get_last_update, update};
+use crate::database::error::DatabaseError;
+use crate::error::Result;
+use crate::fileo::credentials::FileoCredentials;
+use crate::fileo::download::download_memberships_list;
+use crate::import_history::ImportTracker;
+use crate::membership::config::MembershipsProviderConfig;
+use crate::membership::import::{import_counts, import_memberships, import_outcome_of};
+#[cfg(not(test))]
+use crate::notification::error::NotificationError;
+#[cfg(not(test))]
+use crate::tools::email::send_email;
+use crate::tools::env_args::retrieve_arg_value;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use derive_getters::Getters;
+use diesel::r2d2::{ConnectionManager, Pool};
+use dto::import_history::{ImportCounts, ImportOutcome, ImportSource, ImportedElement};
+use rocket::fairing::AdHoc;
+use std::time::Duration;
+
+const REFRESH_LOGIN_ARG: &str = "--fileo-refresh-login";
+const REFRESH_PASSWORD_ARG: &str = "--fileo-refresh-password";
+const REFRESH_INTERVAL_ARG: &str = "--fileo-refresh-interval-hours";
+const REFRESH_ALERT_DELAY_ARG: &str = "--fileo-refresh-alert-delay-hours";
+const ADMIN_ADDRESSES_ARG: &str = "--admin-addresses";
+const DEFAULT_REFRESH_INTERVAL_IN_HOURS: u64 = 24;
+const DEFAULT_REFRESH_ALERT_DELAY_IN_HOURS: i64 = 72;
+
+#[derive(Getters, Clone, Debug)]
+pub struct ScheduledRefreshConfig {
+    credentials: FileoCredentials,
+    interval: Duration,
+    alert_delay: TimeDelta,
+    admin_addresses: Vec<String>,
+}
+
+/// Build the scheduled refresh config from the args.
+/// Return None if no service account has been provided, in which case the memberships won't be refreshed automatically.
+pub fn retrieve_scheduled_refresh_config() -> Option<ScheduledRefreshConfig> {
+    let login = retrieve_arg_value(REFRESH_LOGIN_ARG)?;
+    let password = retrieve_arg_value(REFRESH_PASSWORD_ARG)?;
+    let interval_in_hours = retrieve_arg_value(REFRESH_INTERVAL_ARG)
+        .and_then(|interval| interval.parse::<u64>().ok())
+        .filter(|interval| *interval > 0)
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL_IN_HOURS);
+    let alert_delay_in_hours = retrieve_arg_value(REFRESH_ALERT_DELAY_ARG)
+        .and_then(|delay| delay.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_REFRESH_ALERT_DELAY_IN_HOURS);
+    let admin_addresses = retrieve_arg_value(ADMIN_ADDRESSES_ARG)
+        .map(|addresses| {
+            addresses
+                .split(',')
+                .map(|address| address.trim().to_owned())
+                .filter(|address| !address.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(ScheduledRefreshConfig {
+        credentials: FileoCredentials::new(login, password),
+        interval: Duration::from_secs(interval_in_hours * 3600),
+        alert_delay: TimeDelta::hours(alert_delay_in_hours),
+        admin_addresses,
+    })
+}
+
+/// Once the server is launched, periodically refresh the memberships list in the background.
+/// The first refresh occurs right after the launch.
+pub fn scheduled_refresh_fairing(config: Option<ScheduledRefreshConfig>) -> AdHoc {
+    AdHoc::on_liftoff("Scheduled memberships refresh", move |rocket| {
+        let config = config.clone();
+        Box::pin(async move {
+            let Some(config) = config else {
+                debug!(
+                    "No Fileo service account provided, memberships won't be refreshed automatically."
+                );
+                return;
+            };
+            let pool = rocket.state::<Pool<ConnectionManager<DatabaseConnection>>>();
+            let memberships_provider_config = rocket.state::<MembershipsProviderConfig>();
+            let (Some(pool), Some(memberships_provider_config)) =
+                (pool, memberships_provider_config)
+            else {
+                error!("Can't schedule memberships refresh: missing state.");
+                return;
+            };
+            let pool = pool.clone();
+            let memberships_provider_config = memberships_provider_config.clone();
+
+            rocket::tokio::spawn(async move {
+                let mut interval = rocket::tokio::time::interval(*config.interval());
+                loop {
+                    interval.tick().await;
+                    if let Err(error) =
+                        refresh_memberships(&pool, &memberships_provider_config, &config).await
+                    {
+                        error!("Scheduled memberships refresh failed\n{error:#?}");
+                    }
+                }
+            });
+        })
+    })
+}
+
+/// Download the memberships list with the service account and import it.
+/// Successes and failures are recorded. A refused import is a failure too.
+/// If the refresh has been failing for too long, then the admins are notified.
+pub(crate) async fn refresh_memberships(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    memberships_provider_config: &MembershipsProviderConfig,
+    config: &ScheduledRefreshConfig,
+) -> Result<()> {
+    let tracker = ImportTracker::new(
+        ImportedElement::Memberships,
+        ImportSource::Scheduler,
+        Some(config.credentials().login().clone()),
+    );
+    let downloaded =
+        download_memberships_list(memberships_provider_config, config.credentials()).await;
+    // The connection is only taken once the download is over, so that it's not held while waiting.
+    let mut connection = pool.get().map_err(DatabaseError::from)?;
+    let result = match downloaded {
+        Ok((memberships, report)) => {
+            match import_memberships(&mut connection, &memberships, &report, false) {
+                Ok(diff) => {
+                    tracker.succeeded(pool, import_counts(&diff, &report));
+                    Ok(())
+                }
+                Err(error) => {
+                    let counts = ImportCounts::new(0, 0, 0, report.rejected_rows().len());
+                    tracker.failed(pool, import_outcome_of(&error), counts, &error);
+                    Err(error)
+                }
+            }
+        }
+        Err(error) => {
+            tracker.failed(
+                pool,
+                ImportOutcome::Failure,
+                ImportCounts::default(),
+                &error,
+            );
+            Err(error)
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            update(
+                &mut connection,
+                &UpdatableElement::MembershipsRefreshSuccess,
+            )?;
+            Ok(())
+        }
+        Err(error) => {
+            update(
+                &mut connection,
+                &UpdatableElement::MembershipsRefreshFailure,
+            )?;
+            alert_admins_if_needed(&mut connection, config).await?;
+            Err(error)
+        }
+    }
+}
+
+async fn alert_admins_if_needed(
+    connection: &mut DatabaseConnection,
+    config: &ScheduledRefreshConfig,
+) -> Result<()> {
+    let last_success = get_last_update(connection, &UpdatableElement::Memberships)?;
+    let last_alert = get_last_update(connection, &UpdatableElement::MembershipsRefreshAlert)?;
+    let now = Utc::now().naive_local();
+    if !should_alert(&now, last_success, last_alert, config.alert_delay()) {
+        return Ok(());
+    }
+
+    let last_success = match last_success {
+        None => "jamais".to_owned(),
+        Some(last_success) => format!("le {}", last_success.format("%d/%m/%Y à %H:%M")),
+    };
+    let body = format!(
+        "Bonjour,\n\nLa mise à jour automatique de la liste des licences depuis Fileo échoue. La dernière mise à jour réussie a eu lieu {last_success}.\nMerci de vérifier le compte de service ainsi que l'accès à Fileo."
+    );
+    let recipients = config
+        .admin_addresses()
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if recipients.is_empty() {
+        warn!("Memberships refresh has been failing for too long, but no admin can be alerted.");
+    } else {
+        #[cfg(not(test))] // We don't want to send emails in test mode.
+        send_email(&recipients, "Échec de la mise à jour des licences", &body)
+            .await
+            .map_err(NotificationError::from)?;
+        #[cfg(test)]
+        println!(
+            "Mocking email sending [recipients: {:?}, body: {}]",
+            recipients, body
+        );
+    }
+
+    update(connection, &UpdatableElement::MembershipsRefreshAlert)?;
+    Ok(())
+}
+
+/// Admins should be alerted when memberships haven't been updated for longer than the alert delay.
+/// In such a case, they are alerted again once per alert delay.
+fn should_alert(
+    now: &NaiveDateTime,
+    last_success: Option<NaiveDateTime>,
+    last_alert: Option<NaiveDateTime>,
+    alert_delay: &TimeDelta,
+) -> bool {
+    let failing_for_too_long = match last_success {
+        None => true,
+        Some(last_success) => *now - last_success > *alert_delay,
+    };
+    let recently_alerted = match last_alert {
+        None => false,
+        Some(last_alert) => *now - last_alert < *alert_delay,
+    };
+
+    failing_for_too_long && !recently_alerted
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_scheduled_refresh_config {
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::fileo::scheduled_refresh::{
+            DEFAULT_REFRESH_ALERT_DELAY_IN_HOURS, DEFAULT_REFRESH_INTERVAL_IN_HOURS,
+            retrieve_scheduled_refresh_config,
+        };
+        use crate::tools::env_args::with_env_args;
+        use chrono::TimeDelta;
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            let config = with_env_args(
+                vec![
+                    "--fileo-refresh-login=login".to_owned(),
+                    "--fileo-refresh-password=password".to_owned(),
+                    "--fileo-refresh-interval-hours=6".to_owned(),
+                    "--fileo-refresh-alert-delay-hours=12".to_owned(),
+                    "--admin-addresses=admin@address.com, other@address.com".to_owned(),
+                ],
+                retrieve_scheduled_refresh_config,
+            )
+            .unwrap();
+
+            assert_eq!(
+                &FileoCredentials::new("login".to_owned(), "password".to_owned()),
+                config.credentials()
+            );
+            assert_eq!(&Duration::from_secs(6 * 3600), config.interval());
+            assert_eq!(&TimeDelta::hours(12), config.alert_delay());
+            assert_eq!(
+                &vec![
+                    "admin@address.com".to_owned(),
+                    "other@address.com".to_owned()
+                ],
+                config.admin_addresses()
+            );
+        }
+
+        #[test]
+        fn success_with_defaults() {
+            let config = with_env_args(
+                vec![
+                    "--fileo-refresh-login=login".to_owned(),
+                    "--fileo-refresh-password=password".to_owned(),
+                ],
+                retrieve_scheduled_refresh_config,
+            )
+            .unwrap();
+
+            assert_eq!(
+                &Duration::from_secs(DEFAULT_REFRESH_INTERVAL_IN_HOURS * 3600),
+                config.interval()
+            );
+            assert_eq!(
+                &TimeDelta::hours(DEFAULT_REFRESH_ALERT_DELAY_IN_HOURS),
+                config.alert_delay()
+            );
+            assert!(config.admin_addresses().is_empty());
+        }
+
+        #[test]
+        fn none_without_service_account() {
+            let config = with_env_args(
+                vec!["--fileo-refresh-login=login".to_owned()],
+                retrieve_scheduled_refresh_config,
+            );
+
+            assert!(config.is_none());
+        }
+    }
+
+    mod should_alert {
+        use crate::fileo::scheduled_refresh::should_alert;
+        use chrono::{NaiveDateTime, TimeDelta};
+
+        fn now() -> NaiveDateTime {
+            NaiveDateTime::parse_from_str("2025-04-25 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+        }
+
+        #[test]
+        fn when_never_succeeded() {
+            assert!(should_alert(&now(), None, None, &TimeDelta::hours(72)));
+        }
+
+        #[test]
+        fn not_when_recently_succeeded() {
+            let last_success = now() - TimeDelta::hours(24);
+            assert!(!should_alert(
+                &now(),
+                Some(last_success),
+                None,
+                &TimeDelta::hours(72)
+            ));
+        }
+
+        #[test]
+        fn when_failing_for_too_long() {
+            let last_success = now() - TimeDelta::hours(73);
+            assert!(should_alert(
+                &now(),
+                Some(last_success),
+                None,
+                &TimeDelta::hours(72)
+            ));
+        }
+
+        #[test]
+        fn not_when_recently_alerted() {
+            let last_success = now() - TimeDelta::hours(100);
+            let last_alert = now() - TimeDelta::hours(20);
+            assert!(!should_alert(
+                &now(),
+                Some(last_success),
+                Some(last_alert),
+                &TimeDelta::hours(72)
+            ));
+        }
+    }
+
+    mod refresh_memberships {
+        use crate::database::DatabaseConnection;
+        use crate::database::dao::last_update::{UpdatableElement, get_last_update};
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::fileo::export_filters::FileoExportFilters;
+        use crate::fileo::scheduled_refresh::{ScheduledRefreshConfig, refresh_memberships};
+        use crate::membership::config::MembershipsProviderConfig;
+        use chrono::TimeDelta;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::import_history::{ImportOutcome, ImportSource};
+        use dto::membership::tests::{get_expected_membership, get_membership_as_csv};
+        use encoding::all::ISO_8859_1;
+        use encoding::{EncoderTrap, Encoding};
+        use regex::Regex;
+        use rocket::tokio::runtime::Runtime;
+        use std::time::Duration;
+        use wiremock::matchers::{body_string_contains, method, path, query_param_contains};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        fn create_config() -> ScheduledRefreshConfig {
+            ScheduledRefreshConfig {
+                credentials: FileoCredentials::new(
+                    "service_login".to_owned(),
+                    "service_password".to_owned(),
+                ),
+                interval: Duration::from_secs(3600),
+                alert_delay: TimeDelta::hours(72),
+                admin_addresses: vec!["admin@address.com".to_owned()],
+            }
+        }
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mock_server = MockServer::start().await;
+                let download_link = format!("{}/download.csv", mock_server.uri());
+                Mock::given(method("POST"))
+                    .and(path("/page.php"))
+                    .and(body_string_contains("Action=connect_user"))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(
+                        "Profil Commission thématique - UNSLL - Commission Nationale Monocycle",
+                    ))
+                    .mount(&mock_server)
+                    .await;
+                Mock::given(method("POST"))
+                    .and(path("/page.php"))
+                    .and(query_param_contains(
+                        "P",
+                        "bo/extranet/adhesion/annuaire/index",
+                    ))
+                    .and(body_string_contains("Action=adherent_filtrer"))
+                    .respond_with(ResponseTemplate::new(200))
+                    .mount(&mock_server)
+                    .await;
+                Mock::given(method("POST"))
+                    .and(path("/includer.php"))
+                    .and(query_param_contains("inc", "ajax/adherent/adherent_export"))
+                    .respond_with(ResponseTemplate::new(200).set_body_raw(
+                        format!("<p>Here is the download link: {download_link}</p>"),
+                        "text/html",
+                    ))
+                    .mount(&mock_server)
+                    .await;
+                let message_in_latin1 = ISO_8859_1
+                    .encode(&get_membership_as_csv(), EncoderTrap::Strict)
+                    .unwrap();
+                Mock::given(method("GET"))
+                    .and(path("/download.csv"))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_raw(message_in_latin1.as_slice(), "text/csv"),
+                    )
+                    .mount(&mock_server)
+                    .await;
+
+                let memberships_provider_config = MembershipsProviderConfig::new(
+                    mock_server.uri(),
+                    Regex::new(&format!("{}/download\\.csv", mock_server.uri())).unwrap(),
+                    FileoExportFilters::default(),
+                );
+
+                refresh_memberships(&pool, &memberships_provider_config, &create_config())
+                    .await
+                    .unwrap();
+
+                let mut connection = pool.get().unwrap();
+                assert_eq!(
+                    vec![get_expected_membership()],
+                    dao::membership::retrieve_memberships(&mut connection).unwrap()
+                );
+                assert!(
+                    get_last_update(
+                        &mut connection,
+                        &UpdatableElement::MembershipsRefreshSuccess
+                    )
+                    .unwrap()
+                    .is_some()
+                );
+                assert!(
+                    get_last_update(
+                        &mut connection,
+                        &UpdatableElement::MembershipsRefreshFailure
+                    )
+                    .unwrap()
+                    .is_none()
+                );
+
+                let history = dao::import_history::retrieve(&mut connection, None).unwrap();
+                assert_eq!(1, history.len());
+                assert_eq!(&ImportSource::Scheduler, history[0].source());
+                assert_eq!(&Some("service_login".to_owned()), history[0].actor());
+                assert_eq!(&ImportOutcome::Success, history[0].outcome());
+                assert_eq!(&1, history[0].counts().inserted());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn fail_and_alert() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mock_server = MockServer::start().await;
+                Mock::given(method("POST"))
+                    .and(path("/page.php"))
+                    .and(body_string_contains("Action=connect_user"))
+                    .respond_with(ResponseTemplate::new(500))
+                    .mount(&mock_server)
+                    .await;
+
+                let memberships_provider_config = MembershipsProviderConfig::new(
+                    mock_server.uri(),
+                    Regex::new(&format!("{}/download\\.csv", mock_server.uri())).unwrap(),
+                    FileoExportFilters::default(),
+                );
+
+                let result =
+                    refresh_memberships(&pool, &memberships_provider_config, &create_config())
+                        .await;
+                assert!(result.is_err());
+
+                let mut connection = pool.get().unwrap();
+                assert!(
+                    get_last_update(
+                        &mut connection,
+                        &UpdatableElement::MembershipsRefreshFailure
+                    )
+                    .unwrap()
+                    .is_some()
+                );
+                // Memberships have never been imported, so admins should have been alerted
+                assert!(
+                    get_last_update(&mut connection, &UpdatableElement::MembershipsRefreshAlert)
+                        .unwrap()
+                        .is_some()
+                );
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::database::error::DatabaseError;
+use chrono::{NaiveDateTime, Utc};
+use diesel::r2d2::{ConnectionManager, Pool};
+use dto::import_history::{
+    ImportCounts, ImportHistoryEntry, ImportOutcome, ImportSource, ImportedElement,
+};
+use std::fmt::Debug;
+
+/// An import in progress, to be recorded in the import history once it's over.
+pub struct ImportTracker {
+    element: ImportedElement,
+    source: ImportSource,
+    actor: Option<String>,
+    start: NaiveDateTime,
+}
+
+impl ImportTracker {
+    /// Start tracking an import now.
+    pub fn new(element: ImportedElement, source: ImportSource, actor: Option<String>) -> Self {
+        Self {
+            element,
+            source,
+            actor,
+            start: Utc::now().naive_local(),
+        }
+    }
+
+    /// Record a successful import.
+    pub fn succeeded(
+        self,
+        pool: &Pool<ConnectionManager<DatabaseConnection>>,
+        counts: ImportCounts,
+    ) {
+        self.finish(pool, ImportOutcome::Success, counts, None);
+    }
+
+    /// Record a refused or failed import, along with why.
+    pub fn failed<E: Debug>(
+        self,
+        pool: &Pool<ConnectionManager<DatabaseConnection>>,
+        outcome: ImportOutcome,
+        counts: ImportCounts,
+        error: &E,
+    ) {
+        self.finish(pool, outcome, counts, Some(format!("{error:?}")));
+    }
+
+    /// As the import is already over, failing to record it is only logged.
+    fn finish(
+        self,
+        pool: &Pool<ConnectionManager<DatabaseConnection>>,
+        outcome: ImportOutcome,
+        counts: ImportCounts,
+        error: Option<String>,
+    ) {
+        let entry = ImportHistoryEntry::new(
+            self.element,
+            self.source,
+            self.actor,
+            self.start,
+            Utc::now().naive_local(),
+            counts,
+            outcome,
+            error,
+        );
+        let result = pool
+            .get()
+            .map_err(DatabaseError::from)
+            .and_then(|mut connection| dao::import_history::insert(&mut connection, &entry));
+        if let Err(error) = result {
+            error!(
+                "Can't record import in history [element: {}, outcome: {outcome}]\n{error:#?}",
+                entry.element()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod import_tracker {
+        use crate::database::{dao, with_temp_database};
+        use crate::import_history::ImportTracker;
+        use dto::import_history::{ImportCounts, ImportOutcome, ImportSource, ImportedElement};
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                ImportTracker::new(
+                    ImportedElement::Memberships,
+                    ImportSource::ManualUpload,
+                    Some("jon".to_owned()),
+                )
+                .succeeded(&pool, ImportCounts::new(0, 2, 0, 1));
+                ImportTracker::new(ImportedElement::UdaInstances, ImportSource::Scraping, None)
+                    .failed(
+                        &pool,
+                        ImportOutcome::Failure,
+                        ImportCounts::default(),
+                        &"Timeout",
+                    );
+
+                let mut connection = pool.get().unwrap();
+                let entries = dao::import_history::retrieve(&mut connection, None).unwrap();
+                assert_eq!(2, entries.len());
+                assert_eq!(&ImportOutcome::Failure, entries[0].outcome());
+                assert_eq!(&Some("\"Timeout\"".to_owned()), entries[0].error());
+                assert_eq!(&Some("jon".to_owned()), entries[1].actor());
+                assert_eq!(&ImportCounts::new(0, 2, 0, 1), entries[1].counts());
+                assert_eq!(&None, entries[1].error());
+            })
+        }
+    }
+}
@@ -1,10 +1,12 @@
 #[macro_use]
 extern crate rocket;
+mod audit;
 pub(crate) mod database;
 #[cfg(feature = "demo")]
 mod demo_mock_server;
 mod error;
 mod fileo;
+mod import_history;
 mod membership;
 mod notification;
 mod tools;
@@ -12,6 +14,7 @@ mod uda;
 mod web;
 
 use crate::database::init_connection_pool;
+use crate::database::restore::restore_database_if_requested;
 #[cfg(feature = "demo")]
 use crate::demo_mock_server::init_demo;
 use crate::web::start_servers;
@@ -19,6 +22,7 @@ use crate::web::start_servers;
 #[launch]
 async fn rocket() -> _ {
     env_logger::init();
+    restore_database_if_requested().expect("Failed to restore database");
     let pool = init_connection_pool().expect("Failed to initialize database connection pool");
     #[cfg(feature = "demo")]
     init_demo().await;
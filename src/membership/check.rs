@@ -1,139 +1,450 @@
+use crate::database::DatabaseConnection;
 use crate::database::dao;
 use crate::database::error::DatabaseError::R2d2;
 use crate::error::{ApplicationError, Result};
-use diesel::SqliteConnection;
+use crate::membership::index::MembershipIndex;
+use crate::membership::similar_names::{MIN_FUZZY_CONFIDENCE, SearchedNames};
+use crate::tools::normalize;
+use chrono::NaiveDate;
 use diesel::r2d2::{ConnectionManager, Pool};
-use dto::checked_member::CheckResult::{Match, NoMatch, PartialMatch};
+use dto::checked_member::CheckResult::{Ambiguous, FuzzyMatch, Match, NoMatch, PartialMatch};
 use dto::checked_member::{CheckResult, CheckedMember};
+use dto::match_explanation::MatchingRule::{
+    EmailAddress, Identity, MembershipNumber, MembershipNumberAndIdentity,
+    MembershipNumberAndNames, Names, NoMatchingMembership, SimilarNames, UnknownMembershipNumber,
+};
+use dto::match_explanation::{ComparedField, MatchExplanation, MatchingRule};
 use dto::member_to_check::MemberToCheck;
+use dto::membership::Membership;
+use dto::membership_status::EventPeriod;
+use std::collections::{BTreeSet, HashSet};
 
-/// For each member, look into the database to check whether there is a match.
+/// Up to this number of members, only the memberships they may match by number or names are loaded.
+/// Longer lists are checked against all memberships, loaded at once,
+/// which is much faster than querying them for each member.
+const MAX_MEMBERS_FOR_TARGETED_LOAD: usize = 20;
+
+/// For each member, look into the known memberships to check whether there is a match.
 /// Matches are made in the following order:
 /// 1. If membership number and names match, that's a perfect match ✔
 /// 2. If membership number and identity match, that's also a perfect match ✔
 /// 3. If membership number matches, that's a partial match ✔
 /// 4. If the member to check has a membership number, but this number doesn't match anything, that's a no match ✖
 /// 5. If the names match, that's a partial match ✔
-/// 6. If the identity matches, that's a partial match ✔
-/// 7. If there has been no match so far, then that's a no match ✖
+///    If the birthdate matches as well, that's a perfect match ✔
+///    If the birthdate is known but differs, that's a namesake, which doesn't match ✖
+///    If several people match, that's an ambiguous result, which the checker has to settle ❓
+/// 6. If the identity matches, that's a partial match ✔ (birthdates are compared the same way)
+/// 7. If the email address matches, and the names or the identity are similar enough,
+///    that's a partial match ✔
+///    Members sharing an email address (e.g. a family) are told apart by their names,
+///    so that an unlicensed relative isn't matched with the membership of another one.
+/// 8. If the names or the identity are similar enough to the ones of a membership,
+///    that's a fuzzy match, which should be confirmed by the checker ❓
+/// 9. If there has been no match so far, then that's a no match ✖
+///
+/// Namesakes are never matched by their email address or by similar names.
+/// If a membership type is required (e.g. a competition license for a competition event),
+/// then perfect matches whose membership is of another type are downgraded to partial matches.
+/// If an event period is given, memberships are expected to cover the whole event.
+/// As the member may have renewed their membership in the meantime, all their memberships are
+/// looked at, and the one covering the end of the event is kept.
+///
+/// Each result is explained by the rule which produced it and by the fields that have been compared.
 pub fn check_members<T: MemberToCheck>(
-    pool: &Pool<ConnectionManager<SqliteConnection>>,
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
     members_to_check: Vec<T>,
+    required_membership_type: Option<&str>,
+    event_period: Option<EventPeriod>,
 ) -> Result<Vec<CheckedMember<T>>> {
-    Ok({
-        let mut result = vec![];
-
-        for member_to_check in members_to_check.into_iter() {
-            let mut connection = pool
-                .get()
-                .map_err(|error| ApplicationError::Database(R2d2(error.to_string())))?;
-            let checked_member = CheckedMember::new(
-                member_to_check.clone(),
-                check_member(&mut connection, &member_to_check)?,
-            );
-            result.push(checked_member);
+    let mut connection = pool
+        .get()
+        .map_err(|error| ApplicationError::Database(R2d2(error.to_string())))?;
+    let mut all_memberships_loaded = members_to_check.len() > MAX_MEMBERS_FOR_TARGETED_LOAD;
+    let mut index = if all_memberships_loaded {
+        load_all_memberships(&mut connection)?
+    } else {
+        load_memberships_matching(&mut connection, &members_to_check)?
+    };
+
+    let mut checked_members = Vec::with_capacity(members_to_check.len());
+    for member_to_check in members_to_check {
+        let (check_result, rule) = match check_member(&index, &member_to_check) {
+            (NoMatch, NoMatchingMembership) => {
+                // Email addresses and similar names can only be looked for among all memberships
+                if !all_memberships_loaded {
+                    index = load_all_memberships(&mut connection)?;
+                    all_memberships_loaded = true;
+                }
+                match find_email_match(&index, &member_to_check) {
+                    Some(check_result) => (check_result, EmailAddress),
+                    None => match find_fuzzy_match(&index, &member_to_check) {
+                        NoMatch => (NoMatch, NoMatchingMembership),
+                        check_result => (check_result, SimilarNames),
+                    },
+                }
+            }
+            result => result,
+        };
+        let (check_result, event_memberships) = match &event_period {
+            Some(event_period) => cover_event(&index, check_result, event_period),
+            None => (check_result, vec![]),
+        };
+        let check_result = enforce_membership_type(check_result, required_membership_type);
+        let explanation = explain(
+            rule,
+            &member_to_check,
+            &check_result,
+            required_membership_type,
+        );
+        checked_members.push(CheckedMember::new(
+            member_to_check,
+            check_result,
+            event_period,
+            event_memberships,
+            Some(explanation),
+        ));
+    }
+
+    Ok(checked_members)
+}
+
+fn load_all_memberships(connection: &mut DatabaseConnection) -> Result<MembershipIndex> {
+    Ok(MembershipIndex::new(dao::membership::retrieve_memberships(
+        connection,
+    )?))
+}
+
+/// Load the memberships sharing a membership number, names or an identity with the members,
+/// which are the only ones the rules 1 to 6 of [`check_members`] may pick,
+/// along with the other memberships of the same people, which may cover an event.
+fn load_memberships_matching<T: MemberToCheck>(
+    connection: &mut DatabaseConnection,
+    members_to_check: &[T],
+) -> Result<MembershipIndex> {
+    let mut membership_numbers = BTreeSet::new();
+    let mut identities = BTreeSet::new();
+    for member_to_check in members_to_check {
+        if let Some(membership_number) = member_to_check.membership_num() {
+            membership_numbers.insert(normalize(&membership_number));
         }
+        if let (Some(last_name), Some(first_name)) =
+            (member_to_check.last_name(), member_to_check.first_name())
+        {
+            identities.insert(format!(
+                "{}{}",
+                normalize(&last_name),
+                normalize(&first_name)
+            ));
+        }
+        if let Some(identity) = member_to_check.identity() {
+            identities.insert(normalize(&identity));
+        }
+    }
 
-        result
-    })
+    Ok(MembershipIndex::new(
+        dao::membership::find::all::by_membership_numbers_or_identities(
+            connection,
+            &membership_numbers,
+            &identities,
+        )?,
+    ))
 }
 
-/// Look into the database to check whether there is a match.
-/// Matches are made in the following order:
-/// 1. If membership number and names match, that's a perfect match ✔
-/// 2. If membership number and identity match, that's also a perfect match ✔
-/// 3. If membership number matches, that's a partial match ✔
-/// 4. If the member to check has a membership number, but this number doesn't match anything, that's a no match ✖
-/// 5. If the names match, that's a partial match ✔
-/// 6. If the identity matches, that's a partial match ✔
-/// 7. If there has been no match so far, then that's a no match ✖
+/// Look into the known memberships for a match by membership number, names or identity,
+/// following the rules 1 to 6 of [`check_members`].
+/// The rule which produced the result comes along with it.
 fn check_member<T: MemberToCheck>(
-    connection: &mut SqliteConnection,
+    index: &MembershipIndex,
     member_to_check: &T,
-) -> Result<CheckResult> {
+) -> (CheckResult, MatchingRule) {
     let membership_number = member_to_check.membership_num();
     let first_name = member_to_check.first_name();
     let last_name = member_to_check.last_name();
     let identity = member_to_check.identity();
+    let birthdate = member_to_check.birthdate();
 
-    if membership_number.is_some() {
-        if first_name.is_some() && last_name.is_some() {
-            let membership_number = membership_number
-                .clone()
-                .expect("There should be a value at this point");
-            let last_name = last_name
-                .clone()
-                .expect("There should be a value at this point");
-            let first_name = first_name
-                .clone()
-                .expect("There should be a value at this point");
-            if let Some(membership) = dao::membership::find::first::by_num_last_name_first_name(
-                connection,
-                &membership_number,
-                &last_name,
-                &first_name,
-            )? {
-                return Ok(Match(membership));
+    if let Some(membership_number) = membership_number {
+        if let (Some(last_name), Some(first_name)) = (&last_name, &first_name) {
+            if let Some(membership) =
+                index.first_by_num_last_name_first_name(&membership_number, last_name, first_name)
+            {
+                return (Match(membership.clone()), MembershipNumberAndNames);
             }
         }
 
-        if identity.is_some() {
-            let membership_number = membership_number
-                .clone()
-                .expect("There should be a value at this point");
-            let identity = identity
-                .clone()
-                .expect("There should be a value at this point");
-            if let Some(membership) = dao::membership::find::first::by_num_identity(
-                connection,
-                &membership_number,
-                &identity,
-            )? {
-                return Ok(Match(membership));
+        if let Some(identity) = &identity {
+            if let Some(membership) = index.first_by_num_identity(&membership_number, identity) {
+                return (Match(membership.clone()), MembershipNumberAndIdentity);
             }
         }
 
-        let membership_number = membership_number
-            .clone()
-            .expect("There should be a value at this point");
-        if let Some(membership) =
-            dao::membership::find::first::by_num(connection, &membership_number)?
-        {
-            return Ok(PartialMatch(membership));
+        if let Some(membership) = index.first_by_num(&membership_number) {
+            return (PartialMatch(membership.clone()), MembershipNumber);
         }
 
         // In case the membership number is provided, but it doesn't match anything,
         // then we consider there is no match, even though names or identity could match.
-        return Ok(NoMatch);
+        return (NoMatch, UnknownMembershipNumber);
     }
 
-    if first_name.is_some() && last_name.is_some() {
-        let last_name = last_name
-            .clone()
-            .expect("There should be a value at this point");
-        let first_name = first_name
-            .clone()
-            .expect("There should be a value at this point");
-        if let Some(membership) = dao::membership::find::first::by_last_name_first_name(
-            connection,
-            &last_name,
-            &first_name,
-        )? {
-            return Ok(PartialMatch(membership));
+    if let (Some(last_name), Some(first_name)) = (&last_name, &first_name) {
+        if let Some(check_result) = match_namesakes(
+            index.by_last_name_first_name(last_name, first_name),
+            birthdate,
+        ) {
+            return (check_result, Names);
+        }
+    }
+
+    if let Some(identity) = &identity {
+        if let Some(check_result) = match_namesakes(index.by_identity(identity), birthdate) {
+            return (check_result, Identity);
         }
     }
 
-    if identity.is_some() {
-        let identity = identity
-            .clone()
-            .expect("There should be a value at this point");
-        if let Some(membership) = dao::membership::find::first::by_identity(connection, &identity)?
+    (NoMatch, NoMatchingMembership)
+}
+
+/// Among memberships sharing the member's names, the one with the same birthdate is a perfect match.
+/// Memberships with another birthdate belong to namesakes, and are discarded.
+/// Other memberships can't be told apart:
+/// if they all belong to the same person, the most recent one is a partial match,
+/// otherwise the most recent membership of each person is a candidate of an ambiguous result.
+fn match_namesakes(
+    namesakes: Vec<&Membership>,
+    birthdate: Option<NaiveDate>,
+) -> Option<CheckResult> {
+    if let Some(birthdate) = birthdate {
+        if let Some(membership) = namesakes
+            .iter()
+            .find(|membership| *membership.birthdate() == Some(birthdate))
         {
-            return Ok(PartialMatch(membership));
+            return Some(Match((*membership).clone()));
         }
     }
 
-    Ok(NoMatch)
+    // Namesakes are sorted from the most recent, so the first membership of each person is kept
+    let mut membership_numbers = HashSet::new();
+    let mut candidates = namesakes
+        .into_iter()
+        .filter(|membership| may_belong_to(membership, birthdate))
+        .filter(|membership| membership_numbers.insert(normalize(membership.membership_number())))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    match candidates.len() {
+        0 => None,
+        1 => candidates.pop().map(PartialMatch),
+        _ => Some(Ambiguous(candidates)),
+    }
+}
+
+/// A membership whose birthdate is known and differs from the member's one belongs to someone else.
+fn may_belong_to(membership: &Membership, birthdate: Option<NaiveDate>) -> bool {
+    match (birthdate, membership.birthdate()) {
+        (Some(birthdate), Some(membership_birthdate)) => birthdate == *membership_birthdate,
+        _ => true,
+    }
+}
+
+/// Look for a membership with the same email address as the member.
+/// As emails are encrypted in the database, they can only be compared once decrypted.
+/// Among the memberships sharing this address, the one whose names are the most similar is kept,
+/// then the most recent one.
+/// Memberships whose names aren't similar enough belong to someone else using the same address.
+fn find_email_match<T: MemberToCheck>(
+    index: &MembershipIndex,
+    member_to_check: &T,
+) -> Option<CheckResult> {
+    let email_address = member_to_check.email()?;
+    let birthdate = member_to_check.birthdate();
+    let searched_names = searched_names(member_to_check);
+    index
+        .with_email_address(&email_address)
+        .filter(|(membership, _)| may_belong_to(membership, birthdate))
+        .filter_map(|(membership, fields)| {
+            let confidence = searched_names.confidence(fields)?;
+            (confidence >= MIN_FUZZY_CONFIDENCE).then_some((membership, confidence))
+        })
+        .max_by(|(left_membership, left), (right_membership, right)| {
+            left.total_cmp(right)
+                .then_with(|| left_membership.end_date().cmp(right_membership.end_date()))
+        })
+        .map(|(membership, _)| PartialMatch(membership.clone()))
+}
+
+/// Look for the membership whose names are the most similar to the member's ones.
+/// Among equally similar memberships, the most recent one is kept.
+/// Namesakes, whose birthdate differs from the member's one, are left aside.
+/// Only memberships whose names have lengths that can reach the minimum confidence are compared.
+fn find_fuzzy_match<T: MemberToCheck>(index: &MembershipIndex, member_to_check: &T) -> CheckResult {
+    let birthdate = member_to_check.birthdate();
+    let searched_names = searched_names(member_to_check);
+    index
+        .with_name_lengths(|last_name_length, first_name_length| {
+            searched_names
+                .max_confidence(last_name_length, first_name_length)
+                .is_some_and(|max_confidence| max_confidence >= MIN_FUZZY_CONFIDENCE)
+        })
+        .filter(|(membership, _)| may_belong_to(membership, birthdate))
+        .filter_map(|(membership, fields)| {
+            let confidence = searched_names.confidence(fields)?;
+            (confidence >= MIN_FUZZY_CONFIDENCE).then_some((membership, confidence))
+        })
+        .max_by(|(left_membership, left), (right_membership, right)| {
+            left.total_cmp(right)
+                .then_with(|| left_membership.end_date().cmp(right_membership.end_date()))
+        })
+        .map(|(membership, confidence)| {
+            FuzzyMatch(membership.clone(), (confidence * 100.0).round() as u8)
+        })
+        .unwrap_or(NoMatch)
+}
+
+/// The names of a member to check are only compared if both are known,
+/// as a last name alone would be similar to too many people's one.
+fn searched_names<T: MemberToCheck>(member_to_check: &T) -> SearchedNames {
+    let (last_name, first_name) = match (member_to_check.last_name(), member_to_check.first_name())
+    {
+        (Some(last_name), Some(first_name)) => (Some(last_name), Some(first_name)),
+        _ => (None, None),
+    };
+    SearchedNames::new(
+        last_name.as_deref(),
+        first_name.as_deref(),
+        member_to_check.identity().as_deref(),
+    )
+}
+
+/// Look among all the memberships of the member for the ones covering the event.
+/// If they do, the last of them replaces the membership found, and they all come along with it.
+fn cover_event(
+    index: &MembershipIndex,
+    check_result: CheckResult,
+    event_period: &EventPeriod,
+) -> (CheckResult, Vec<Membership>) {
+    let Some(membership) = check_result.membership() else {
+        return (check_result, vec![]);
+    };
+    let memberships = index.all_by_num(membership.membership_number());
+    let event_memberships = event_period
+        .select_covering_memberships(&memberships)
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    match event_memberships.last() {
+        Some(last_membership) => {
+            let check_result = check_result.with_membership(last_membership.clone());
+            (check_result, event_memberships)
+        }
+        None => (check_result, vec![]),
+    }
+}
+
+/// Downgrade a perfect match to a partial match when the membership is not of the required type.
+/// Types are compared once normalized, so that case and accents don't matter.
+fn enforce_membership_type(
+    check_result: CheckResult,
+    required_membership_type: Option<&str>,
+) -> CheckResult {
+    match (check_result, required_membership_type) {
+        (Match(membership), Some(required_membership_type))
+            if !has_membership_type(&membership, required_membership_type) =>
+        {
+            PartialMatch(membership)
+        }
+        (check_result, _) => check_result,
+    }
+}
+
+fn has_membership_type(membership: &Membership, membership_type: &str) -> bool {
+    membership.membership_type().as_deref().map(normalize) == Some(normalize(membership_type))
+}
+
+/// Explain the result with the rule which produced it,
+/// and with the fields of the member that agree or disagree with the membership found, if any.
+fn explain<T: MemberToCheck>(
+    rule: MatchingRule,
+    member_to_check: &T,
+    check_result: &CheckResult,
+    required_membership_type: Option<&str>,
+) -> MatchExplanation {
+    let Some(membership) = check_result.membership() else {
+        return MatchExplanation::new(rule, vec![], vec![]);
+    };
+
+    let (agreeing_fields, disagreeing_fields): (Vec<_>, Vec<_>) =
+        compare_fields(member_to_check, membership, required_membership_type)
+            .into_iter()
+            .partition(|(_, agreeing)| *agreeing);
+    MatchExplanation::new(
+        rule,
+        agreeing_fields
+            .into_iter()
+            .map(|(field, _)| field)
+            .collect(),
+        disagreeing_fields
+            .into_iter()
+            .map(|(field, _)| field)
+            .collect(),
+    )
+}
+
+/// Compare the fields known on both sides, the same way the rules do.
+fn compare_fields<T: MemberToCheck>(
+    member_to_check: &T,
+    membership: &Membership,
+    required_membership_type: Option<&str>,
+) -> Vec<(ComparedField, bool)> {
+    let last_name = normalize(membership.name());
+    let first_name = normalize(membership.first_name());
+    let mut comparisons = vec![];
+
+    if let Some(membership_number) = member_to_check.membership_num() {
+        let agreeing = normalize(&membership_number) == normalize(membership.membership_number());
+        comparisons.push((ComparedField::MembershipNumber, agreeing));
+    }
+    if let Some(searched_last_name) = member_to_check.last_name() {
+        comparisons.push((
+            ComparedField::LastName,
+            normalize(&searched_last_name) == last_name,
+        ));
+    }
+    if let Some(searched_first_name) = member_to_check.first_name() {
+        comparisons.push((
+            ComparedField::FirstName,
+            normalize(&searched_first_name) == first_name,
+        ));
+    }
+    if let Some(identity) = member_to_check.identity() {
+        let identity = normalize(&identity);
+        let agreeing = identity == format!("{last_name}{first_name}")
+            || identity == format!("{first_name}{last_name}");
+        comparisons.push((ComparedField::Identity, agreeing));
+    }
+    if let (Some(birthdate), Some(membership_birthdate)) =
+        (member_to_check.birthdate(), membership.birthdate())
+    {
+        comparisons.push((ComparedField::Birthdate, birthdate == *membership_birthdate));
+    }
+    if let Some(email_address) = member_to_check
+        .email()
+        .filter(|email_address| !email_address.trim().is_empty())
+    {
+        comparisons.push((
+            ComparedField::EmailAddress,
+            membership.has_email_address(&email_address),
+        ));
+    }
+    if let Some(required_membership_type) = required_membership_type {
+        comparisons.push((
+            ComparedField::MembershipType,
+            has_membership_type(membership, required_membership_type),
+        ));
+    }
+
+    comparisons
 }
 
 #[cfg(test)]
@@ -141,13 +452,32 @@ mod tests {
     mod check_members {
         use crate::database::dao::membership::replace_memberships;
         use crate::database::with_temp_database;
-        use crate::membership::check::check_members;
-        use dto::checked_member::CheckResult::{Match, NoMatch};
+        use crate::membership::check::{MAX_MEMBERS_FOR_TARGETED_LOAD, check_members};
+        use crate::membership::tests::{jon_doe, jon_doe_previous_membership, other_jon_doe};
+        use chrono::NaiveDate;
+        use dto::checked_member::CheckResult::{FuzzyMatch, Match, NoMatch};
         use dto::checked_member::CheckedMember;
         use dto::csv_member::CsvMember;
+        use dto::match_explanation::ComparedField::{FirstName, LastName, MembershipNumber};
+        use dto::match_explanation::MatchExplanation;
+        use dto::match_explanation::MatchingRule::{
+            MembershipNumberAndNames, NoMatchingMembership, SimilarNames, UnknownMembershipNumber,
+        };
         use dto::membership::tests::{
             MEMBER_FIRST_NAME, MEMBER_NAME, MEMBERSHIP_NUMBER, get_expected_membership,
         };
+        use dto::membership_status::EventPeriod;
+        use dto::membership_status::MemberStatus::UpToDate;
+
+        fn jon_doe_to_check() -> CsvMember {
+            CsvMember::new(
+                Some(jon_doe().membership_number().to_owned()),
+                None,
+                Some(jon_doe().name().to_owned()),
+                Some(jon_doe().first_name().to_owned()),
+                None,
+            )
+        }
 
         #[test]
         fn success() {
@@ -160,15 +490,46 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
 
                 assert_eq!(
                     vec![CheckedMember::new(
                         member_to_check.clone(),
-                        Match(membership)
+                        Match(membership),
+                        None,
+                        vec![],
+                        Some(MatchExplanation::new(
+                            MembershipNumberAndNames,
+                            vec![MembershipNumber, LastName, FirstName],
+                            vec![]
+                        ))
                     )],
-                    check_members(&pool, vec![member_to_check]).unwrap()
+                    check_members(&pool, vec![member_to_check], None, None).unwrap()
+                );
+            });
+        }
+
+        #[test]
+        fn success_when_many_members() {
+            with_temp_database(|pool| {
+                let membership = get_expected_membership();
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[membership.clone()]).unwrap();
+                let member_to_check = CsvMember::new(
+                    Some(MEMBERSHIP_NUMBER.to_owned()),
+                    None,
+                    Some(MEMBER_NAME.to_owned()),
+                    Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
+                let members_to_check = vec![member_to_check; MAX_MEMBERS_FOR_TARGETED_LOAD + 1];
+
+                let checked_members = check_members(&pool, members_to_check, None, None).unwrap();
+                assert_eq!(MAX_MEMBERS_FOR_TARGETED_LOAD + 1, checked_members.len());
+                assert!(checked_members.iter().all(
+                    |checked_member| *checked_member.membership() == Match(membership.clone())
+                ));
             });
         }
 
@@ -184,28 +545,415 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
 
                 assert_eq!(
-                    vec![CheckedMember::new(member_to_check.clone(), NoMatch)],
-                    check_members(&pool, vec![member_to_check]).unwrap()
+                    vec![CheckedMember::new(
+                        member_to_check.clone(),
+                        NoMatch,
+                        None,
+                        vec![],
+                        Some(MatchExplanation::new(
+                            UnknownMembershipNumber,
+                            vec![],
+                            vec![]
+                        ))
+                    )],
+                    check_members(&pool, vec![member_to_check], None, None).unwrap()
+                );
+            });
+        }
+
+        #[test]
+        fn fuzzy_match_when_typo_in_names() {
+            with_temp_database(|pool| {
+                let membership = get_expected_membership();
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[membership.clone()]).unwrap();
+                let member_to_check = CsvMember::new(
+                    None,
+                    None,
+                    Some(MEMBER_NAME.to_owned()),
+                    Some(format!("{MEMBER_FIRST_NAME}n")),
+                    None,
+                );
+
+                assert_eq!(
+                    vec![CheckedMember::new(
+                        member_to_check.clone(),
+                        FuzzyMatch(membership, 88),
+                        None,
+                        vec![],
+                        Some(MatchExplanation::new(
+                            SimilarNames,
+                            vec![LastName],
+                            vec![FirstName]
+                        ))
+                    )],
+                    check_members(&pool, vec![member_to_check], None, None).unwrap()
+                );
+            });
+        }
+
+        #[test]
+        fn previous_membership_when_covering_event() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), jon_doe_previous_membership()])
+                    .unwrap();
+                let member_to_check = jon_doe_to_check();
+                let event_period = EventPeriod::new(
+                    NaiveDate::from_ymd_opt(2024, 9, 20).unwrap(),
+                    NaiveDate::from_ymd_opt(2024, 9, 25).unwrap(),
+                );
+
+                assert_eq!(
+                    vec![CheckedMember::new(
+                        member_to_check.clone(),
+                        Match(jon_doe_previous_membership()),
+                        Some(event_period),
+                        vec![jon_doe_previous_membership()],
+                        Some(MatchExplanation::new(
+                            MembershipNumberAndNames,
+                            vec![MembershipNumber, LastName, FirstName],
+                            vec![]
+                        ))
+                    )],
+                    check_members(&pool, vec![member_to_check], None, Some(event_period)).unwrap()
+                );
+            });
+        }
+
+        #[test]
+        fn successive_memberships_when_renewed_during_event() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), jon_doe_previous_membership()])
+                    .unwrap();
+                let member_to_check = jon_doe_to_check();
+                let event_period = EventPeriod::new(
+                    NaiveDate::from_ymd_opt(2024, 9, 29).unwrap(),
+                    NaiveDate::from_ymd_opt(2024, 10, 2).unwrap(),
+                );
+
+                let checked_members =
+                    check_members(&pool, vec![member_to_check], None, Some(event_period)).unwrap();
+                assert_eq!(&Match(jon_doe()), checked_members[0].membership());
+                assert_eq!(
+                    &vec![jon_doe_previous_membership(), jon_doe()],
+                    checked_members[0].event_memberships()
                 );
+                assert_eq!(UpToDate, checked_members[0].compute_member_status());
             });
         }
+
+        #[test]
+        fn no_match_when_only_namesakes() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), other_jon_doe()]).unwrap();
+                let member_to_check = CsvMember::new(
+                    None,
+                    None,
+                    Some("Doe".to_owned()),
+                    Some("Jon".to_owned()),
+                    NaiveDate::from_ymd_opt(2000, 1, 1),
+                );
+
+                assert_eq!(
+                    vec![CheckedMember::new(
+                        member_to_check.clone(),
+                        NoMatch,
+                        None,
+                        vec![],
+                        Some(MatchExplanation::new(NoMatchingMembership, vec![], vec![]))
+                    )],
+                    check_members(&pool, vec![member_to_check], None, None).unwrap()
+                );
+            });
+        }
+    }
+
+    mod find_fuzzy_match {
+        use crate::membership::check::find_fuzzy_match;
+        use crate::membership::index::MembershipIndex;
+        use crate::membership::tests::{jon_doe, jon_doe_previous_membership, jonette_snow};
+        use chrono::NaiveDate;
+        use dto::checked_member::CheckResult::{FuzzyMatch, NoMatch};
+        use dto::csv_member::CsvMember;
+
+        #[test]
+        fn success_when_typo_in_names() {
+            let index = MembershipIndex::new(vec![
+                jon_doe_previous_membership(),
+                jon_doe(),
+                jonette_snow(),
+            ]);
+            let member_to_check = CsvMember::new(
+                None,
+                None,
+                Some("Doe".to_owned()),
+                Some("Jonn".to_owned()),
+                None,
+            );
+
+            assert_eq!(
+                FuzzyMatch(jon_doe(), 88),
+                find_fuzzy_match(&index, &member_to_check)
+            );
+        }
+
+        #[test]
+        fn success_when_typo_in_identity() {
+            let index = MembershipIndex::new(vec![jon_doe(), jonette_snow()]);
+            let member_to_check =
+                CsvMember::new(None, Some("Jonn Doe".to_owned()), None, None, None);
+
+            assert_eq!(
+                FuzzyMatch(jon_doe(), 86),
+                find_fuzzy_match(&index, &member_to_check)
+            );
+        }
+
+        #[test]
+        fn fail_when_too_different() {
+            let index = MembershipIndex::new(vec![jon_doe(), jonette_snow()]);
+            let member_to_check = CsvMember::new(
+                None,
+                None,
+                Some("Smith".to_owned()),
+                Some("John".to_owned()),
+                None,
+            );
+
+            assert_eq!(NoMatch, find_fuzzy_match(&index, &member_to_check));
+        }
+
+        #[test]
+        fn fail_when_birthdate_differs() {
+            let index = MembershipIndex::new(vec![jon_doe(), jonette_snow()]);
+            let member_to_check = CsvMember::new(
+                None,
+                None,
+                Some("Doe".to_owned()),
+                Some("Jonn".to_owned()),
+                NaiveDate::from_ymd_opt(2000, 1, 1),
+            );
+
+            assert_eq!(NoMatch, find_fuzzy_match(&index, &member_to_check));
+        }
+    }
+
+    mod find_email_match {
+        use crate::membership::check::find_email_match;
+        use crate::membership::index::MembershipIndex;
+        use crate::membership::tests::{jon_doe, jonette_snow, other_jon_doe};
+        use dto::checked_member::CheckResult::PartialMatch;
+        use dto::membership::Membership;
+        use dto::uda_member::UdaMember;
+
+        fn uda_member(first_name: &str, last_name: &str, email: &str) -> UdaMember {
+            UdaMember::new(
+                1,
+                None,
+                first_name.to_owned(),
+                last_name.to_owned(),
+                email.to_owned(),
+                None,
+                true,
+            )
+        }
+
+        #[test]
+        fn success() {
+            let index = MembershipIndex::new(vec![jon_doe(), jonette_snow()]);
+            let member_to_check = uda_member("Jonete", "Snow", " Jonette.Snow@address.com");
+
+            assert_eq!(
+                Some(PartialMatch(jonette_snow())),
+                find_email_match(&index, &member_to_check)
+            );
+        }
+
+        /// Jon Doe's brother, who shares his email address.
+        fn jack_doe() -> Membership {
+            let other_jon_doe = other_jon_doe();
+            Membership::new(
+                "Doe".to_owned(),
+                "Jack".to_owned(),
+                *other_jon_doe.birthdate(),
+                other_jon_doe.membership_number().to_owned(),
+                None,
+                other_jon_doe.email_address().to_owned(),
+                *other_jon_doe.start_date(),
+                *other_jon_doe.end_date(),
+                other_jon_doe.club().to_owned(),
+                other_jon_doe.structure_code().to_owned(),
+                None,
+                None,
+            )
+        }
+
+        #[test]
+        fn success_when_shared_email_address() {
+            let index = MembershipIndex::new(vec![jon_doe(), jack_doe()]);
+            let member_to_check = uda_member("Jacky", "Doe", "jon.doe@address.com");
+
+            assert_eq!(
+                Some(PartialMatch(jack_doe())),
+                find_email_match(&index, &member_to_check)
+            );
+        }
+
+        #[test]
+        fn none_when_relative_sharing_email_address() {
+            // Their sister, who isn't licensed
+            let index = MembershipIndex::new(vec![jon_doe(), jack_doe()]);
+            let member_to_check = uda_member("Jane", "Doe", "jon.doe@address.com");
+
+            assert_eq!(None, find_email_match(&index, &member_to_check));
+        }
+
+        #[test]
+        fn none_when_unknown_email_address() {
+            let index = MembershipIndex::new(vec![jon_doe(), jonette_snow()]);
+            let member_to_check = uda_member("Jon", "Doe", "jon@doe.com");
+
+            assert_eq!(None, find_email_match(&index, &member_to_check));
+        }
+    }
+
+    mod explain {
+        use crate::membership::check::explain;
+        use crate::membership::tests::jon_doe;
+        use dto::checked_member::CheckResult::{NoMatch, PartialMatch};
+        use dto::csv_member::CsvMember;
+        use dto::match_explanation::ComparedField::{
+            Birthdate, FirstName, Identity, LastName, MembershipNumber, MembershipType,
+        };
+        use dto::match_explanation::MatchExplanation;
+        use dto::match_explanation::MatchingRule::{
+            MembershipNumber as MembershipNumberRule, Names,
+        };
+        use dto::uda_member::UdaMember;
+
+        #[test]
+        fn success() {
+            let member_to_check = CsvMember::new(
+                Some("0123456".to_owned()),
+                None,
+                Some("Doe".to_owned()),
+                Some("John".to_owned()),
+                jon_doe().birthdate().to_owned(),
+            );
+
+            assert_eq!(
+                MatchExplanation::new(
+                    MembershipNumberRule,
+                    vec![MembershipNumber, LastName, Birthdate],
+                    vec![FirstName, MembershipType]
+                ),
+                explain(
+                    MembershipNumberRule,
+                    &member_to_check,
+                    &PartialMatch(jon_doe()),
+                    Some("Compétition")
+                )
+            );
+        }
+
+        #[test]
+        fn ignore_empty_email_address() {
+            let member_to_check = UdaMember::new(
+                1,
+                None,
+                "Jon".to_owned(),
+                "Doe".to_owned(),
+                "".to_owned(),
+                None,
+                true,
+            );
+
+            assert_eq!(
+                MatchExplanation::new(Names, vec![LastName, FirstName, Identity], vec![]),
+                explain(Names, &member_to_check, &PartialMatch(jon_doe()), None)
+            );
+        }
+
+        #[test]
+        fn no_field_when_no_match() {
+            let member_to_check =
+                CsvMember::new(None, Some("Jon Doe".to_owned()), None, None, None);
+
+            assert_eq!(
+                MatchExplanation::new(Names, vec![], vec![]),
+                explain(Names, &member_to_check, &NoMatch, None)
+            );
+        }
+    }
+
+    mod enforce_membership_type {
+        use crate::membership::check::enforce_membership_type;
+        use dto::checked_member::CheckResult::{Match, NoMatch, PartialMatch};
+        use dto::membership::tests::get_expected_membership;
+
+        #[test]
+        fn keep_match_when_no_required_type() {
+            let membership = get_expected_membership();
+            assert_eq!(
+                Match(membership.clone()),
+                enforce_membership_type(Match(membership), None)
+            );
+        }
+
+        #[test]
+        fn keep_match_when_required_type() {
+            let membership = get_expected_membership();
+            assert_eq!(
+                Match(membership.clone()),
+                enforce_membership_type(Match(membership), Some(" competition "))
+            );
+        }
+
+        #[test]
+        fn downgrade_match_when_wrong_type() {
+            let membership = get_expected_membership();
+            assert_eq!(
+                PartialMatch(membership.clone()),
+                enforce_membership_type(Match(membership), Some("Loisir"))
+            );
+        }
+
+        #[test]
+        fn keep_no_match() {
+            assert_eq!(NoMatch, enforce_membership_type(NoMatch, Some("Loisir")));
+        }
     }
 
     mod check_member {
-        use crate::database::dao::membership::replace_memberships;
+        use crate::database::DatabaseConnection;
+        use crate::database::dao::membership::{replace_memberships, retrieve_memberships};
         use crate::database::with_temp_database;
         use crate::membership::check::check_member;
-        use chrono::Months;
-        use dto::checked_member::CheckResult::{Match, NoMatch, PartialMatch};
+        use crate::membership::index::MembershipIndex;
+        use crate::membership::tests::{jon_doe, jon_doe_previous_membership, other_jon_doe};
+        use chrono::{Months, NaiveDate};
+        use dto::checked_member::CheckResult::{Ambiguous, Match, NoMatch, PartialMatch};
         use dto::csv_member::CsvMember;
+        use dto::match_explanation::MatchingRule::{
+            Identity, MembershipNumberAndIdentity, MembershipNumberAndNames, Names,
+            NoMatchingMembership, UnknownMembershipNumber,
+        };
         use dto::membership::Membership;
         use dto::membership::tests::{
             MEMBER_FIRST_NAME, MEMBER_NAME, MEMBERSHIP_NUMBER, get_expected_membership,
         };
 
+        fn index(connection: &mut DatabaseConnection) -> MembershipIndex {
+            MembershipIndex::new(retrieve_memberships(connection).unwrap())
+        }
+
         #[test]
         fn success() {
             with_temp_database(|pool| {
@@ -217,11 +965,12 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
 
                 assert_eq!(
-                    Match(membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(membership), MembershipNumberAndNames),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -237,11 +986,12 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
 
                 assert_eq!(
-                    Match(membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(membership), MembershipNumberAndNames),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -257,11 +1007,12 @@ mod tests {
                     None,
                     Some(format!(" {MEMBER_NAME}  ")),
                     Some(format!("{MEMBER_FIRST_NAME}  ")),
+                    None,
                 );
 
                 assert_eq!(
-                    Match(membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(membership), MembershipNumberAndNames),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -277,11 +1028,12 @@ mod tests {
                     Some(format!("{} {}", MEMBER_NAME, MEMBER_FIRST_NAME)),
                     None,
                     None,
+                    None,
                 );
 
                 assert_eq!(
-                    Match(membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(membership), MembershipNumberAndIdentity),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -297,11 +1049,12 @@ mod tests {
                     Some(format!("{MEMBER_NAME} {MEMBER_FIRST_NAME}")),
                     None,
                     None,
+                    None,
                 );
 
                 assert_eq!(
-                    PartialMatch(membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (PartialMatch(membership), Identity),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -318,11 +1071,112 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
+                );
+
+                assert_eq!(
+                    (NoMatch, UnknownMembershipNumber),
+                    check_member(&index(&mut connection), &member_to_check)
+                );
+            });
+        }
+
+        #[test]
+        fn match_namesake_when_birthdate() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), other_jon_doe()]).unwrap();
+                let member_to_check = CsvMember::new(
+                    None,
+                    None,
+                    Some("Doe".to_owned()),
+                    Some("Jon".to_owned()),
+                    *other_jon_doe().birthdate(),
                 );
 
                 assert_eq!(
-                    NoMatch,
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(other_jon_doe()), Names),
+                    check_member(&index(&mut connection), &member_to_check)
+                );
+            });
+        }
+
+        #[test]
+        fn match_when_identity_and_birthdate() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), other_jon_doe()]).unwrap();
+                let member_to_check = CsvMember::new(
+                    None,
+                    Some("Jon Doe".to_owned()),
+                    None,
+                    None,
+                    *jon_doe().birthdate(),
+                );
+
+                assert_eq!(
+                    (Match(jon_doe()), Identity),
+                    check_member(&index(&mut connection), &member_to_check)
+                );
+            });
+        }
+
+        #[test]
+        fn no_match_when_only_namesakes() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), other_jon_doe()]).unwrap();
+                let member_to_check = CsvMember::new(
+                    None,
+                    None,
+                    Some("Doe".to_owned()),
+                    Some("Jon".to_owned()),
+                    NaiveDate::from_ymd_opt(2000, 1, 1),
+                );
+
+                assert_eq!(
+                    (NoMatch, NoMatchingMembership),
+                    check_member(&index(&mut connection), &member_to_check)
+                );
+            });
+        }
+
+        #[test]
+        fn ambiguous_when_namesakes_without_birthdate() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(
+                    &mut connection,
+                    &[jon_doe_previous_membership(), jon_doe(), other_jon_doe()],
+                )
+                .unwrap();
+                let member_to_check = CsvMember::new(
+                    None,
+                    None,
+                    Some("Doe".to_owned()),
+                    Some("Jon".to_owned()),
+                    None,
+                );
+
+                assert_eq!(
+                    (Ambiguous(vec![jon_doe(), other_jon_doe()]), Names),
+                    check_member(&index(&mut connection), &member_to_check)
+                );
+            });
+        }
+
+        #[test]
+        fn partial_match_when_several_memberships_of_same_member() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe_previous_membership(), jon_doe()])
+                    .unwrap();
+                let member_to_check =
+                    CsvMember::new(None, Some("Jon Doe".to_owned()), None, None, None);
+
+                assert_eq!(
+                    (PartialMatch(jon_doe()), Identity),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -343,6 +1197,8 @@ mod tests {
                     matching_membership.end_date().to_owned(),
                     matching_membership.club().to_owned(),
                     matching_membership.structure_code().to_owned(),
+                    None,
+                    None,
                 );
                 let not_matching_membership = Membership::new(
                     "Not the right name".to_owned(),
@@ -355,6 +1211,8 @@ mod tests {
                     matching_membership.end_date().to_owned(),
                     matching_membership.club().to_owned(),
                     matching_membership.structure_code().to_owned(),
+                    None,
+                    None,
                 );
                 replace_memberships(
                     &mut connection,
@@ -371,11 +1229,12 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
 
                 assert_eq!(
-                    Match(matching_membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(matching_membership), MembershipNumberAndNames),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
@@ -404,6 +1263,8 @@ mod tests {
                         .unwrap(),
                     newest_membership.club().to_owned(),
                     newest_membership.structure_code().to_owned(),
+                    None,
+                    None,
                 );
                 replace_memberships(
                     &mut connection,
@@ -415,13 +1276,100 @@ mod tests {
                     None,
                     Some(MEMBER_NAME.to_owned()),
                     Some(MEMBER_FIRST_NAME.to_owned()),
+                    None,
                 );
 
                 assert_eq!(
-                    Match(newest_membership),
-                    check_member(&mut connection, &member_to_check).unwrap()
+                    (Match(newest_membership), MembershipNumberAndNames),
+                    check_member(&index(&mut connection), &member_to_check)
                 );
             });
         }
     }
+
+    mod benchmark {
+        use crate::database::dao::membership::replace_memberships;
+        use crate::database::with_temp_database;
+        use crate::membership::check::check_members;
+        use chrono::NaiveDate;
+        use dto::checked_member::CheckResult::{Match, NoMatch, PartialMatch};
+        use dto::csv_member::CsvMember;
+        use dto::membership::Membership;
+        use std::time::Instant;
+
+        const MEMBERSHIPS_COUNT: usize = 30_000;
+        const MEMBERS_COUNT: usize = 2_000;
+
+        fn membership(index: usize) -> Membership {
+            Membership::new(
+                format!("Name{index}"),
+                format!("First name{index}"),
+                NaiveDate::from_ymd_opt(1980, 1, 1),
+                index.to_string(),
+                None,
+                format!("member{index}@address.com"),
+                NaiveDate::from_ymd_opt(2024, 9, 30).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+                "My club".to_owned(),
+                "Z01234".to_owned(),
+                None,
+                None,
+            )
+        }
+
+        /// A third of the members have a membership number, a third only have their names,
+        /// and the last third is unknown.
+        fn member_to_check(index: usize) -> CsvMember {
+            let number = index * (MEMBERSHIPS_COUNT / MEMBERS_COUNT);
+            match index % 3 {
+                0 => CsvMember::new(
+                    Some(number.to_string()),
+                    None,
+                    Some(format!("Name{number}")),
+                    Some(format!("First name{number}")),
+                    None,
+                ),
+                1 => CsvMember::new(
+                    None,
+                    None,
+                    Some(format!("Name{number}")),
+                    Some(format!("First name{number}")),
+                    None,
+                ),
+                _ => CsvMember::new(
+                    None,
+                    Some(format!("Unknown member {number}")),
+                    None,
+                    None,
+                    None,
+                ),
+            }
+        }
+
+        #[test]
+        #[ignore]
+        fn check_large_member_list() {
+            with_temp_database(|pool| {
+                let memberships = (0..MEMBERSHIPS_COUNT).map(membership).collect::<Vec<_>>();
+                replace_memberships(&mut pool.get().unwrap(), &memberships).unwrap();
+                let members_to_check = (0..MEMBERS_COUNT).map(member_to_check).collect();
+
+                let start = Instant::now();
+                let checked_members = check_members(&pool, members_to_check, None, None).unwrap();
+                println!(
+                    "Checked {MEMBERS_COUNT} members against {MEMBERSHIPS_COUNT} memberships in {:?}",
+                    start.elapsed()
+                );
+
+                assert_eq!(MEMBERS_COUNT, checked_members.len());
+                for (index, checked_member) in checked_members.iter().enumerate() {
+                    match index % 3 {
+                        0 => assert!(matches!(checked_member.membership(), Match(_))),
+                        1 => assert!(matches!(checked_member.membership(), PartialMatch(_))),
+                        _ => assert_eq!(&NoMatch, checked_member.membership()),
+                    }
+                }
+            });
+        }
+    }
 }
@@ -1,17 +1,24 @@
+use crate::fileo::export_filters::FileoExportFilters;
 use derive_getters::Getters;
 use regex::Regex;
 
-#[derive(Getters)]
+#[derive(Getters, Clone)]
 pub struct MembershipsProviderConfig {
     host: String,
     download_link_regex: Regex,
+    export_filters: FileoExportFilters,
 }
 
 impl MembershipsProviderConfig {
-    pub fn new(host: String, download_link_regex: Regex) -> Self {
+    pub fn new(
+        host: String,
+        download_link_regex: Regex,
+        export_filters: FileoExportFilters,
+    ) -> Self {
         Self {
             host,
             download_link_regex,
+            export_filters,
         }
     }
 }
@@ -0,0 +1,8 @@
+use dto::import_rejection::ImportRejection;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MembershipError {
+    #[error("The number of memberships would drop by more than the allowed percentage.")]
+    SuspiciousMembershipsDrop(ImportRejection),
+}
@@ -0,0 +1,318 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::database::error::DatabaseError;
+use crate::error::{ApplicationError, Result};
+use crate::membership::error::MembershipError;
+use crate::membership::error::MembershipError::SuspiciousMembershipsDrop;
+use crate::membership::retention::without_expired_personal_data;
+use crate::tools::env_args::retrieve_arg_value;
+use diesel::Connection;
+use dto::import_history::{ImportCounts, ImportOutcome};
+use dto::import_rejection::ImportRejection;
+use dto::import_report::ImportReport;
+use dto::membership::Membership;
+use dto::membership_diff::{MembershipChangeKind, MembershipsDiff};
+
+const MAX_DROP_PERCENTAGE_ARG: &str = "--max-memberships-drop-percentage";
+const DEFAULT_MAX_DROP_PERCENTAGE: u8 = 10;
+
+/// Replace known memberships with the imported ones.
+/// Unless forced, the import is refused if the number of memberships drops by more than
+/// the allowed percentage, as it most likely means the imported list is truncated.
+/// The rows that couldn't be read are recorded along with the import.
+/// Personal data of memberships that are already past the retention period isn't imported.
+pub fn import_memberships(
+    connection: &mut DatabaseConnection,
+    memberships: &[Membership],
+    report: &ImportReport,
+    force: bool,
+) -> Result<MembershipsDiff> {
+    let previous_count = dao::membership::count(connection)?;
+    if force {
+        warn!(
+            "Forcing memberships import [previous count: {previous_count}, new count: {}]",
+            memberships.len()
+        );
+    } else {
+        check_memberships_drop(
+            previous_count,
+            memberships.len(),
+            retrieve_max_drop_percentage(),
+        )?;
+    }
+
+    let memberships = without_expired_personal_data(memberships);
+    Ok(connection.transaction::<_, DatabaseError, _>(|connection| {
+        let diff = dao::membership::replace_memberships(connection, &memberships)?;
+        dao::membership_import::insert_rejected_rows(connection, report.rejected_rows())?;
+        Ok(diff)
+    })?)
+}
+
+/// Sum up what an import has changed, for the import history.
+pub fn import_counts(diff: &MembershipsDiff, report: &ImportReport) -> ImportCounts {
+    ImportCounts::new(
+        diff.count(MembershipChangeKind::Removed),
+        diff.count(MembershipChangeKind::Added),
+        diff.count(MembershipChangeKind::Changed),
+        report.rejected_rows().len(),
+    )
+}
+
+/// A suspicious import is refused, while other errors are failures.
+pub fn import_outcome_of(error: &ApplicationError) -> ImportOutcome {
+    match error {
+        ApplicationError::Membership(SuspiciousMembershipsDrop(_)) => ImportOutcome::Refused,
+        _ => ImportOutcome::Failure,
+    }
+}
+
+fn check_memberships_drop(
+    previous_count: usize,
+    new_count: usize,
+    max_drop_percentage: u8,
+) -> Result<(), MembershipError> {
+    if new_count >= previous_count {
+        return Ok(());
+    }
+
+    let drop_percentage = (previous_count - new_count) as f64 * 100.0 / previous_count as f64;
+    if drop_percentage > max_drop_percentage as f64 {
+        error!(
+            "Refusing memberships import [previous count: {previous_count}, new count: {new_count}, max drop: {max_drop_percentage}%]"
+        );
+        Err(SuspiciousMembershipsDrop(ImportRejection::new(
+            previous_count,
+            new_count,
+            max_drop_percentage,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn retrieve_max_drop_percentage() -> u8 {
+    retrieve_arg_value(MAX_DROP_PERCENTAGE_ARG)
+        .and_then(|percentage| percentage.parse::<u8>().ok())
+        .filter(|percentage| *percentage <= 100)
+        .unwrap_or(DEFAULT_MAX_DROP_PERCENTAGE)
+}
+
+#[cfg(test)]
+mod tests {
+    mod check_memberships_drop {
+        use crate::membership::error::MembershipError::SuspiciousMembershipsDrop;
+        use crate::membership::import::check_memberships_drop;
+        use dto::import_rejection::ImportRejection;
+        use parameterized::{ide, parameterized};
+
+        ide!();
+
+        #[parameterized(
+            counts = {(0, 0), (0, 100), (100, 150), (100, 90), (100, 100)},
+        )]
+        fn accept(counts: (usize, usize)) {
+            let (previous_count, new_count) = counts;
+            assert_eq!(
+                Ok(()),
+                check_memberships_drop(previous_count, new_count, 10)
+            );
+        }
+
+        #[parameterized(
+            counts = {(100, 89), (100, 0), (3, 2)},
+        )]
+        fn refuse(counts: (usize, usize)) {
+            let (previous_count, new_count) = counts;
+            assert_eq!(
+                Err(SuspiciousMembershipsDrop(ImportRejection::new(
+                    previous_count,
+                    new_count,
+                    10
+                ))),
+                check_memberships_drop(previous_count, new_count, 10)
+            );
+        }
+    }
+
+    mod retrieve_max_drop_percentage {
+        use crate::membership::import::{
+            DEFAULT_MAX_DROP_PERCENTAGE, MAX_DROP_PERCENTAGE_ARG, retrieve_max_drop_percentage,
+        };
+        use crate::tools::env_args::with_env_args;
+
+        #[test]
+        fn success() {
+            let percentage = with_env_args(
+                vec![format!("{MAX_DROP_PERCENTAGE_ARG}=25")],
+                retrieve_max_drop_percentage,
+            );
+            assert_eq!(25, percentage);
+        }
+
+        #[test]
+        fn default_when_invalid() {
+            let percentage = with_env_args(
+                vec![format!("{MAX_DROP_PERCENTAGE_ARG}=150")],
+                retrieve_max_drop_percentage,
+            );
+            assert_eq!(DEFAULT_MAX_DROP_PERCENTAGE, percentage);
+        }
+    }
+
+    mod import_memberships {
+        use crate::database::{dao, with_temp_database};
+        use crate::error::ApplicationError;
+        use crate::membership::error::MembershipError::SuspiciousMembershipsDrop;
+        use crate::membership::import::import_memberships;
+        use crate::membership::tests::{jon_doe, jonette_snow};
+        use dto::import_report::{ImportReport, RejectedRow};
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                import_memberships(
+                    &mut connection,
+                    &[jon_doe(), jonette_snow()],
+                    &ImportReport::default(),
+                    false,
+                )
+                .unwrap();
+                assert_eq!(
+                    vec![jon_doe(), jonette_snow()],
+                    dao::membership::retrieve_memberships(&mut connection).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn refuse_when_too_many_memberships_dropped() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                import_memberships(
+                    &mut connection,
+                    &[jon_doe(), jonette_snow()],
+                    &ImportReport::default(),
+                    false,
+                )
+                .unwrap();
+
+                let result = import_memberships(
+                    &mut connection,
+                    &[jon_doe()],
+                    &ImportReport::default(),
+                    false,
+                );
+                assert!(matches!(
+                    result,
+                    Err(ApplicationError::Membership(SuspiciousMembershipsDrop(_)))
+                ));
+                assert_eq!(
+                    vec![jon_doe(), jonette_snow()],
+                    dao::membership::retrieve_memberships(&mut connection).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn success_when_forced() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                import_memberships(
+                    &mut connection,
+                    &[jon_doe(), jonette_snow()],
+                    &ImportReport::default(),
+                    false,
+                )
+                .unwrap();
+
+                import_memberships(
+                    &mut connection,
+                    &[jon_doe()],
+                    &ImportReport::default(),
+                    true,
+                )
+                .unwrap();
+                assert_eq!(
+                    vec![jon_doe()],
+                    dao::membership::retrieve_memberships(&mut connection).unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn success_with_rejected_rows() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+
+                let report = ImportReport::new(vec![RejectedRow::new(
+                    3,
+                    "Doe;Jon".to_owned(),
+                    "expected 10 fields, found 2".to_owned(),
+                )]);
+                import_memberships(&mut connection, &[jon_doe()], &report, false).unwrap();
+                assert_eq!(
+                    Some(report),
+                    dao::membership_import::retrieve_last_report(&mut connection).unwrap()
+                );
+            })
+        }
+    }
+    mod import_counts {
+        use crate::membership::import::import_counts;
+        use crate::membership::tests::{jon_doe, jon_doe_previous_membership, jonette_snow};
+        use chrono::NaiveDate;
+        use dto::import_history::ImportCounts;
+        use dto::import_report::{ImportReport, RejectedRow};
+        use dto::membership_diff::{MembershipChange, MembershipsDiff};
+
+        #[test]
+        fn success() {
+            let date = NaiveDate::from_ymd_opt(2025, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap();
+            let diff = MembershipsDiff::new(
+                date,
+                vec![
+                    MembershipChange::added(&jon_doe()),
+                    MembershipChange::added(&jonette_snow()),
+                    MembershipChange::removed(&jon_doe()),
+                    MembershipChange::between(&jon_doe_previous_membership(), &jon_doe()).unwrap(),
+                ],
+            );
+            let report = ImportReport::new(vec![RejectedRow::new(
+                3,
+                "Doe;Jon".to_owned(),
+                "expected 10 fields, found 2".to_owned(),
+            )]);
+
+            assert_eq!(ImportCounts::new(1, 2, 1, 1), import_counts(&diff, &report));
+        }
+    }
+
+    mod import_outcome_of {
+        use crate::database::error::DatabaseError;
+        use crate::error::ApplicationError;
+        use crate::membership::error::MembershipError::SuspiciousMembershipsDrop;
+        use crate::membership::import::import_outcome_of;
+        use dto::import_history::ImportOutcome;
+        use dto::import_rejection::ImportRejection;
+
+        #[test]
+        fn refused_when_suspicious_drop() {
+            let error = ApplicationError::Membership(SuspiciousMembershipsDrop(
+                ImportRejection::new(100, 10, 10),
+            ));
+            assert_eq!(ImportOutcome::Refused, import_outcome_of(&error));
+        }
+
+        #[test]
+        fn failure_otherwise() {
+            let error = ApplicationError::Database(DatabaseError::ConnectionFailed);
+            assert_eq!(ImportOutcome::Failure, import_outcome_of(&error));
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use crate::tools::normalize;
+use dto::membership::Membership;
+use std::collections::{BTreeMap, HashMap};
+
+/// The normalized fields of a membership, as stored in the database.
+pub struct NormalizedFields {
+    membership_number: String,
+    last_name: String,
+    first_name: String,
+    last_name_first_name: String,
+    first_name_last_name: String,
+    email_address: String,
+}
+
+impl NormalizedFields {
+    fn new(membership: &Membership) -> Self {
+        let last_name = normalize(membership.name());
+        let first_name = normalize(membership.first_name());
+        Self {
+            membership_number: normalize(membership.membership_number()),
+            last_name_first_name: format!("{last_name}{first_name}"),
+            first_name_last_name: format!("{first_name}{last_name}"),
+            last_name,
+            first_name,
+            email_address: normalize_email_address(membership.email_address()),
+        }
+    }
+
+    pub fn membership_number(&self) -> &str {
+        &self.membership_number
+    }
+
+    pub fn last_name(&self) -> &str {
+        &self.last_name
+    }
+
+    pub fn first_name(&self) -> &str {
+        &self.first_name
+    }
+
+    pub fn last_name_first_name(&self) -> &str {
+        &self.last_name_first_name
+    }
+
+    pub fn first_name_last_name(&self) -> &str {
+        &self.first_name_last_name
+    }
+
+    fn name_lengths(&self) -> (usize, usize) {
+        (
+            self.last_name.chars().count(),
+            self.first_name.chars().count(),
+        )
+    }
+}
+
+/// Email addresses are compared regardless of case and surrounding spaces.
+fn normalize_email_address(email_address: &str) -> String {
+    email_address.trim().to_lowercase()
+}
+
+/// Known memberships, indexed by their normalized fields,
+/// so that a whole list of members can be checked without querying the database for each of them.
+/// Lookups return memberships in the same order as the database: most recent first.
+pub struct MembershipIndex {
+    memberships: Vec<Membership>,
+    fields: Vec<NormalizedFields>,
+    by_membership_number: HashMap<String, Vec<usize>>,
+    by_last_name_first_name: HashMap<(String, String), Vec<usize>>,
+    by_identity: HashMap<String, Vec<usize>>,
+    by_email_address: HashMap<String, Vec<usize>>,
+    by_name_lengths: BTreeMap<(usize, usize), Vec<usize>>,
+}
+
+impl MembershipIndex {
+    pub fn new(memberships: Vec<Membership>) -> Self {
+        let mut memberships = memberships
+            .into_iter()
+            .map(|membership| {
+                let fields = NormalizedFields::new(&membership);
+                (membership, fields)
+            })
+            .collect::<Vec<_>>();
+        memberships.sort_by(|(left, left_fields), (right, right_fields)| {
+            right
+                .end_date()
+                .cmp(left.end_date())
+                .then_with(|| {
+                    left_fields
+                        .membership_number
+                        .cmp(&right_fields.membership_number)
+                })
+                .then_with(|| {
+                    left_fields
+                        .last_name_first_name
+                        .cmp(&right_fields.last_name_first_name)
+                })
+        });
+        let (memberships, fields): (Vec<_>, Vec<_>) = memberships.into_iter().unzip();
+
+        let mut by_membership_number = HashMap::<_, Vec<_>>::new();
+        let mut by_last_name_first_name = HashMap::<_, Vec<_>>::new();
+        let mut by_identity = HashMap::<_, Vec<_>>::new();
+        let mut by_email_address = HashMap::<_, Vec<_>>::new();
+        let mut by_name_lengths = BTreeMap::<_, Vec<_>>::new();
+        for (position, fields) in fields.iter().enumerate() {
+            by_membership_number
+                .entry(fields.membership_number.clone())
+                .or_default()
+                .push(position);
+            by_last_name_first_name
+                .entry((fields.last_name.clone(), fields.first_name.clone()))
+                .or_default()
+                .push(position);
+            by_identity
+                .entry(fields.last_name_first_name.clone())
+                .or_default()
+                .push(position);
+            if fields.first_name_last_name != fields.last_name_first_name {
+                by_identity
+                    .entry(fields.first_name_last_name.clone())
+                    .or_default()
+                    .push(position);
+            }
+            if !fields.email_address.is_empty() {
+                by_email_address
+                    .entry(fields.email_address.clone())
+                    .or_default()
+                    .push(position);
+            }
+            by_name_lengths
+                .entry(fields.name_lengths())
+                .or_default()
+                .push(position);
+        }
+
+        Self {
+            memberships,
+            fields,
+            by_membership_number,
+            by_last_name_first_name,
+            by_identity,
+            by_email_address,
+            by_name_lengths,
+        }
+    }
+
+    /// Memberships with the given email address, most recent first, along with their normalized fields.
+    pub fn with_email_address(
+        &self,
+        email_address: &str,
+    ) -> impl Iterator<Item = (&Membership, &NormalizedFields)> {
+        self.resolve_with_fields(
+            self.by_email_address
+                .get(&normalize_email_address(email_address)),
+        )
+    }
+
+    /// Memberships whose normalized last name and first name lengths (in characters)
+    /// are accepted by the given filter, most recent first, along with their normalized fields.
+    /// Only the lengths are checked against the filter, not each membership.
+    pub fn with_name_lengths(
+        &self,
+        accept: impl Fn(usize, usize) -> bool,
+    ) -> impl Iterator<Item = (&Membership, &NormalizedFields)> {
+        let mut positions = self
+            .by_name_lengths
+            .iter()
+            .filter(|((last_name_length, first_name_length), _)| {
+                accept(*last_name_length, *first_name_length)
+            })
+            .flat_map(|(_, positions)| positions.iter().copied())
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions
+            .into_iter()
+            .map(|position| (&self.memberships[position], &self.fields[position]))
+    }
+
+    pub fn first_by_num(&self, membership_number: &str) -> Option<&Membership> {
+        self.by_num(membership_number).next()
+    }
+
+    /// All the memberships of a member, most recent first.
+    pub fn all_by_num(&self, membership_number: &str) -> Vec<&Membership> {
+        self.by_num(membership_number).collect()
+    }
+
+    pub fn first_by_num_last_name_first_name(
+        &self,
+        membership_number: &str,
+        last_name: &str,
+        first_name: &str,
+    ) -> Option<&Membership> {
+        let last_name = normalize(last_name);
+        let first_name = normalize(first_name);
+        self.by_num_with_fields(membership_number)
+            .find(|(_, fields)| fields.last_name == last_name && fields.first_name == first_name)
+            .map(|(membership, _)| membership)
+    }
+
+    pub fn first_by_num_identity(
+        &self,
+        membership_number: &str,
+        identity: &str,
+    ) -> Option<&Membership> {
+        let identity = normalize(identity);
+        self.by_num_with_fields(membership_number)
+            .find(|(_, fields)| {
+                fields.last_name_first_name == identity || fields.first_name_last_name == identity
+            })
+            .map(|(membership, _)| membership)
+    }
+
+    pub fn by_last_name_first_name(&self, last_name: &str, first_name: &str) -> Vec<&Membership> {
+        let key = (normalize(last_name), normalize(first_name));
+        self.resolve(self.by_last_name_first_name.get(&key))
+            .collect()
+    }
+
+    /// The identity may be either "last name first name" or "first name last name".
+    pub fn by_identity(&self, identity: &str) -> Vec<&Membership> {
+        self.resolve(self.by_identity.get(&normalize(identity)))
+            .collect()
+    }
+
+    fn by_num(&self, membership_number: &str) -> impl Iterator<Item = &Membership> {
+        self.resolve(self.by_membership_number.get(&normalize(membership_number)))
+    }
+
+    fn by_num_with_fields(
+        &self,
+        membership_number: &str,
+    ) -> impl Iterator<Item = (&Membership, &NormalizedFields)> {
+        self.resolve_with_fields(self.by_membership_number.get(&normalize(membership_number)))
+    }
+
+    fn resolve_with_fields<'a>(
+        &'a self,
+        positions: Option<&'a Vec<usize>>,
+    ) -> impl Iterator<Item = (&'a Membership, &'a NormalizedFields)> {
+        positions
+            .into_iter()
+            .flatten()
+            .map(|position| (&self.memberships[*position], &self.fields[*position]))
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        positions: Option<&'a Vec<usize>>,
+    ) -> impl Iterator<Item = &'a Membership> {
+        positions
+            .into_iter()
+            .flatten()
+            .map(|position| &self.memberships[*position])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod membership_index {
+        use crate::membership::index::MembershipIndex;
+        use crate::membership::tests::{
+            jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
+        };
+
+        fn index() -> MembershipIndex {
+            MembershipIndex::new(vec![
+                jon_doe_previous_membership(),
+                jonette_snow(),
+                other_jon_doe(),
+                jon_doe(),
+            ])
+        }
+
+        #[test]
+        fn sort_most_recent_first() {
+            let index = index();
+            let memberships = index
+                .with_name_lengths(|_, _| true)
+                .map(|(membership, _)| membership.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                vec![
+                    jonette_snow(),
+                    jon_doe(),
+                    other_jon_doe(),
+                    jon_doe_previous_membership()
+                ],
+                memberships
+            );
+        }
+
+        #[test]
+        fn find_by_num() {
+            let index = index();
+            assert_eq!(Some(&jon_doe()), index.first_by_num("0123456"));
+            assert_eq!(None, index.first_by_num("42"));
+        }
+
+        #[test]
+        fn find_all_by_num() {
+            let index = index();
+            let (jon_doe, previous_membership) = (jon_doe(), jon_doe_previous_membership());
+            assert_eq!(
+                vec![&jon_doe, &previous_membership],
+                index.all_by_num("123456")
+            );
+            assert!(index.all_by_num("42").is_empty());
+        }
+
+        #[test]
+        fn find_by_num_last_name_first_name() {
+            let index = index();
+            assert_eq!(
+                Some(&jon_doe()),
+                index.first_by_num_last_name_first_name(" 123456", "DOE", "jon ")
+            );
+            assert_eq!(
+                None,
+                index.first_by_num_last_name_first_name("123456", "Snow", "Jonette")
+            );
+        }
+
+        #[test]
+        fn find_by_num_identity() {
+            let index = index();
+            assert_eq!(
+                Some(&jon_doe()),
+                index.first_by_num_identity("123456", "Jon Doe")
+            );
+            assert_eq!(
+                Some(&jon_doe()),
+                index.first_by_num_identity("123456", "Doe Jon")
+            );
+            assert_eq!(None, index.first_by_num_identity("654321", "Doe Jon"));
+        }
+
+        #[test]
+        fn find_namesakes() {
+            let index = index();
+            let (jon_doe, other_jon_doe, previous_membership) =
+                (jon_doe(), other_jon_doe(), jon_doe_previous_membership());
+            let expected_memberships = vec![&jon_doe, &other_jon_doe, &previous_membership];
+            assert_eq!(
+                expected_memberships,
+                index.by_last_name_first_name("Doe", "Jon")
+            );
+            assert_eq!(expected_memberships, index.by_identity("jon-doe"));
+            assert!(index.by_identity("Jon Snow").is_empty());
+        }
+
+        #[test]
+        fn normalize_fields() {
+            let index = index();
+            let (membership, fields) = index.with_name_lengths(|_, _| true).nth(1).unwrap();
+            assert_eq!(&jon_doe(), membership);
+            assert_eq!("123456", fields.membership_number);
+            assert_eq!("doe", fields.last_name());
+            assert_eq!("jon", fields.first_name());
+            assert_eq!("doejon", fields.last_name_first_name());
+            assert_eq!("jondoe", fields.first_name_last_name());
+            assert_eq!("jon.doe@address.com", fields.email_address);
+        }
+
+        #[test]
+        fn find_by_email_address() {
+            let index = index();
+            let memberships = index
+                .with_email_address(" Jon.Doe@Address.com")
+                .map(|(membership, _)| membership.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                vec![jon_doe(), other_jon_doe(), jon_doe_previous_membership()],
+                memberships
+            );
+            assert_eq!(0, index.with_email_address("").count());
+        }
+
+        #[test]
+        fn find_by_name_lengths() {
+            let index = index();
+            let memberships = index
+                .with_name_lengths(|last_name_length, first_name_length| {
+                    last_name_length == 3 && first_name_length == 3
+                })
+                .map(|(membership, _)| membership.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                vec![jon_doe(), other_jon_doe(), jon_doe_previous_membership()],
+                memberships
+            );
+            assert_eq!(0, index.with_name_lengths(|_, _| false).count());
+        }
+    }
+}
@@ -1,22 +1,36 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao;
 use crate::error::Result;
-use diesel::SqliteConnection;
-use dto::member_to_look_up::MemberToLookUp;
+use crate::membership::index::MembershipIndex;
+use crate::membership::similar_names::{MIN_FUZZY_CONFIDENCE, SearchedNames};
+use crate::tools::normalize;
+use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
 use dto::membership::Membership;
+use dto::membership_candidate::MembershipCandidate;
+use std::cmp::Ordering;
 use std::collections::BTreeSet;
 
+const MAX_CANDIDATES: usize = 20;
+
 /// Load all memberships filtered by given information.
+/// If a date is given, then archived memberships that were valid on this date are retrieved instead.
 /// If no information is given, then nothing is retrieved.
 pub fn look_member_up(
-    connection: &mut SqliteConnection,
+    connection: &mut DatabaseConnection,
     member_to_look_up: &MemberToLookUp,
 ) -> Result<BTreeSet<Membership>> {
-    if member_to_look_up.membership_num().is_none()
-        && member_to_look_up.last_name().is_none()
-        && member_to_look_up.first_name().is_none()
-    {
+    if has_no_criteria(member_to_look_up) {
         return Ok(BTreeSet::new());
     }
 
+    if let Some(valid_on) = member_to_look_up.valid_on() {
+        return Ok(crate::database::dao::membership_history::find_valid_on(
+            connection,
+            member_to_look_up,
+            valid_on,
+        )?);
+    }
+
     Ok(
         crate::database::dao::membership::find::all::by_member_to_lookup(
             connection,
@@ -25,6 +39,134 @@ pub fn look_member_up(
     )
 }
 
+/// Load the memberships that look like the given information, the most relevant first.
+/// In prefix mode, each field should start with the given information.
+/// In fuzzy mode, the names should be similar enough to the given ones, as in the members check,
+/// so that typos are tolerated, while the membership number should match.
+/// In exact mode, all matching memberships are perfect candidates.
+/// Only exact mode takes the validity date into account: archived memberships aren't scored.
+/// If no information is given, then nothing is retrieved.
+pub fn look_candidates_up(
+    connection: &mut DatabaseConnection,
+    member_to_look_up: &MemberToLookUp,
+) -> Result<Vec<MembershipCandidate>> {
+    if has_no_criteria(member_to_look_up) {
+        return Ok(vec![]);
+    }
+
+    let mut candidates = match member_to_look_up.mode() {
+        LookUpMode::Exact => look_member_up(connection, member_to_look_up)?
+            .into_iter()
+            .map(|membership| MembershipCandidate::new(membership, 1.0))
+            .collect(),
+        LookUpMode::Prefix => {
+            dao::membership::find::all::by_member_to_lookup(connection, member_to_look_up)?
+                .into_iter()
+                .filter_map(|membership| {
+                    let score = compute_prefix_score(&membership, member_to_look_up)?;
+                    Some(MembershipCandidate::new(membership, score))
+                })
+                .collect()
+        }
+        LookUpMode::Fuzzy => look_similar_candidates_up(connection, member_to_look_up)?,
+    };
+
+    rank_candidates(&mut candidates);
+    candidates.truncate(MAX_CANDIDATES);
+    Ok(candidates)
+}
+
+fn has_no_criteria(member_to_look_up: &MemberToLookUp) -> bool {
+    member_to_look_up.membership_num().is_none()
+        && member_to_look_up.last_name().is_none()
+        && member_to_look_up.first_name().is_none()
+}
+
+/// Score the memberships whose names are similar enough to the given ones.
+/// Without names, the memberships with the given number are perfect candidates.
+fn look_similar_candidates_up(
+    connection: &mut DatabaseConnection,
+    member_to_look_up: &MemberToLookUp,
+) -> Result<Vec<MembershipCandidate>> {
+    let searched_names = SearchedNames::new(
+        member_to_look_up.last_name().as_deref(),
+        member_to_look_up.first_name().as_deref(),
+        None,
+    );
+    let membership_number = member_to_look_up.membership_num().as_deref().map(normalize);
+    let index = MembershipIndex::new(dao::membership::retrieve_memberships(connection)?);
+
+    let candidates = index
+        .with_name_lengths(|last_name_length, first_name_length| {
+            searched_names
+                .max_confidence(last_name_length, first_name_length)
+                .is_none_or(|max_confidence| max_confidence >= MIN_FUZZY_CONFIDENCE)
+        })
+        .filter(|(_, fields)| {
+            membership_number
+                .as_ref()
+                .is_none_or(|membership_number| membership_number == fields.membership_number())
+        })
+        .filter_map(|(membership, fields)| {
+            let score = searched_names.confidence(fields).unwrap_or(1.0);
+            (score >= MIN_FUZZY_CONFIDENCE)
+                .then(|| MembershipCandidate::new(membership.clone(), score))
+        })
+        .collect();
+
+    Ok(candidates)
+}
+
+/// The average prefix score of the given fields, once normalized.
+/// None if no information is given.
+fn compute_prefix_score(
+    membership: &Membership,
+    member_to_look_up: &MemberToLookUp,
+) -> Option<f64> {
+    let scores = [
+        (
+            member_to_look_up.membership_num(),
+            membership.membership_number(),
+        ),
+        (member_to_look_up.last_name(), membership.name()),
+        (member_to_look_up.first_name(), membership.first_name()),
+    ]
+    .into_iter()
+    .filter_map(|(searched, known)| {
+        searched
+            .as_ref()
+            .map(|searched| prefix_score(&normalize(searched), &normalize(known)))
+    })
+    .collect::<Vec<_>>();
+
+    if scores.is_empty() {
+        None
+    } else {
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+/// How much of the known field the searched prefix covers.
+fn prefix_score(searched: &str, known: &str) -> f64 {
+    let known_length = known.chars().count();
+    if known_length == 0 {
+        return 1.0;
+    }
+
+    (searched.chars().count() as f64 / known_length as f64).min(1.0)
+}
+
+/// Best scores first. Among equal scores, most recent memberships first.
+fn rank_candidates(candidates: &mut [MembershipCandidate]) {
+    candidates.sort_by(|left, right| {
+        right
+            .score()
+            .partial_cmp(left.score())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| right.membership().cmp(left.membership()))
+    });
+}
+
 #[cfg(test)]
 mod tests {
     mod look_member_up {
@@ -34,7 +176,8 @@ mod tests {
         use crate::membership::tests::{
             jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
         };
-        use dto::member_to_look_up::MemberToLookUp;
+        use chrono::NaiveDate;
+        use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
         use dto::membership::Membership;
         use std::collections::BTreeSet;
 
@@ -52,8 +195,13 @@ mod tests {
                     ],
                 )
                 .unwrap();
-                let member_to_look_up =
-                    MemberToLookUp::new(Some(jon_doe().membership_number().to_owned()), None, None);
+                let member_to_look_up = MemberToLookUp::new(
+                    Some(jon_doe().membership_number().to_owned()),
+                    None,
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
 
                 let result = look_member_up(&mut connection, &member_to_look_up).unwrap();
 
@@ -78,8 +226,13 @@ mod tests {
                     ],
                 )
                 .unwrap();
-                let member_to_look_up =
-                    MemberToLookUp::new(None, Some(jon_doe().name().to_owned()), None);
+                let member_to_look_up = MemberToLookUp::new(
+                    None,
+                    Some(jon_doe().name().to_owned()),
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
 
                 let result = look_member_up(&mut connection, &member_to_look_up).unwrap();
 
@@ -104,8 +257,13 @@ mod tests {
                     ],
                 )
                 .unwrap();
-                let member_to_look_up =
-                    MemberToLookUp::new(None, None, Some(jon_doe().first_name().to_owned()));
+                let member_to_look_up = MemberToLookUp::new(
+                    None,
+                    None,
+                    Some(jon_doe().first_name().to_owned()),
+                    None,
+                    LookUpMode::Exact,
+                );
 
                 let result = look_member_up(&mut connection, &member_to_look_up).unwrap();
 
@@ -130,12 +288,149 @@ mod tests {
                     ],
                 )
                 .unwrap();
-                let member_to_look_up = MemberToLookUp::new(None, None, None);
+                let member_to_look_up =
+                    MemberToLookUp::new(None, None, None, None, LookUpMode::Exact);
 
                 let result = look_member_up(&mut connection, &member_to_look_up).unwrap();
 
                 assert_eq!(BTreeSet::<Membership>::new(), result);
             });
         }
+
+        #[test]
+        fn valid_on_date() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe_previous_membership()]).unwrap();
+                replace_memberships(
+                    &mut connection,
+                    &[jonette_snow(), jon_doe(), other_jon_doe()],
+                )
+                .unwrap();
+                let member_to_look_up = MemberToLookUp::new(
+                    None,
+                    Some(jon_doe().name().to_owned()),
+                    None,
+                    NaiveDate::from_ymd_opt(2024, 3, 1),
+                    LookUpMode::Exact,
+                );
+
+                let result = look_member_up(&mut connection, &member_to_look_up).unwrap();
+
+                assert_eq!(BTreeSet::from([jon_doe_previous_membership()]), result);
+            });
+        }
+    }
+
+    mod look_candidates_up {
+        use crate::database::dao::membership::replace_memberships;
+        use crate::database::with_temp_database;
+        use crate::membership::look_up::look_candidates_up;
+        use crate::membership::tests::{
+            jon_doe, jon_doe_previous_membership, jonette_snow, other_jon_doe,
+        };
+        use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
+        use dto::membership::Membership;
+
+        fn look_up(member_to_look_up: MemberToLookUp) -> Vec<(Membership, f64)> {
+            let mut result = vec![];
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(
+                    &mut connection,
+                    &[
+                        jonette_snow(),
+                        jon_doe(),
+                        jon_doe_previous_membership(),
+                        other_jon_doe(),
+                    ],
+                )
+                .unwrap();
+
+                result = look_candidates_up(&mut connection, &member_to_look_up)
+                    .unwrap()
+                    .into_iter()
+                    .map(|candidate| (candidate.membership().clone(), *candidate.score()))
+                    .collect();
+            });
+            result
+        }
+
+        #[test]
+        fn fuzzy_with_typo() {
+            let member_to_look_up = MemberToLookUp::new(
+                None,
+                Some(jon_doe().name().to_owned()),
+                Some("Jno".to_owned()),
+                None,
+                LookUpMode::Fuzzy,
+            );
+
+            let result = look_up(member_to_look_up);
+
+            let candidates = result
+                .iter()
+                .map(|(membership, _)| membership.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                vec![other_jon_doe(), jon_doe(), jon_doe_previous_membership()],
+                candidates
+            );
+            assert!(result.iter().all(|(_, score)| *score < 1.0));
+        }
+
+        #[test]
+        fn fuzzy_with_membership_number() {
+            let member_to_look_up = MemberToLookUp::new(
+                Some(jon_doe().membership_number().to_owned()),
+                Some(jon_doe().name().to_owned()),
+                Some("Jno".to_owned()),
+                None,
+                LookUpMode::Fuzzy,
+            );
+
+            let candidates = look_up(member_to_look_up)
+                .into_iter()
+                .map(|(membership, _)| membership)
+                .collect::<Vec<_>>();
+            assert_eq!(vec![jon_doe(), jon_doe_previous_membership()], candidates);
+        }
+
+        #[test]
+        fn fuzzy_without_similar_membership() {
+            let member_to_look_up = MemberToLookUp::new(
+                None,
+                Some("Martin".to_owned()),
+                None,
+                None,
+                LookUpMode::Fuzzy,
+            );
+
+            assert!(look_up(member_to_look_up).is_empty());
+        }
+
+        #[test]
+        fn prefix() {
+            let member_to_look_up = MemberToLookUp::new(
+                None,
+                None,
+                Some("Jonet".to_owned()),
+                None,
+                LookUpMode::Prefix,
+            );
+
+            let result = look_up(member_to_look_up);
+
+            assert_eq!(1, result.len());
+            assert_eq!(jonette_snow(), result[0].0);
+            assert!(result[0].1 < 1.0);
+        }
+
+        #[test]
+        fn no_criteria() {
+            let member_to_look_up = MemberToLookUp::new(None, None, None, None, LookUpMode::Fuzzy);
+
+            assert!(look_up(member_to_look_up).is_empty());
+        }
     }
 }
@@ -1,6 +1,11 @@
 pub(crate) mod check;
 pub(crate) mod config;
+pub(crate) mod error;
+pub(crate) mod import;
+pub(crate) mod index;
 pub(crate) mod look_up;
+pub(crate) mod retention;
+pub(crate) mod similar_names;
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -19,6 +24,8 @@ pub(crate) mod tests {
             NaiveDate::from_ymd_opt(2026, 9, 30).unwrap(),
             "My club".to_string(),
             "Z01234".to_string(),
+            None,
+            None,
         )
     }
 
@@ -34,6 +41,8 @@ pub(crate) mod tests {
             NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
             "My club".to_string(),
             "Z01234".to_string(),
+            None,
+            None,
         )
     }
 
@@ -49,6 +58,8 @@ pub(crate) mod tests {
             NaiveDate::from_ymd_opt(2024, 9, 30).unwrap(),
             "My club".to_string(),
             "Z01234".to_string(),
+            None,
+            None,
         )
     }
 
@@ -64,6 +75,8 @@ pub(crate) mod tests {
             NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
             "My club".to_string(),
             "Z01234".to_string(),
+            None,
+            None,
         )
     }
 }
@@ -0,0 +1,344 @@
+use crate::audit::{SYSTEM_ACTOR, record_action};
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::database::error::DatabaseError;
+use crate::error::Result;
+use crate::tools::env_args::retrieve_arg_value;
+use chrono::{Months, NaiveDate, Utc};
+use derive_getters::Getters;
+use diesel::Connection;
+use diesel::r2d2::{ConnectionManager, Pool};
+use dto::audit_log::AuditAction;
+use dto::membership::Membership;
+use rocket::fairing::AdHoc;
+use std::time::Duration;
+
+const RETENTION_MONTHS_ARG: &str = "--personal-data-retention-months";
+const PURGE_INTERVAL_ARG: &str = "--personal-data-purge-interval-hours";
+const DEFAULT_PURGE_INTERVAL_IN_HOURS: u64 = 24;
+
+/// How long personal data of expired memberships is kept, and how often it is purged.
+#[derive(Getters, Clone, Debug, PartialEq)]
+pub struct RetentionPolicy {
+    retention_months: u32,
+    interval: Duration,
+}
+
+/// Build the retention policy from the args.
+/// Return None if no retention period has been provided, in which case personal data is never purged.
+pub fn retrieve_retention_policy() -> Option<RetentionPolicy> {
+    let retention_months = retrieve_arg_value(RETENTION_MONTHS_ARG)?
+        .parse::<u32>()
+        .inspect_err(|error| warn!("Ignoring invalid retention period: {error}"))
+        .ok()?;
+    let interval_in_hours = retrieve_arg_value(PURGE_INTERVAL_ARG)
+        .and_then(|interval| interval.parse::<u64>().ok())
+        .filter(|interval| *interval > 0)
+        .unwrap_or(DEFAULT_PURGE_INTERVAL_IN_HOURS);
+
+    Some(RetentionPolicy {
+        retention_months,
+        interval: Duration::from_secs(interval_in_hours * 3600),
+    })
+}
+
+/// The date before which memberships must have ended for their personal data to be purged.
+fn purge_cutoff(policy: &RetentionPolicy, today: NaiveDate) -> Option<NaiveDate> {
+    today.checked_sub_months(Months::new(*policy.retention_months()))
+}
+
+/// Blank the personal data of the memberships that ended before the current purge cutoff, if any,
+/// so that importing them again doesn't restore what has already been purged.
+pub(crate) fn without_expired_personal_data(memberships: &[Membership]) -> Vec<Membership> {
+    let cutoff = retrieve_retention_policy()
+        .and_then(|policy| purge_cutoff(&policy, Utc::now().date_naive()));
+    memberships
+        .iter()
+        .map(|membership| match cutoff {
+            Some(cutoff) if membership.end_date() < &cutoff => membership.without_personal_data(),
+            _ => membership.clone(),
+        })
+        .collect()
+}
+
+/// Once the server is launched, periodically purge the personal data of memberships
+/// that have expired for longer than the retention period.
+/// The first purge occurs right after the launch.
+pub fn personal_data_purge_fairing(policy: Option<RetentionPolicy>) -> AdHoc {
+    AdHoc::on_liftoff("Personal data purge", move |rocket| {
+        let policy = policy.clone();
+        Box::pin(async move {
+            let Some(policy) = policy else {
+                debug!("No retention period provided, personal data won't be purged.");
+                return;
+            };
+            let Some(pool) = rocket.state::<Pool<ConnectionManager<DatabaseConnection>>>() else {
+                error!("Can't schedule personal data purge: missing state.");
+                return;
+            };
+            let pool = pool.clone();
+
+            rocket::tokio::spawn(async move {
+                let mut interval = rocket::tokio::time::interval(*policy.interval());
+                loop {
+                    interval.tick().await;
+                    let today = Utc::now().date_naive();
+                    if let Err(error) = purge_personal_data(&pool, &policy, today) {
+                        error!("Personal data purge failed\n{error:#?}");
+                    }
+                }
+            });
+        })
+    })
+}
+
+/// Erase the email address, the cell number and the birthdate of the memberships
+/// (current and archived) that ended more than the retention period before today,
+/// as well as the email addresses and rejected rows recorded along with older imports.
+/// Other fields are kept, so that statistics can still be computed.
+/// Return how many memberships have been pseudonymized.
+pub(crate) fn purge_personal_data(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    policy: &RetentionPolicy,
+    today: NaiveDate,
+) -> Result<usize> {
+    let Some(cutoff) = purge_cutoff(policy, today) else {
+        return Ok(0);
+    };
+
+    let mut connection = pool.get().map_err(DatabaseError::from)?;
+    let (mut membership_numbers, import_rows) =
+        connection.transaction::<_, DatabaseError, _>(|connection| {
+            let mut membership_numbers =
+                dao::membership::pseudonymize_ended_before(connection, &cutoff)?;
+            membership_numbers.extend(dao::membership_history::pseudonymize_ended_before(
+                connection, &cutoff,
+            )?);
+            let import_rows =
+                dao::membership_import::pseudonymize_imported_before(connection, &cutoff)?;
+            Ok((membership_numbers, import_rows))
+        })?;
+    membership_numbers.sort();
+    membership_numbers.dedup();
+
+    let count = membership_numbers.len();
+    info!(
+        "Personal data purged [cutoff: {cutoff}, memberships: {count}, import rows: {import_rows}]"
+    );
+    if count > 0 {
+        record_action(
+            pool,
+            SYSTEM_ACTOR,
+            AuditAction::PersonalDataPurge,
+            count,
+            membership_numbers,
+        );
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_retention_policy {
+        use crate::membership::retention::{
+            DEFAULT_PURGE_INTERVAL_IN_HOURS, PURGE_INTERVAL_ARG, RETENTION_MONTHS_ARG,
+            RetentionPolicy, retrieve_retention_policy,
+        };
+        use crate::tools::env_args::with_env_args;
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            let policy = with_env_args(
+                vec![
+                    format!("{RETENTION_MONTHS_ARG}=36"),
+                    format!("{PURGE_INTERVAL_ARG}=12"),
+                ],
+                retrieve_retention_policy,
+            );
+
+            assert_eq!(
+                Some(RetentionPolicy {
+                    retention_months: 36,
+                    interval: Duration::from_secs(12 * 3600),
+                }),
+                policy
+            );
+        }
+
+        #[test]
+        fn success_with_default_interval() {
+            let policy = with_env_args(
+                vec![format!("{RETENTION_MONTHS_ARG}=36")],
+                retrieve_retention_policy,
+            )
+            .unwrap();
+
+            assert_eq!(
+                &Duration::from_secs(DEFAULT_PURGE_INTERVAL_IN_HOURS * 3600),
+                policy.interval()
+            );
+        }
+
+        #[test]
+        fn none_when_missing_or_invalid() {
+            assert_eq!(None, with_env_args(vec![], retrieve_retention_policy));
+            assert_eq!(
+                None,
+                with_env_args(
+                    vec![format!("{RETENTION_MONTHS_ARG}=forever")],
+                    retrieve_retention_policy
+                )
+            );
+        }
+    }
+
+    mod purge_personal_data {
+        use crate::database::dao;
+        use crate::database::dao::membership::replace_memberships;
+        use crate::database::dao::membership_import::{
+            insert_rejected_rows, retrieve_last_diff, retrieve_last_report,
+        };
+        use crate::database::with_temp_database;
+        use crate::fileo::download::read_memberships_export;
+        use crate::membership::import::import_memberships;
+        use crate::membership::retention::{
+            RETENTION_MONTHS_ARG, RetentionPolicy, purge_personal_data,
+        };
+        use crate::membership::tests::{jon_doe, jonette_snow};
+        use crate::tools::env_args::with_env_args;
+        use chrono::{NaiveDate, Utc};
+        use dto::audit_log::{AuditAction, AuditLogFilter};
+        use dto::import_report::RejectedRow;
+        use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
+        use dto::membership::tests::{get_expected_membership, get_membership_as_csv};
+        use encoding::all::ISO_8859_1;
+        use encoding::{EncoderTrap, Encoding};
+        use std::collections::BTreeSet;
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
+                let policy = RetentionPolicy {
+                    retention_months: 12,
+                    interval: Duration::from_secs(3600),
+                };
+
+                let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+                assert_eq!(1, purge_personal_data(&pool, &policy, today).unwrap());
+
+                let memberships = dao::membership::retrieve_memberships(&mut connection).unwrap();
+                assert!(memberships[0].email_address().is_empty());
+                assert_eq!(&None, memberships[0].birthdate());
+                assert_eq!(jonette_snow(), memberships[1]);
+
+                let filter =
+                    AuditLogFilter::new(None, Some(AuditAction::PersonalDataPurge), None, None);
+                let entries = dao::audit_log::retrieve(&mut connection, &filter).unwrap();
+                assert_eq!(1, entries.len());
+                assert_eq!(&vec!["123456".to_owned()], entries[0].targets());
+
+                // Already purged memberships are not purged again
+                assert_eq!(0, purge_personal_data(&pool, &policy, today).unwrap());
+            })
+        }
+
+        #[test]
+        fn success_with_old_imports() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                replace_memberships(&mut connection, &[jon_doe(), jonette_snow()]).unwrap();
+                let rejected_rows = vec![RejectedRow::new(
+                    2,
+                    "123456;Doe;Jon;jon.doe@address.com".to_owned(),
+                    "Invalid end date".to_owned(),
+                )];
+                insert_rejected_rows(&mut connection, &rejected_rows).unwrap();
+                let policy = RetentionPolicy {
+                    retention_months: 12,
+                    interval: Duration::from_secs(3600),
+                };
+
+                let today = NaiveDate::from_ymd_opt(2100, 1, 1).unwrap();
+                purge_personal_data(&pool, &policy, today).unwrap();
+
+                let diff = retrieve_last_diff(&mut connection).unwrap().unwrap();
+                assert_eq!(2, diff.changes().len());
+                assert!(diff.changes().iter().all(|change| {
+                    change.email_address().is_none() && change.previous_email_address().is_none()
+                }));
+                let report = retrieve_last_report(&mut connection).unwrap().unwrap();
+                assert!(report.rejected_rows()[0].record().is_empty());
+                assert_eq!("Invalid end date", report.rejected_rows()[0].reason());
+            })
+        }
+
+        #[test]
+        fn keep_purged_when_imported_again() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let file_content = ISO_8859_1
+                    .encode(&get_membership_as_csv(), EncoderTrap::Strict)
+                    .unwrap();
+                let (memberships, report) = read_memberships_export(&file_content).unwrap();
+                import_memberships(&mut connection, &memberships, &report, false).unwrap();
+                let policy = RetentionPolicy {
+                    retention_months: 1,
+                    interval: Duration::from_secs(3600),
+                };
+                let today = Utc::now().date_naive();
+                assert_eq!(1, purge_personal_data(&pool, &policy, today).unwrap());
+
+                let diff = with_env_args(vec![format!("{RETENTION_MONTHS_ARG}=1")], || {
+                    import_memberships(&mut connection, &memberships, &report, false)
+                })
+                .unwrap();
+
+                assert!(diff.changes().is_empty());
+                let expected_membership = get_expected_membership().without_personal_data();
+                assert_eq!(
+                    vec![expected_membership.clone()],
+                    dao::membership::retrieve_memberships(&mut connection).unwrap()
+                );
+                let member_to_look_up = MemberToLookUp::new(
+                    Some(expected_membership.membership_number().clone()),
+                    None,
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
+                let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+                assert_eq!(
+                    BTreeSet::from([expected_membership]),
+                    dao::membership_history::find_valid_on(
+                        &mut connection,
+                        &member_to_look_up,
+                        &date
+                    )
+                    .unwrap()
+                );
+            })
+        }
+
+        #[test]
+        fn keep_recent_imports() {
+            with_temp_database(|pool| {
+                let mut connection = pool.get().unwrap();
+                let diff = replace_memberships(&mut connection, &[jon_doe()]).unwrap();
+                let policy = RetentionPolicy {
+                    retention_months: 12,
+                    interval: Duration::from_secs(3600),
+                };
+
+                let today = Utc::now().date_naive();
+                purge_personal_data(&pool, &policy, today).unwrap();
+
+                assert_eq!(Some(diff), retrieve_last_diff(&mut connection).unwrap());
+            })
+        }
+    }
+}
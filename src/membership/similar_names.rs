@@ -0,0 +1,157 @@
+use crate::membership::index::NormalizedFields;
+use crate::tools::normalize;
+use crate::tools::similarity::{max_similarity, similarity};
+
+/// Below this confidence, a membership is too different from the searched names to be proposed.
+pub(crate) const MIN_FUZZY_CONFIDENCE: f64 = 0.8;
+
+/// The names and identity someone is searched by, normalized once
+/// so that they can be compared to many memberships.
+pub(crate) struct SearchedNames {
+    last_name: Option<String>,
+    first_name: Option<String>,
+    identity: Option<String>,
+}
+
+impl SearchedNames {
+    pub(crate) fn new(
+        last_name: Option<&str>,
+        first_name: Option<&str>,
+        identity: Option<&str>,
+    ) -> Self {
+        Self {
+            last_name: last_name.map(normalize),
+            first_name: first_name.map(normalize),
+            identity: identity.map(normalize),
+        }
+    }
+
+    /// How similar the names of the membership are to the searched ones, from 0 to 1.
+    /// The given names are compared one by one and averaged, the identity is compared as a whole,
+    /// and the best confidence is kept.
+    /// None if neither names nor identity are searched.
+    pub(crate) fn confidence(&self, fields: &NormalizedFields) -> Option<f64> {
+        let names_confidence = average([
+            self.last_name
+                .as_ref()
+                .map(|last_name| similarity(last_name, fields.last_name())),
+            self.first_name
+                .as_ref()
+                .map(|first_name| similarity(first_name, fields.first_name())),
+        ]);
+        let identity_confidence = self.identity.as_ref().map(|identity| {
+            similarity(identity, fields.last_name_first_name())
+                .max(similarity(identity, fields.first_name_last_name()))
+        });
+
+        names_confidence
+            .into_iter()
+            .chain(identity_confidence)
+            .max_by(f64::total_cmp)
+    }
+
+    /// The highest confidence names of the given lengths (in characters) can reach,
+    /// computed the same way as the confidence itself.
+    pub(crate) fn max_confidence(
+        &self,
+        last_name_length: usize,
+        first_name_length: usize,
+    ) -> Option<f64> {
+        let names_max_confidence = average([
+            self.last_name
+                .as_ref()
+                .map(|last_name| max_similarity(last_name.chars().count(), last_name_length)),
+            self.first_name
+                .as_ref()
+                .map(|first_name| max_similarity(first_name.chars().count(), first_name_length)),
+        ]);
+        let identity_max_confidence = self.identity.as_ref().map(|identity| {
+            max_similarity(
+                identity.chars().count(),
+                last_name_length + first_name_length,
+            )
+        });
+
+        names_max_confidence
+            .into_iter()
+            .chain(identity_max_confidence)
+            .max_by(f64::total_cmp)
+    }
+}
+
+/// None if no value is given.
+fn average<const N: usize>(values: [Option<f64>; N]) -> Option<f64> {
+    let values = values.into_iter().flatten().collect::<Vec<_>>();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod confidence {
+        use crate::membership::index::MembershipIndex;
+        use crate::membership::similar_names::SearchedNames;
+        use crate::membership::tests::jon_doe;
+
+        fn confidence(searched_names: SearchedNames) -> Option<f64> {
+            let index = MembershipIndex::new(vec![jon_doe()]);
+            let (_, fields) = index.with_name_lengths(|_, _| true).next().unwrap();
+            searched_names.confidence(fields)
+        }
+
+        #[test]
+        fn success_with_names() {
+            assert_eq!(
+                Some((1.0 + (1.0 - 1.0 / 3.0)) / 2.0),
+                confidence(SearchedNames::new(Some("Doe"), Some("Jno"), None))
+            );
+        }
+
+        #[test]
+        fn success_with_single_name() {
+            assert_eq!(
+                Some(1.0),
+                confidence(SearchedNames::new(Some("DOE"), None, None))
+            );
+        }
+
+        #[test]
+        fn success_with_best_of_names_and_identity() {
+            assert_eq!(
+                Some(1.0),
+                confidence(SearchedNames::new(
+                    Some("Snow"),
+                    Some("Jonette"),
+                    Some("Jon Doe")
+                ))
+            );
+        }
+
+        #[test]
+        fn none_when_nothing_searched() {
+            assert_eq!(None, confidence(SearchedNames::new(None, None, None)));
+        }
+    }
+
+    mod max_confidence {
+        use crate::membership::similar_names::SearchedNames;
+
+        #[test]
+        fn success() {
+            let searched_names = SearchedNames::new(Some("Doe"), Some("Jon"), None);
+
+            assert_eq!(Some(1.0), searched_names.max_confidence(3, 3));
+            assert_eq!(Some((0.5 + 1.0) / 2.0), searched_names.max_confidence(6, 3));
+        }
+
+        #[test]
+        fn success_with_identity() {
+            let searched_names = SearchedNames::new(None, None, Some("Jon Doe"));
+
+            assert_eq!(Some(1.0), searched_names.max_confidence(3, 3));
+        }
+    }
+}
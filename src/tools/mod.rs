@@ -1,5 +1,6 @@
 pub mod email;
 pub mod env_args;
+pub mod similarity;
 pub mod test;
 pub mod web;
 
@@ -0,0 +1,89 @@
+/// How similar two strings are, from 0 (nothing in common) to 1 (equal).
+/// It is based on the number of edits (insertion, deletion, substitution or transposition of two
+/// adjacent characters) required to go from one to the other.
+/// Strings should be normalized beforehand.
+pub fn similarity(left: &str, right: &str) -> f64 {
+    let left = left.chars().collect::<Vec<_>>();
+    let right = right.chars().collect::<Vec<_>>();
+    let max_length = left.len().max(right.len());
+    if max_length == 0 {
+        return 1.0;
+    }
+
+    1.0 - edit_distance(&left, &right) as f64 / max_length as f64
+}
+
+/// The highest similarity two strings of the given lengths (in characters) can have,
+/// as at least their difference in length has to be inserted.
+/// It's much cheaper than computing the similarity itself, so it can be used to discard strings.
+pub fn max_similarity(left_length: usize, right_length: usize) -> f64 {
+    let max_length = left_length.max(right_length);
+    if max_length == 0 {
+        return 1.0;
+    }
+
+    1.0 - left_length.abs_diff(right_length) as f64 / max_length as f64
+}
+
+/// Optimal string alignment distance, i.e. Levenshtein distance with transpositions.
+fn edit_distance(left: &[char], right: &[char]) -> usize {
+    let mut distances = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let substitution_cost = if left[i - 1] == right[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution_cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[left.len()][right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    mod similarity {
+        use crate::tools::similarity::similarity;
+        use parameterized::{ide, parameterized};
+
+        ide!();
+
+        #[parameterized(
+            strings = {("doe", "doe"), ("", ""), ("jonh", "john"), ("jon", "john"), ("doe", "snow"), ("abc", "")},
+            expected_similarity = {1.0, 1.0, 0.75, 0.75, 0.25, 0.0},
+        )]
+        fn success(strings: (&str, &str), expected_similarity: f64) {
+            let (left, right) = strings;
+            assert_eq!(expected_similarity, similarity(left, right));
+            assert_eq!(expected_similarity, similarity(right, left));
+        }
+    }
+    mod max_similarity {
+        use crate::tools::similarity::{max_similarity, similarity};
+        use parameterized::{ide, parameterized};
+
+        ide!();
+
+        #[parameterized(
+            strings = {("doe", "doe"), ("", ""), ("jonh", "john"), ("jon", "john"), ("doe", "snow"), ("abc", "")},
+            expected_max_similarity = {1.0, 1.0, 1.0, 0.75, 0.75, 0.0},
+        )]
+        fn success(strings: (&str, &str), expected_max_similarity: f64) {
+            let (left, right) = strings;
+            let max = max_similarity(left.chars().count(), right.chars().count());
+            assert_eq!(expected_max_similarity, max);
+            assert!(similarity(left, right) <= max);
+        }
+    }
+}
@@ -1,14 +1,275 @@
+use crate::tools::env_args::retrieve_arg_value;
 use crate::tools::log_message_and_return;
 use crate::web::error::WebError;
 use crate::web::error::WebError::CantCreateClient;
-use reqwest::Client;
+use derive_getters::Getters;
+use reqwest::{Client, RequestBuilder, Response};
+use std::time::Duration;
 
+const TIMEOUT_ARG: &str = "--outbound-timeout-seconds";
+const MAX_RETRIES_ARG: &str = "--outbound-max-retries";
+const INITIAL_BACKOFF_ARG: &str = "--outbound-initial-backoff-milliseconds";
+
+const DEFAULT_TIMEOUT_IN_SECONDS: u64 = 30;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+#[cfg(not(test))]
+const DEFAULT_INITIAL_BACKOFF_IN_MILLISECONDS: u64 = 500;
+#[cfg(test)]
+const DEFAULT_INITIAL_BACKOFF_IN_MILLISECONDS: u64 = 1; // Keep failing tests fast
+
+/// How requests to remote services (Fileo, UDA...) should behave.
+#[derive(Getters, Clone, Debug, PartialEq)]
+pub struct OutboundRequestsConfig {
+    timeout: Duration,
+    max_retries: u32,
+    initial_backoff: Duration,
+}
+
+impl OutboundRequestsConfig {
+    pub fn new(timeout: Duration, max_retries: u32, initial_backoff: Duration) -> Self {
+        Self {
+            timeout,
+            max_retries,
+            initial_backoff,
+        }
+    }
+
+    /// How long to wait before the given retry (starting at 1).
+    /// The delay doubles after each attempt.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+/// Build the config from the args passed to the app.
+/// Missing or invalid args fall back to their default value.
+pub fn retrieve_outbound_requests_config() -> OutboundRequestsConfig {
+    let timeout = retrieve_arg_value(TIMEOUT_ARG)
+        .and_then(|timeout| timeout.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_TIMEOUT_IN_SECONDS);
+    let max_retries = retrieve_arg_value(MAX_RETRIES_ARG)
+        .and_then(|max_retries| max_retries.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_MAX_RETRIES);
+    let initial_backoff = retrieve_arg_value(INITIAL_BACKOFF_ARG)
+        .and_then(|initial_backoff| initial_backoff.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_INITIAL_BACKOFF_IN_MILLISECONDS);
+
+    OutboundRequestsConfig::new(
+        Duration::from_secs(timeout),
+        max_retries,
+        Duration::from_millis(initial_backoff),
+    )
+}
+
+/// Build a client whose requests time out as configured.
 pub fn build_client() -> Result<Client, WebError> {
     reqwest::ClientBuilder::new()
         .cookie_store(true)
+        .timeout(*retrieve_outbound_requests_config().timeout())
         .build()
         .map_err(log_message_and_return(
             "Can't build HTTP client.",
             CantCreateClient,
         ))
 }
+
+/// Send the request, and send it again if it failed because of a connection error,
+/// a timeout or a server error.
+/// Non-idempotent requests (e.g. POST) may have been handled by the server even if it failed,
+/// so they are only sent again when the connection couldn't be established.
+/// When all attempts have failed, the last error or response is returned.
+pub async fn send_with_retries(request: RequestBuilder) -> reqwest::Result<Response> {
+    let Some(template) = request.try_clone() else {
+        // Streamed bodies can't be sent twice
+        return request.send().await;
+    };
+    let (operation, idempotent) = match request.build() {
+        Ok(request) => (
+            format!("{} {}", request.method(), request.url()),
+            request.method().is_idempotent(),
+        ),
+        Err(_) => ("HTTP request".to_owned(), false),
+    };
+    let should_retry = if idempotent {
+        is_transient_failure
+    } else {
+        is_connection_failure
+    };
+
+    with_retries(&operation, should_retry, || {
+        template
+            .try_clone()
+            .expect("request should be cloneable")
+            .send()
+    })
+    .await
+}
+
+fn is_transient_failure(result: &reqwest::Result<Response>) -> bool {
+    match result {
+        Ok(response) => response.status().is_server_error(),
+        Err(error) => error.is_connect() || error.is_timeout(),
+    }
+}
+
+fn is_connection_failure(result: &reqwest::Result<Response>) -> bool {
+    matches!(result, Err(error) if error.is_connect())
+}
+
+/// Run the operation, and run it again with an exponential backoff
+/// as long as its result should be retried and the maximum number of retries is not reached.
+pub async fn with_retries<T, E, F, Fut>(
+    operation: &str,
+    should_retry: impl Fn(&Result<T, E>) -> bool,
+    mut run: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let config = retrieve_outbound_requests_config();
+    let mut attempt = 1;
+    loop {
+        let result = run().await;
+        if !should_retry(&result) {
+            if attempt > 1 {
+                info!("{operation} succeeded after {attempt} attempts");
+            }
+            return result;
+        }
+        if attempt > *config.max_retries() {
+            error!("{operation} failed after {attempt} attempts");
+            return result;
+        }
+
+        let backoff = config.backoff(attempt);
+        warn!("{operation} failed on attempt {attempt}, retrying in {backoff:?}");
+        rocket::tokio::time::sleep(backoff).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_outbound_requests_config {
+        use crate::tools::env_args::with_env_args;
+        use crate::tools::web::{
+            INITIAL_BACKOFF_ARG, MAX_RETRIES_ARG, OutboundRequestsConfig, TIMEOUT_ARG,
+            retrieve_outbound_requests_config,
+        };
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            let args = vec![
+                format!("{TIMEOUT_ARG}=5"),
+                format!("{MAX_RETRIES_ARG}=2"),
+                format!("{INITIAL_BACKOFF_ARG}=100"),
+            ];
+
+            let config = with_env_args(args, retrieve_outbound_requests_config);
+            assert_eq!(
+                OutboundRequestsConfig::new(Duration::from_secs(5), 2, Duration::from_millis(100)),
+                config
+            );
+        }
+    }
+
+    mod backoff {
+        use crate::tools::web::OutboundRequestsConfig;
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            let config =
+                OutboundRequestsConfig::new(Duration::from_secs(1), 3, Duration::from_millis(100));
+
+            assert_eq!(Duration::from_millis(100), config.backoff(1));
+            assert_eq!(Duration::from_millis(200), config.backoff(2));
+            assert_eq!(Duration::from_millis(400), config.backoff(3));
+        }
+    }
+
+    mod send_with_retries {
+        use crate::tools::web::{build_client, send_with_retries};
+        use reqwest::StatusCode;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        #[async_test]
+        async fn success_after_server_error() {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/flaky"))
+                .respond_with(ResponseTemplate::new(503))
+                .up_to_n_times(2)
+                .expect(2)
+                .mount(&mock_server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/flaky"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let client = build_client().unwrap();
+            let request = client.get(format!("{}/flaky", mock_server.uri()));
+            let response = send_with_retries(request).await.unwrap();
+
+            assert_eq!(StatusCode::OK, response.status());
+        }
+
+        #[async_test]
+        async fn fail_when_retries_exhausted() {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/down"))
+                .respond_with(ResponseTemplate::new(500))
+                .expect(4)
+                .mount(&mock_server)
+                .await;
+
+            let client = build_client().unwrap();
+            let request = client.get(format!("{}/down", mock_server.uri()));
+            let response = send_with_retries(request).await.unwrap();
+
+            assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        }
+
+        #[async_test]
+        async fn no_retry_when_client_error() {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/missing"))
+                .respond_with(ResponseTemplate::new(404))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let client = build_client().unwrap();
+            let request = client.get(format!("{}/missing", mock_server.uri()));
+            let response = send_with_retries(request).await.unwrap();
+
+            assert_eq!(StatusCode::NOT_FOUND, response.status());
+        }
+
+        #[async_test]
+        async fn no_retry_when_non_idempotent_request_fails_on_server() {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/login"))
+                .respond_with(ResponseTemplate::new(503))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let client = build_client().unwrap();
+            let request = client.post(format!("{}/login", mock_server.uri()));
+            let response = send_with_retries(request).await.unwrap();
+
+            assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+        }
+    }
+}
@@ -1,39 +1,70 @@
 use crate::database;
+use crate::database::DatabaseConnection;
 use crate::database::error::DatabaseError;
 use crate::error::Result;
-use diesel::SqliteConnection;
+use crate::import_history::ImportTracker;
+use crate::tools::web::with_retries;
+use crate::uda::retry::is_transient_failure;
 use diesel::r2d2::{ConnectionManager, Pool};
+use dto::import_history::{ImportCounts, ImportOutcome, ImportSource, ImportedElement};
 use dto::uda_instance::Instance;
 use reqwest::Client;
 use uda_connector::configuration::Configuration;
 
-/// Retrieve a list of all UDA instances.
+/// Retrieve a list of all UDA instances, and replace the known ones with them.
+/// The refresh is recorded in the import history, whatever its outcome.
 pub async fn retrieve_uda_instances(
-    pool: &Pool<ConnectionManager<SqliteConnection>>,
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
     client: &Client,
     configuration: &Configuration,
 ) -> Result<Vec<Instance>> {
-    let instances = uda_connector::instances::retrieve_uda_instances(
-        client,
-        configuration.instances_list_url().as_str(),
-    )
+    let tracker = ImportTracker::new(ImportedElement::UdaInstances, ImportSource::Scraping, None);
+    match refresh_uda_instances(pool, client, configuration).await {
+        Ok((instances, (deleted_count, inserted_count))) => {
+            tracker.succeeded(pool, ImportCounts::new(deleted_count, inserted_count, 0, 0));
+            Ok(instances)
+        }
+        Err(error) => {
+            tracker.failed(
+                pool,
+                ImportOutcome::Failure,
+                ImportCounts::default(),
+                &error,
+            );
+            Err(error)
+        }
+    }
+}
+
+async fn refresh_uda_instances(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    client: &Client,
+    configuration: &Configuration,
+) -> Result<(Vec<Instance>, (usize, usize))> {
+    let instances = with_retries("UDA instances retrieval", is_transient_failure, || {
+        uda_connector::instances::retrieve_uda_instances(
+            client,
+            configuration.instances_list_url().as_str(),
+        )
+    })
     .await?;
 
     let mut connection = pool.get().map_err(DatabaseError::from)?;
-    database::dao::uda_instance::replace_all(&mut connection, &instances)?;
+    let counts = database::dao::uda_instance::replace_all(&mut connection, &instances)?;
 
-    Ok(instances)
+    Ok((instances, counts))
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
     mod retrieve_uda_instances {
-        use crate::database::with_temp_database;
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
         use crate::error::ApplicationError::Uda;
         use crate::tools::web::build_client;
         use crate::uda::instances::retrieve_uda_instances;
-        use diesel::SqliteConnection;
         use diesel::r2d2::ConnectionManager;
+        use dto::import_history::{ImportOutcome, ImportedElement};
         use r2d2::Pool;
         use reqwest::header::LOCATION;
         use rocket::tokio::runtime::Runtime;
@@ -44,7 +75,7 @@ pub(crate) mod tests {
 
         #[test]
         fn success() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
 
                 Mock::given(method("GET"))
@@ -61,6 +92,13 @@ pub(crate) mod tests {
                     .unwrap();
 
                 assert_eq!(get_expected_instances(), instances);
+
+                let mut connection = pool.get().unwrap();
+                let history = dao::import_history::retrieve(&mut connection, None).unwrap();
+                assert_eq!(1, history.len());
+                assert_eq!(&ImportedElement::UdaInstances, history[0].element());
+                assert_eq!(&ImportOutcome::Success, history[0].outcome());
+                assert_eq!(&instances.len(), history[0].counts().inserted());
             }
 
             with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
@@ -68,7 +106,7 @@ pub(crate) mod tests {
 
         #[test]
         fn fail_when_connection_failed() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
 
                 Mock::given(method("GET"))
@@ -98,7 +136,7 @@ pub(crate) mod tests {
 
         #[test]
         fn fail_when_cant_read_page_content() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
 
                 Mock::given(method("GET"))
@@ -118,6 +156,10 @@ pub(crate) mod tests {
                     error,
                     Uda(uda_connector::error::UdaError::CantReadPageContent)
                 ));
+
+                let mut connection = pool.get().unwrap();
+                let history = dao::import_history::retrieve(&mut connection, None).unwrap();
+                assert_eq!(&ImportOutcome::Failure, history[0].outcome());
             }
             with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
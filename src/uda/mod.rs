@@ -1,3 +1,4 @@
 pub(crate) mod authentication;
 pub(crate) mod credentials;
 pub(crate) mod instances;
+pub(crate) mod retry;
@@ -0,0 +1,25 @@
+use uda_connector::error::UdaError;
+
+/// Whether a call to UDA failed for a reason that may not last, so that it is worth retrying.
+/// A connection failure may also occur once the request has been sent,
+/// so only idempotent calls (e.g. retrievals or authentication) should be retried.
+pub(crate) fn is_transient_failure<T>(result: &Result<T, UdaError>) -> bool {
+    matches!(result, Err(UdaError::ConnectionFailed))
+}
+
+#[cfg(test)]
+mod tests {
+    mod is_transient_failure {
+        use crate::uda::retry::is_transient_failure;
+        use uda_connector::error::UdaError;
+
+        #[test]
+        fn success() {
+            assert!(is_transient_failure::<()>(&Err(UdaError::ConnectionFailed)));
+            assert!(!is_transient_failure::<()>(&Err(
+                UdaError::LackOfPermissions
+            )));
+            assert!(!is_transient_failure(&Ok(())));
+        }
+    }
+}
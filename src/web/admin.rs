@@ -0,0 +1,103 @@
+use crate::fileo::credentials::FileoCredentials;
+use crate::tools::env_args::retrieve_arg_value;
+use rocket::State;
+use rocket::http::Status;
+use rocket::outcome::{Outcome, try_outcome};
+use rocket::request::{self, FromRequest, Request};
+use std::ops::Deref;
+
+const ADMIN_LOGINS_ARG: &str = "--admin-logins";
+
+/// The Fileo logins allowed to use the administration features.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AdminLogins {
+    logins: Vec<String>,
+}
+
+impl AdminLogins {
+    pub fn new(logins: Vec<String>) -> Self {
+        Self { logins }
+    }
+
+    pub fn contains(&self, login: &str) -> bool {
+        self.logins.iter().any(|admin_login| admin_login == login)
+    }
+}
+
+/// Build the admin logins from the comma-separated `--admin-logins` arg.
+/// If missing, nobody can use the administration features.
+pub fn retrieve_admin_logins() -> AdminLogins {
+    let logins = retrieve_arg_value(ADMIN_LOGINS_ARG)
+        .map(|logins| {
+            logins
+                .split(',')
+                .map(|login| login.trim().to_owned())
+                .filter(|login| !login.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    AdminLogins::new(logins)
+}
+
+/// The Fileo credentials of an admin.
+/// If an endpoint is reserved to admins, then its implementation should require an [AdminCredentials] parameter.
+/// Callers that are not logged in are forwarded with an Unauthorized status,
+/// while callers that are logged in without being admins receive a Forbidden status.
+#[derive(Debug, Clone)]
+pub struct AdminCredentials {
+    credentials: FileoCredentials,
+}
+
+impl Deref for AdminCredentials {
+    type Target = FileoCredentials;
+
+    fn deref(&self) -> &Self::Target {
+        &self.credentials
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminCredentials {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let credentials = try_outcome!(req.guard::<FileoCredentials>().await);
+        let admin_logins = try_outcome!(req.guard::<&State<AdminLogins>>().await);
+        if admin_logins.contains(credentials.login()) {
+            Outcome::Success(AdminCredentials { credentials })
+        } else {
+            warn!(
+                "Non-admin user tried to access an admin feature [login: {}]",
+                credentials.login()
+            );
+            Outcome::Error((Status::Forbidden, ()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod retrieve_admin_logins {
+        use crate::tools::env_args::with_env_args;
+        use crate::web::admin::{ADMIN_LOGINS_ARG, AdminLogins, retrieve_admin_logins};
+
+        #[test]
+        fn success() {
+            let admin_logins = with_env_args(
+                vec![format!("{ADMIN_LOGINS_ARG}=admin, other_admin,")],
+                retrieve_admin_logins,
+            );
+            assert_eq!(
+                AdminLogins::new(vec!["admin".to_owned(), "other_admin".to_owned()]),
+                admin_logins
+            );
+        }
+
+        #[test]
+        fn nobody_when_missing() {
+            let admin_logins = with_env_args(vec![], retrieve_admin_logins);
+            assert_eq!(AdminLogins::default(), admin_logins);
+        }
+    }
+}
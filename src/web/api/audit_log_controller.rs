@@ -0,0 +1,141 @@
+use crate::audit::build_filter;
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::tools::log_error_and_return;
+use crate::web::admin::AdminCredentials;
+use diesel::r2d2::{ConnectionManager, Pool};
+use dto::audit_log::AuditLogEntry;
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+/// Browse the audit log, the most recent entries first. Reserved to admins.
+/// Entries can be filtered by actor, action and dates (YYYY-MM-DD, inclusive).
+/// Return a 400 status if the action or a date is invalid.
+#[get("/audit-log?<actor>&<action>&<from>&<to>")]
+pub async fn list_audit_log(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    actor: Option<&str>,
+    action: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    _credentials: AdminCredentials,
+) -> Result<Json<Vec<AuditLogEntry>>, Status> {
+    let filter = build_filter(actor, action, from, to).map_err(|error| {
+        debug!("Invalid audit log filter [error: {error}]");
+        Status::BadRequest
+    })?;
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let entries = dao::audit_log::retrieve(&mut connection, &filter)
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+
+    Ok(Json(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    mod list_audit_log {
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::admin::AdminLogins;
+        use crate::web::api::audit_log_controller::list_audit_log;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use chrono::NaiveDate;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::audit_log::{AuditAction, AuditLogEntry};
+        use rocket::http::Status;
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use uuid::Uuid;
+
+        fn create_entry(actor: &str, action: AuditAction) -> AuditLogEntry {
+            AuditLogEntry::new(
+                NaiveDate::from_ymd_opt(2025, 4, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 30, 0)
+                    .unwrap(),
+                actor.to_owned(),
+                action,
+                1,
+                vec!["123456".to_owned()],
+            )
+        }
+
+        async fn create_client(
+            pool: Pool<ConnectionManager<DatabaseConnection>>,
+            admin_logins: Vec<String>,
+        ) -> (Client, String) {
+            let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
+            let uuid = Uuid::new_v4().to_string();
+            credentials_storage.store(
+                uuid.clone(),
+                FileoCredentials::new("test_login".to_owned(), "test_password".to_owned()),
+            );
+
+            let rocket = rocket::build()
+                .manage(credentials_storage)
+                .manage(AdminLogins::new(admin_logins))
+                .manage(pool)
+                .mount("/", routes![list_audit_log]);
+
+            (Client::tracked(rocket).await.unwrap(), uuid)
+        }
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mut connection = pool.get().unwrap();
+                let expected_entry = create_entry("jon", AuditAction::Notification);
+                dao::audit_log::insert(&mut connection, &expected_entry).unwrap();
+                dao::audit_log::insert(&mut connection, &create_entry("jon", AuditAction::LookUp))
+                    .unwrap();
+
+                let (client, uuid) = create_client(pool, vec!["test_login".to_owned()]).await;
+                let response = client
+                    .get("/audit-log?actor=jon&action=Notification&from=2025-04-01&to=")
+                    .cookie((AUTHENTICATION_COOKIE, uuid))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::Ok, response.status());
+                let entries: Vec<AuditLogEntry> = response.into_json().await.unwrap();
+                assert_eq!(vec![expected_entry], entries);
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn bad_request_when_unknown_action() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, uuid) = create_client(pool, vec!["test_login".to_owned()]).await;
+                let response = client
+                    .get("/audit-log?action=Delete")
+                    .cookie((AUTHENTICATION_COOKIE, uuid))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::BadRequest, response.status());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn forbidden_when_not_admin() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, uuid) = create_client(pool, vec!["admin".to_owned()]).await;
+                let response = client
+                    .get("/audit-log")
+                    .cookie((AUTHENTICATION_COOKIE, uuid))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::Forbidden, response.status());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::database::DatabaseConnection;
+use crate::database::backup::backup_database;
+use crate::database::error::DatabaseError;
+use crate::tools::{log_error_and_return, log_message_and_return};
+use crate::web::admin::AdminCredentials;
+use chrono::Utc;
+use diesel::r2d2::{ConnectionManager, Pool};
+use rocket::State;
+use rocket::http::{Header, Status};
+use std::fs;
+use uuid::Uuid;
+
+#[derive(Debug, Responder)]
+#[response(content_type = "binary")]
+pub struct DatabaseBackup {
+    content: Vec<u8>,
+    content_disposition: Header<'static>,
+}
+
+/// Back the database up while the app keeps running, and send the backup as a file.
+/// Reserved to admins.
+/// Only SQLite databases can be backed up this way: a 501 status is returned for PostgreSQL.
+#[get("/database/backup")]
+pub async fn download_backup(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    credentials: AdminCredentials,
+) -> Result<DatabaseBackup, Status> {
+    let backup = std::env::temp_dir().join(format!("backup-{}.db", Uuid::new_v4()));
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    match backup_database(&mut connection, &backup) {
+        Ok(()) => {}
+        Err(DatabaseError::UnsupportedBackend) => return Err(Status::NotImplemented),
+        Err(error) => return Err(log_error_and_return(Status::InternalServerError)(error)),
+    }
+    let content = fs::read(&backup).map_err(log_message_and_return(
+        "Can't read database backup",
+        Status::InternalServerError,
+    ))?;
+    if let Err(error) = fs::remove_file(&backup) {
+        warn!("Can't delete temporary database backup [file: {backup:?}, error: {error}]");
+    }
+    info!(
+        "Database backup downloaded [login: {}]",
+        credentials.login()
+    );
+
+    let file_name = format!("backup-{}.db", Utc::now().format("%Y%m%d-%H%M%S"));
+    Ok(DatabaseBackup {
+        content,
+        content_disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{file_name}\""),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    mod download_backup {
+        use crate::database::DatabaseConnection;
+        use crate::database::with_temp_sqlite_database;
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::admin::AdminLogins;
+        use crate::web::api::database_controller::download_backup;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use rocket::http::Status;
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+
+        const UUID: &str = "e9af5e0f-c441-4bcd-bf22-31cc5b1f2f9e";
+
+        async fn create_client(
+            pool: Pool<ConnectionManager<DatabaseConnection>>,
+            admin_logins: Vec<String>,
+        ) -> Client {
+            let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
+            credentials_storage.store(
+                UUID.to_owned(),
+                FileoCredentials::new("test_login".to_owned(), "test_password".to_owned()),
+            );
+
+            let rocket = rocket::build()
+                .manage(credentials_storage)
+                .manage(AdminLogins::new(admin_logins))
+                .manage(pool)
+                .mount("/", routes![download_backup]);
+
+            Client::tracked(rocket).await.unwrap()
+        }
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let client = create_client(pool, vec!["test_login".to_owned()]).await;
+                let response = client
+                    .get("/database/backup")
+                    .cookie((AUTHENTICATION_COOKIE, UUID))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::Ok, response.status());
+                let content = response.into_bytes().await.unwrap();
+                assert!(content.starts_with(b"SQLite format 3"));
+            }
+            with_temp_sqlite_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn forbidden_when_not_admin() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let client = create_client(pool, vec!["admin".to_owned()]).await;
+                let response = client
+                    .get("/database/backup")
+                    .cookie((AUTHENTICATION_COOKIE, UUID))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::Forbidden, response.status());
+            }
+            with_temp_sqlite_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+}
@@ -1,20 +1,31 @@
-use crate::database::dao::membership::replace_memberships;
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::database::error::DatabaseError;
 use crate::error::ApplicationError;
 use crate::fileo::authentication::AUTHENTICATION_COOKIE;
 use crate::fileo::credentials::FileoCredentials;
-use crate::fileo::download::{download_memberships_list, login_to_fileo};
+use crate::fileo::download::{download_memberships_list, login_to_fileo, read_memberships_export};
+use crate::fileo::health_check::check_fileo_health;
+use crate::import_history::ImportTracker;
 use crate::membership::config::MembershipsProviderConfig;
+use crate::membership::error::MembershipError;
+use crate::membership::import::{import_counts, import_memberships};
 use crate::tools::web::build_client;
 use crate::tools::{log_error_and_return, log_message_and_return};
+use crate::web::admin::AdminLogins;
 use crate::web::credentials_storage::CredentialsStorage;
 use crate::web::error::WebError;
-use diesel::SqliteConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
+use dto::fileo_health::FileoHealthReport;
+use dto::import_history::{ImportCounts, ImportOutcome, ImportSource, ImportedElement};
+use dto::import_rejection::ImportRejection;
+use dto::import_report::ImportReport;
+use dto::membership::Membership;
 use rocket::State;
+use rocket::data::{Data, ToByteUnit};
 use rocket::http::{Cookie, CookieJar, Status};
 use rocket::serde::json::Json;
 use rocket::time::Duration;
-use std::sync::Mutex;
 use uuid::Uuid;
 
 /// Try and log a user onto Fileo app.
@@ -24,7 +35,7 @@ use uuid::Uuid;
 #[post("/fileo/login", format = "application/json", data = "<credentials>")]
 pub async fn login(
     memberships_provider_config: &State<MembershipsProviderConfig>,
-    credentials_storage: &State<Mutex<CredentialsStorage<FileoCredentials>>>,
+    credentials_storage: &State<CredentialsStorage<FileoCredentials>>,
     cookie_jar: &CookieJar<'_>,
     credentials: Json<FileoCredentials>,
 ) -> Result<(Status, ()), Status> {
@@ -33,15 +44,12 @@ pub async fn login(
     let credentials = credentials.into_inner();
     match login_to_fileo(&client, host, &credentials).await {
         Ok(_) => {
-            let mut mutex = credentials_storage
-                .lock()
-                .map_err(log_error_and_return(Status::InternalServerError))?;
             let uuid = Uuid::new_v4().to_string();
             let cookie = Cookie::build((AUTHENTICATION_COOKIE.to_owned(), uuid.clone()))
                 .max_age(Duration::days(365))
                 .build();
             cookie_jar.add_private(cookie);
-            (*mutex).store(uuid.clone(), credentials);
+            credentials_storage.store(uuid.clone(), credentials);
             Ok((Status::Ok, ()))
         }
         Err(ApplicationError::Web(WebError::LackOfPermissions)) => Err(Status::Forbidden),
@@ -50,34 +58,205 @@ pub async fn login(
     }
 }
 
+const MAX_UPLOADED_FILE_SIZE_IN_MEBIBYTES: u8 = 20;
+
+#[derive(Debug, Responder)]
+pub enum ImportMembershipsError {
+    #[response(status = 409)]
+    SuspiciousMembershipsDrop(Json<ImportRejection>),
+    Failure(Status),
+}
+
 /// Download memberships csv file from remote provided in config,
 /// write said file into filesystem
 /// and load it into memory.
 /// Finally, clean all old memberships files.
-#[get("/fileo/memberships", format = "text/plain-text")]
+/// Return the report of the rows that couldn't be read.
+/// If too many memberships would disappear, the import is refused with a conflict,
+/// unless it is forced, which only admins can do.
+#[get("/fileo/memberships?<force>", format = "text/plain-text")]
 pub async fn download_memberships(
     memberships_provider_config: &State<MembershipsProviderConfig>,
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    admin_logins: &State<AdminLogins>,
+    credentials: FileoCredentials,
+    force: Option<bool>,
+) -> Result<Json<ImportReport>, ImportMembershipsError> {
+    let force = check_force(admin_logins, &credentials, force)?;
+    let tracker = ImportTracker::new(
+        ImportedElement::Memberships,
+        ImportSource::Scraping,
+        Some(credentials.login().clone()),
+    );
+    let (memberships, report) =
+        match download_memberships_list(memberships_provider_config, &credentials).await {
+            Ok(result) => result,
+            Err(error) => {
+                error!("Can't download memberships list\n{error:#?}");
+                tracker.failed(
+                    pool,
+                    ImportOutcome::Failure,
+                    ImportCounts::default(),
+                    &error,
+                );
+                return Err(ImportMembershipsError::Failure(Status::InternalServerError));
+            }
+        };
+
+    import(pool, tracker, &memberships, report, force)
+}
+
+/// Import a memberships list exported from Fileo by hand,
+/// for when Fileo can't be reached from the server.
+/// Apart from that, it behaves as [download_memberships].
+#[post(
+    "/fileo/memberships/upload?<force>",
+    format = "text/csv",
+    data = "<file>"
+)]
+pub async fn upload_memberships(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    admin_logins: &State<AdminLogins>,
     credentials: FileoCredentials,
-) -> Result<Status, Status> {
-    let memberships = download_memberships_list(memberships_provider_config, &credentials)
+    file: Data<'_>,
+    force: Option<bool>,
+) -> Result<Json<ImportReport>, ImportMembershipsError> {
+    let force = check_force(admin_logins, &credentials, force)?;
+    let tracker = ImportTracker::new(
+        ImportedElement::Memberships,
+        ImportSource::ManualUpload,
+        Some(credentials.login().clone()),
+    );
+    let file_content = file
+        .open(MAX_UPLOADED_FILE_SIZE_IN_MEBIBYTES.mebibytes())
+        .into_bytes()
         .await
         .map_err(log_message_and_return(
-            "Can't download memberships list",
-            Status::InternalServerError,
+            "Can't read uploaded memberships list",
+            ImportMembershipsError::Failure(Status::BadRequest),
         ))?;
+    if !file_content.is_complete() {
+        error!("Uploaded memberships list is too large");
+        return Err(ImportMembershipsError::Failure(Status::PayloadTooLarge));
+    }
+
+    let (memberships, report) = match read_memberships_export(&file_content) {
+        Ok(result) => result,
+        Err(error) => {
+            error!("Can't read uploaded memberships list\n{error:#?}");
+            tracker.failed(
+                pool,
+                ImportOutcome::Failure,
+                ImportCounts::default(),
+                &error,
+            );
+            return Err(ImportMembershipsError::Failure(Status::BadRequest));
+        }
+    };
+
+    import(pool, tracker, &memberships, report, force)
+}
+
+/// Run the whole Fileo flow without importing anything, to detect whether Fileo has changed.
+/// The report is recorded, so that it can be looked at later on.
+/// If a step broke, then the report is returned with a Bad Gateway status.
+#[get("/fileo/health")]
+pub async fn check_health(
+    memberships_provider_config: &State<MembershipsProviderConfig>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    credentials: FileoCredentials,
+) -> Result<(Status, Json<FileoHealthReport>), Status> {
+    let report = check_fileo_health(memberships_provider_config, &credentials)
+        .await
+        .map_err(log_error_and_return(Status::InternalServerError))?;
 
     let mut connection = pool
         .get()
         .map_err(log_error_and_return(Status::InternalServerError))?;
-    replace_memberships(&mut connection, &memberships)
+    dao::fileo_health_check::insert(&mut connection, &report)
         .map_err(log_error_and_return(Status::InternalServerError))?;
 
-    Ok(Status::NoContent)
+    let status = if report.is_healthy() {
+        Status::Ok
+    } else {
+        Status::BadGateway
+    };
+    Ok((status, Json(report)))
+}
+
+/// Retrieve the report of the most recent Fileo health check.
+#[get("/fileo/health/last")]
+pub async fn last_health_check(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    _credentials: FileoCredentials,
+) -> Result<Json<FileoHealthReport>, Status> {
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    dao::fileo_health_check::retrieve_last(&mut connection)
+        .map_err(log_error_and_return(Status::InternalServerError))?
+        .map(Json)
+        .ok_or(Status::NotFound)
+}
+
+/// Forcing an import bypasses the check on the memberships drop, so it is reserved to admins.
+/// Return whether the import should be forced.
+fn check_force(
+    admin_logins: &AdminLogins,
+    credentials: &FileoCredentials,
+    force: Option<bool>,
+) -> Result<bool, ImportMembershipsError> {
+    let force = force.unwrap_or(false);
+    if force && !admin_logins.contains(credentials.login()) {
+        warn!(
+            "Non-admin user tried to force a memberships import [login: {}]",
+            credentials.login()
+        );
+        return Err(ImportMembershipsError::Failure(Status::Forbidden));
+    }
+
+    Ok(force)
+}
+
+/// Import the memberships, then record the import in the history, whatever its outcome.
+fn import(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    tracker: ImportTracker,
+    memberships: &[Membership],
+    report: ImportReport,
+    force: bool,
+) -> Result<Json<ImportReport>, ImportMembershipsError> {
+    let rejected_counts = ImportCounts::new(0, 0, 0, report.rejected_rows().len());
+    let result = pool
+        .get()
+        .map_err(|error| ApplicationError::from(DatabaseError::from(error)))
+        .and_then(|mut connection| {
+            import_memberships(&mut connection, memberships, &report, force)
+        });
+    match result {
+        Ok(diff) => {
+            tracker.succeeded(pool, import_counts(&diff, &report));
+            Ok(Json(report))
+        }
+        Err(ApplicationError::Membership(MembershipError::SuspiciousMembershipsDrop(
+            rejection,
+        ))) => {
+            tracker.failed(pool, ImportOutcome::Refused, rejected_counts, &rejection);
+            Err(ImportMembershipsError::SuspiciousMembershipsDrop(Json(
+                rejection,
+            )))
+        }
+        Err(error) => {
+            error!("{error:#?}");
+            tracker.failed(pool, ImportOutcome::Failure, rejected_counts, &error);
+            Err(ImportMembershipsError::Failure(Status::InternalServerError))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::fileo::export_filters::FileoExportFilters;
     use crate::membership::config::MembershipsProviderConfig;
     use regex::Regex;
     use wiremock::matchers::{body_string_contains, method, path};
@@ -87,6 +266,7 @@ mod tests {
         MembershipsProviderConfig::new(
             uri.to_owned(),
             Regex::new(&format!("{}/download\\.csv", uri)).unwrap(),
+            FileoExportFilters::default(),
         )
     }
 
@@ -102,6 +282,7 @@ mod tests {
     }
 
     mod login {
+        use crate::database::DatabaseConnection;
         use crate::database::with_temp_database;
         use crate::fileo::authentication::AUTHENTICATION_COOKIE;
         use crate::fileo::credentials::FileoCredentials;
@@ -110,20 +291,18 @@ mod tests {
             create_memberships_provider_test_config, setup_login,
         };
         use crate::web::credentials_storage::CredentialsStorage;
-        use diesel::SqliteConnection;
         use diesel::r2d2::{ConnectionManager, Pool};
         use reqwest::header::CONTENT_TYPE;
         use rocket::http::{ContentType, Header, Status};
         use rocket::local::asynchronous::Client;
         use rocket::serde::json::json;
         use rocket::tokio::runtime::Runtime;
-        use std::sync::Mutex;
         use wiremock::matchers::{body_string_contains, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         #[test]
         fn success() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
                 setup_login(&mock_server).await;
 
@@ -131,12 +310,11 @@ mod tests {
 
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let credentials_storage_mutex =
-                    Mutex::new(CredentialsStorage::<FileoCredentials>::default());
+                let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
 
                 let rocket = rocket::build()
                     .manage(config)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .manage(pool)
                     .mount("/", routes![login]);
                 let client = Client::tracked(rocket).await.unwrap();
@@ -163,7 +341,7 @@ mod tests {
 
         #[test]
         fn fail_when_unauthorized() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
                 Mock::given(method("POST"))
                     .and(path("/page.php"))
@@ -176,12 +354,11 @@ mod tests {
 
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let credentials_storage_mutex =
-                    Mutex::new(CredentialsStorage::<FileoCredentials>::default());
+                let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
 
                 let rocket = rocket::build()
                     .manage(config)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .manage(pool)
                     .mount("/", routes![login]);
                 let client = Client::tracked(rocket).await.unwrap();
@@ -208,7 +385,7 @@ mod tests {
 
         #[test]
         fn fail_when_forbidden() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
                 Mock::given(method("POST"))
                     .and(path("/page.php"))
@@ -224,12 +401,11 @@ mod tests {
 
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let credentials_storage_mutex =
-                    Mutex::new(CredentialsStorage::<FileoCredentials>::default());
+                let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
 
                 let rocket = rocket::build()
                     .manage(config)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .manage(pool)
                     .mount("/", routes![login]);
                 let client = Client::tracked(rocket).await.unwrap();
@@ -256,7 +432,7 @@ mod tests {
 
         #[test]
         fn fail_when_bad_gateway() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
                 Mock::given(method("POST"))
                     .and(path("/page.php"))
@@ -269,12 +445,11 @@ mod tests {
 
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let credentials_storage_mutex =
-                    Mutex::new(CredentialsStorage::<FileoCredentials>::default());
+                let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
 
                 let rocket = rocket::build()
                     .manage(config)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .manage(pool)
                     .mount("/", routes![login]);
                 let client = Client::tracked(rocket).await.unwrap();
@@ -301,16 +476,18 @@ mod tests {
     }
 
     mod download_members {
+        use crate::database::DatabaseConnection;
         use crate::database::{dao, with_temp_database};
         use crate::fileo::authentication::AUTHENTICATION_COOKIE;
         use crate::fileo::credentials::FileoCredentials;
-        use crate::web::api::fileo_controller::download_memberships;
+        use crate::web::admin::AdminLogins;
         use crate::web::api::fileo_controller::tests::{
             create_memberships_provider_test_config, setup_login,
         };
+        use crate::web::api::fileo_controller::{ImportMembershipsError, download_memberships};
         use crate::web::credentials_storage::CredentialsStorage;
-        use diesel::SqliteConnection;
         use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::import_report::ImportReport;
         use dto::membership::tests::{get_expected_membership, get_membership_as_csv};
         use encoding::all::ISO_8859_1;
         use encoding::{EncoderTrap, Encoding};
@@ -318,13 +495,12 @@ mod tests {
         use rocket::http::{Cookie, Status};
         use rocket::local::asynchronous::Client;
         use rocket::tokio::runtime::Runtime;
-        use std::sync::Mutex;
         use wiremock::matchers::{body_string_contains, method, path, query_param_contains};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         #[test]
         fn should_download_members() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
 
                 let config = create_memberships_provider_test_config(&mock_server.uri());
@@ -366,15 +542,15 @@ mod tests {
 
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let mut credentials_storage = CredentialsStorage::default();
+                let credentials_storage = CredentialsStorage::default();
                 let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
                 credentials_storage.store(uuid.clone(), credentials);
-                let credentials_storage_mutex = Mutex::new(credentials_storage);
 
                 let rocket = rocket::build()
                     .manage(config)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .manage(pool)
+                    .manage(AdminLogins::default())
                     .mount("/", routes![download_memberships]);
                 let client = Client::tracked(rocket).await.unwrap();
 
@@ -382,11 +558,13 @@ mod tests {
                 let request = client.get("/fileo/memberships").cookie(cookie);
                 let response = request.dispatch().await;
 
-                assert_eq!(Status::NoContent, response.status());
+                assert_eq!(Status::Ok, response.status());
+                let report = response.into_json::<ImportReport>().await.unwrap();
+                assert!(report.rejected_rows().is_empty());
 
                 let mut connection = client
                     .rocket()
-                    .state::<Pool<ConnectionManager<SqliteConnection>>>()
+                    .state::<Pool<ConnectionManager<DatabaseConnection>>>()
                     .unwrap()
                     .get()
                     .unwrap();
@@ -398,7 +576,7 @@ mod tests {
 
         #[test]
         fn should_not_download_members_when_error() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
 
                 let config = create_memberships_provider_test_config(&mock_server.uri());
@@ -407,11 +585,230 @@ mod tests {
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
                 let pool_state = State::from(&pool);
+                let admin_logins = AdminLogins::default();
+                let admin_logins_state = State::from(&admin_logins);
+
+                let result = download_memberships(
+                    config_state,
+                    pool_state,
+                    admin_logins_state,
+                    credentials,
+                    None,
+                )
+                .await;
+                assert!(matches!(
+                    result,
+                    Err(ImportMembershipsError::Failure(Status::InternalServerError))
+                ));
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod upload_memberships {
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::admin::AdminLogins;
+        use crate::web::api::fileo_controller::upload_memberships;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::import_report::ImportReport;
+        use dto::membership::tests::{get_expected_membership, get_membership_as_csv};
+        use encoding::all::ISO_8859_1;
+        use encoding::{EncoderTrap, Encoding};
+        use rocket::http::{ContentType, Cookie, Status};
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+
+        async fn build_client(
+            pool: Pool<ConnectionManager<DatabaseConnection>>,
+            admin_logins: Vec<String>,
+        ) -> (Client, Cookie<'static>) {
+            let credentials =
+                FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
+            let credentials_storage = CredentialsStorage::default();
+            let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
+            credentials_storage.store(uuid.clone(), credentials);
+
+            let rocket = rocket::build()
+                .manage(credentials_storage)
+                .manage(pool)
+                .manage(AdminLogins::new(admin_logins))
+                .mount("/", routes![upload_memberships]);
+            let client = Client::tracked(rocket).await.unwrap();
+            (client, Cookie::new(AUTHENTICATION_COOKIE, uuid))
+        }
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, cookie) = build_client(pool, vec![]).await;
+                let file_content = ISO_8859_1
+                    .encode(&get_membership_as_csv(), EncoderTrap::Strict)
+                    .unwrap();
+
+                let request = client
+                    .post("/fileo/memberships/upload")
+                    .header(ContentType::CSV)
+                    .cookie(cookie)
+                    .body(file_content);
+                let response = request.dispatch().await;
+
+                assert_eq!(Status::Ok, response.status());
+                let report = response.into_json::<ImportReport>().await.unwrap();
+                assert!(report.rejected_rows().is_empty());
+
+                let mut connection = client
+                    .rocket()
+                    .state::<Pool<ConnectionManager<DatabaseConnection>>>()
+                    .unwrap()
+                    .get()
+                    .unwrap();
+                let memberships = dao::membership::retrieve_memberships(&mut connection).unwrap();
+                assert_eq!(vec![get_expected_membership()], memberships);
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn fail_when_unauthenticated() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, _) = build_client(pool, vec![]).await;
+
+                let request = client
+                    .post("/fileo/memberships/upload")
+                    .header(ContentType::CSV)
+                    .body(get_membership_as_csv());
+                let response = request.dispatch().await;
+
+                assert_eq!(Status::Unauthorized, response.status());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn success_when_forced_by_admin() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, cookie) = build_client(pool, vec!["test_login".to_owned()]).await;
+                let file_content = ISO_8859_1
+                    .encode(&get_membership_as_csv(), EncoderTrap::Strict)
+                    .unwrap();
+
+                let request = client
+                    .post("/fileo/memberships/upload?force=true")
+                    .header(ContentType::CSV)
+                    .cookie(cookie)
+                    .body(file_content);
+                let response = request.dispatch().await;
 
-                let result = download_memberships(config_state, pool_state, credentials).await;
-                assert_eq!(Status::InternalServerError, result.unwrap_err());
+                assert_eq!(Status::Ok, response.status());
             }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn forbidden_when_forced_by_non_admin() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, cookie) = build_client(pool, vec!["admin".to_owned()]).await;
+                let file_content = ISO_8859_1
+                    .encode(&get_membership_as_csv(), EncoderTrap::Strict)
+                    .unwrap();
+
+                let request = client
+                    .post("/fileo/memberships/upload?force=true")
+                    .header(ContentType::CSV)
+                    .cookie(cookie)
+                    .body(file_content);
+                let response = request.dispatch().await;
 
+                assert_eq!(Status::Forbidden, response.status());
+                let mut connection = client
+                    .rocket()
+                    .state::<Pool<ConnectionManager<DatabaseConnection>>>()
+                    .unwrap()
+                    .get()
+                    .unwrap();
+                assert!(
+                    dao::membership::retrieve_memberships(&mut connection)
+                        .unwrap()
+                        .is_empty()
+                );
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod check_health {
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::api::fileo_controller::tests::create_memberships_provider_test_config;
+        use crate::web::api::fileo_controller::{check_health, last_health_check};
+        use crate::web::credentials_storage::CredentialsStorage;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::fileo_health::{FileoFlowStep, FileoHealthReport};
+        use rocket::http::{Cookie, Status};
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        #[test]
+        fn broken_when_login_page_changed() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mock_server = MockServer::start().await;
+                Mock::given(method("POST"))
+                    .and(path("/page.php"))
+                    .and(body_string_contains("Action=connect_user"))
+                    .respond_with(
+                        ResponseTemplate::new(200).set_body_string("<h1>Nouvel accueil</h1>"),
+                    )
+                    .mount(&mock_server)
+                    .await;
+
+                let config = create_memberships_provider_test_config(&mock_server.uri());
+                let credentials =
+                    FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
+                let credentials_storage = CredentialsStorage::default();
+                let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
+                credentials_storage.store(uuid.clone(), credentials);
+
+                let rocket = rocket::build()
+                    .manage(config)
+                    .manage(credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![check_health, last_health_check]);
+                let client = Client::tracked(rocket).await.unwrap();
+                let cookie = Cookie::new(AUTHENTICATION_COOKIE, uuid);
+
+                let response = client
+                    .get("/fileo/health/last")
+                    .cookie(cookie.clone())
+                    .dispatch()
+                    .await;
+                assert_eq!(Status::NotFound, response.status());
+
+                let response = client.get("/fileo/health").cookie(cookie).dispatch().await;
+                assert_eq!(Status::BadGateway, response.status());
+                let report = response.into_json::<FileoHealthReport>().await.unwrap();
+                assert_eq!(&Some(FileoFlowStep::Login), report.failed_step());
+                assert_eq!(&Some("Nouvel accueil".to_owned()), report.snippet());
+
+                let mut connection = client
+                    .rocket()
+                    .state::<Pool<ConnectionManager<DatabaseConnection>>>()
+                    .unwrap()
+                    .get()
+                    .unwrap();
+                assert_eq!(
+                    Some(report),
+                    dao::fileo_health_check::retrieve_last(&mut connection).unwrap()
+                );
+            }
             with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
     }
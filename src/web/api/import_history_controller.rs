@@ -0,0 +1,130 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::fileo::credentials::FileoCredentials;
+use crate::tools::log_error_and_return;
+use diesel::r2d2::{ConnectionManager, Pool};
+use dto::import_history::{ImportHistoryEntry, ImportedElement};
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use std::str::FromStr;
+
+/// List the most recent refreshes of memberships and UDA instances, the most recent first.
+/// They can be restricted to a single element (`Memberships` or `UdaInstances`).
+/// Return a 400 status if the element is unknown.
+#[get("/imports?<element>")]
+pub async fn list_imports(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    element: Option<&str>,
+    _credentials: FileoCredentials,
+) -> Result<Json<Vec<ImportHistoryEntry>>, Status> {
+    let element = element
+        .filter(|element| !element.is_empty())
+        .map(ImportedElement::from_str)
+        .transpose()
+        .map_err(|error| {
+            debug!("Invalid imported element [error: {error}]");
+            Status::BadRequest
+        })?;
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let entries = dao::import_history::retrieve(&mut connection, element)
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+
+    Ok(Json(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    mod list_imports {
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::api::import_history_controller::list_imports;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use chrono::NaiveDate;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::import_history::{
+            ImportCounts, ImportHistoryEntry, ImportOutcome, ImportSource, ImportedElement,
+        };
+        use rocket::http::Status;
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use uuid::Uuid;
+
+        fn create_entry(element: ImportedElement) -> ImportHistoryEntry {
+            let date = NaiveDate::from_ymd_opt(2025, 4, 30)
+                .unwrap()
+                .and_hms_opt(10, 30, 0)
+                .unwrap();
+            ImportHistoryEntry::new(
+                element,
+                ImportSource::Scheduler,
+                None,
+                date,
+                date,
+                ImportCounts::new(0, 3, 0, 0),
+                ImportOutcome::Success,
+                None,
+            )
+        }
+
+        async fn create_client(
+            pool: Pool<ConnectionManager<DatabaseConnection>>,
+        ) -> (Client, String) {
+            let credentials_storage = CredentialsStorage::<FileoCredentials>::default();
+            let uuid = Uuid::new_v4().to_string();
+            credentials_storage.store(uuid.clone(), FileoCredentials::default());
+
+            let rocket = rocket::build()
+                .manage(credentials_storage)
+                .manage(pool)
+                .mount("/", routes![list_imports]);
+
+            (Client::tracked(rocket).await.unwrap(), uuid)
+        }
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mut connection = pool.get().unwrap();
+                let expected_entry = create_entry(ImportedElement::Memberships);
+                dao::import_history::insert(&mut connection, &expected_entry).unwrap();
+                dao::import_history::insert(
+                    &mut connection,
+                    &create_entry(ImportedElement::UdaInstances),
+                )
+                .unwrap();
+
+                let (client, uuid) = create_client(pool).await;
+                let response = client
+                    .get("/imports?element=Memberships")
+                    .cookie((AUTHENTICATION_COOKIE, uuid))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::Ok, response.status());
+                let entries: Vec<ImportHistoryEntry> = response.into_json().await.unwrap();
+                assert_eq!(vec![expected_entry], entries);
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn bad_request_when_unknown_element() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (client, uuid) = create_client(pool).await;
+                let response = client
+                    .get("/imports?element=Clubs")
+                    .cookie((AUTHENTICATION_COOKIE, uuid))
+                    .dispatch()
+                    .await;
+
+                assert_eq!(Status::BadRequest, response.status());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+}
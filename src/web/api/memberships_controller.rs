@@ -1,16 +1,22 @@
+use crate::audit::record_action;
+use crate::database::DatabaseConnection;
+use crate::database::dao;
 use crate::fileo::credentials::FileoCredentials;
 use crate::membership;
 use crate::membership::check::check_members;
 use crate::tools::email::send_email;
 use crate::tools::{log_error_and_return, log_message_and_return};
 use crate::uda::credentials::UdaCredentials;
-use diesel::SqliteConnection;
+use chrono::NaiveDate;
 use diesel::r2d2::{ConnectionManager, Pool};
-use dto::checked_member::CheckedMember;
+use dto::audit_log::AuditAction;
+use dto::checked_member::{CheckResult, CheckedMember};
 use dto::csv_member::CsvMember;
 use dto::email::Email;
 use dto::member_to_check::MemberToCheck;
-use dto::member_to_look_up::MemberToLookUp;
+use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
+use dto::membership::Membership;
+use dto::membership_status::EventPeriod;
 use dto::uda_member::UdaMember;
 use rocket::State;
 use rocket::http::Status;
@@ -20,52 +26,144 @@ use rocket::serde::json::{Json, json};
 /// Return the result as JSON-encoded string,
 /// within which each member having a valid membership has its last occurrence associated,
 /// while each member having no valid membership has no element associated.
+/// When a membership type is required, matches with another type are only partial.
+/// When the event dates are provided (YYYY-MM-DD), memberships have to cover the whole event.
 #[post(
-    "/members/csv/check",
+    "/members/csv/check?<required_membership_type>&<event_start_date>&<event_end_date>",
     format = "application/json",
     data = "<members_to_check>"
 )]
 pub async fn check_csv_members(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     members_to_check: Json<Vec<CsvMember>>,
-    _credentials: FileoCredentials,
+    required_membership_type: Option<&str>,
+    event_start_date: Option<&str>,
+    event_end_date: Option<&str>,
+    credentials: FileoCredentials,
 ) -> Result<String, Status> {
-    let result = check(pool.inner(), members_to_check.into_inner())?;
+    let result = check(
+        pool.inner(),
+        members_to_check.into_inner(),
+        required_membership_type,
+        parse_event_period(event_start_date, event_end_date)?,
+    )?;
+    record_check(pool, &credentials, AuditAction::CsvCheck, &result);
 
     Ok(json!(result).to_string())
 }
 
 #[post(
-    "/members/uda/check",
+    "/members/uda/check?<required_membership_type>&<event_start_date>&<event_end_date>",
     format = "application/json",
     data = "<members_to_check>"
 )]
 pub async fn check_uda_members(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     members_to_check: Json<Vec<UdaMember>>,
-    _fileo_credentials: FileoCredentials,
+    required_membership_type: Option<&str>,
+    event_start_date: Option<&str>,
+    event_end_date: Option<&str>,
+    fileo_credentials: FileoCredentials,
     _uda_credentials: UdaCredentials,
 ) -> Result<String, Status> {
-    let result = check(pool.inner(), members_to_check.into_inner())?;
+    let result = check(
+        pool.inner(),
+        members_to_check.into_inner(),
+        required_membership_type,
+        parse_event_period(event_start_date, event_end_date)?,
+    )?;
+    record_check(pool, &fileo_credentials, AuditAction::UdaCheck, &result);
 
     Ok(json!(result).to_string())
 }
 
 fn check<T: MemberToCheck>(
-    pool: &Pool<ConnectionManager<SqliteConnection>>,
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
     members_to_check: Vec<T>,
+    required_membership_type: Option<&str>,
+    event_period: Option<EventPeriod>,
 ) -> Result<Vec<CheckedMember<T>>, Status> {
-    let checked_members = check_members(pool, members_to_check)
-        .map_err(log_error_and_return(Status::InternalServerError))?;
+    // An empty field in the form means no type is required
+    let required_membership_type =
+        required_membership_type.filter(|membership_type| !membership_type.trim().is_empty());
+    let checked_members = check_members(
+        pool,
+        members_to_check,
+        required_membership_type,
+        event_period,
+    )
+    .map_err(log_error_and_return(Status::InternalServerError))?;
 
     Ok(checked_members)
 }
 
+/// Empty dates mean there is no event to check the memberships for.
+/// Both dates are required otherwise, and the event can't end before it starts.
+fn parse_event_period(
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Option<EventPeriod>, Status> {
+    let start_date = start_date.map(str::trim).filter(|date| !date.is_empty());
+    let end_date = end_date.map(str::trim).filter(|date| !date.is_empty());
+    let (start_date, end_date) = match (start_date, end_date) {
+        (None, None) => return Ok(None),
+        (Some(start_date), Some(end_date)) => (start_date, end_date),
+        _ => {
+            debug!("Incomplete event period [start: {start_date:?}, end: {end_date:?}]");
+            return Err(Status::BadRequest);
+        }
+    };
+
+    let start_date = NaiveDate::parse_from_str(start_date, "%Y-%m-%d").map_err(
+        log_message_and_return("Invalid event start date", Status::BadRequest),
+    )?;
+    let end_date = NaiveDate::parse_from_str(end_date, "%Y-%m-%d").map_err(
+        log_message_and_return("Invalid event end date", Status::BadRequest),
+    )?;
+    if end_date < start_date {
+        debug!("Event ending before it starts [start: {start_date}, end: {end_date}]");
+        return Err(Status::BadRequest);
+    }
+
+    Ok(Some(EventPeriod::new(start_date, end_date)))
+}
+
+/// Record the check, along with the memberships that have been found.
+/// All the candidates of ambiguous results have been shown to the checker, so they are recorded too.
+fn record_check<T: MemberToCheck>(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    credentials: &FileoCredentials,
+    action: AuditAction,
+    checked_members: &[CheckedMember<T>],
+) {
+    let found_memberships = checked_members
+        .iter()
+        .flat_map(|checked_member| match checked_member.membership() {
+            CheckResult::Match(membership)
+            | CheckResult::PartialMatch(membership)
+            | CheckResult::FuzzyMatch(membership, _) => vec![membership],
+            CheckResult::Ambiguous(candidates) => candidates.iter().collect(),
+            CheckResult::NoMatch => vec![],
+        })
+        .map(|membership| membership.membership_number().clone())
+        .collect();
+    record_action(
+        pool,
+        credentials.login(),
+        action,
+        checked_members.len(),
+        found_memberships,
+    );
+}
+
 /// Email all recipients specified as argument.
+/// Only their count is recorded in the audit log, as most of them aren't known members
+/// and their email addresses mustn't be kept there.
 #[post("/members/notify", format = "application/json", data = "<email>")]
 pub async fn notify_members(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     email: Json<Email>,
-    _credentials: FileoCredentials,
+    credentials: FileoCredentials,
 ) -> Result<(), String> {
     let recipients = email
         .recipients()
@@ -78,19 +176,30 @@ pub async fn notify_members(
             "Couldn't send email",
             "Email has not been sent.",
         ))?;
+    record_action(
+        pool,
+        credentials.login(),
+        AuditAction::Notification,
+        email.recipients().len(),
+        Vec::new(),
+    );
 
     Ok(())
 }
 
+/// Look memberships up.
+/// In exact mode, matching memberships are returned.
+/// Otherwise, ranked candidates are returned, along with their score.
+/// Archived memberships can only be looked up in exact mode: a date in other modes is a bad request.
 #[post(
     "/members/lookup",
     format = "application/json",
     data = "<member_to_look_up>"
 )]
 pub async fn look_member_up(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     member_to_look_up: Json<MemberToLookUp>,
-    _credentials: FileoCredentials,
+    credentials: FileoCredentials,
 ) -> Result<String, Status> {
     let member_to_look_up = member_to_look_up.into_inner();
 
@@ -105,10 +214,58 @@ pub async fn look_member_up(
     let mut connection = pool
         .get()
         .map_err(log_error_and_return(Status::InternalServerError))?;
-    let memberships = membership::look_up::look_member_up(&mut connection, &member_to_look_up)
+    if *member_to_look_up.mode() == LookUpMode::Exact {
+        let memberships = membership::look_up::look_member_up(&mut connection, &member_to_look_up)
+            .map_err(log_error_and_return(Status::InternalServerError))?;
+        let found_memberships = memberships.iter().map(Membership::membership_number);
+        record_look_up(pool, &credentials, found_memberships);
+        return Ok(json!(memberships).to_string());
+    }
+
+    if member_to_look_up.valid_on().is_some() {
+        debug!("Can't look archived memberships up approximately [member: {member_to_look_up:?}]");
+        return Err(Status::BadRequest);
+    }
+    let candidates = membership::look_up::look_candidates_up(&mut connection, &member_to_look_up)
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let found_memberships = candidates
+        .iter()
+        .map(|candidate| candidate.membership().membership_number());
+    record_look_up(pool, &credentials, found_memberships);
+
+    Ok(json!(candidates).to_string())
+}
+
+fn record_look_up<'a>(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    credentials: &FileoCredentials,
+    found_memberships: impl Iterator<Item = &'a String>,
+) {
+    let found_memberships = found_memberships.cloned().collect::<Vec<_>>();
+    record_action(
+        pool,
+        credentials.login(),
+        AuditAction::LookUp,
+        found_memberships.len(),
+        found_memberships,
+    );
+}
+
+/// Retrieve the changes brought by the last memberships import.
+/// Return a 404 status if memberships have never been imported.
+#[get("/memberships/diff")]
+pub async fn last_import_diff(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    _credentials: FileoCredentials,
+) -> Result<String, Status> {
+    let mut connection = pool
+        .get()
         .map_err(log_error_and_return(Status::InternalServerError))?;
+    let diff = dao::membership_import::retrieve_last_diff(&mut connection)
+        .map_err(log_error_and_return(Status::InternalServerError))?
+        .ok_or(Status::NotFound)?;
 
-    Ok(json!(memberships).to_string())
+    Ok(json!(diff).to_string())
 }
 
 #[cfg(test)]
@@ -116,21 +273,19 @@ mod tests {
     use crate::fileo::credentials::FileoCredentials;
     use crate::uda::credentials::UdaCredentials;
     use crate::web::credentials_storage::CredentialsStorage;
-    use std::sync::Mutex;
 
-    fn initialize_fileo_login() -> (String, Mutex<CredentialsStorage<FileoCredentials>>) {
+    fn initialize_fileo_login() -> (String, CredentialsStorage<FileoCredentials>) {
         let credentials =
             FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
 
         let uuid = "e9af5e0f-c441-4bcd-bf22-31cc5b1f2f9e".to_owned();
-        let mut storage = CredentialsStorage::<FileoCredentials>::default();
+        let storage = CredentialsStorage::<FileoCredentials>::default();
         storage.store(uuid.clone(), credentials);
 
-        let storage_mutex = Mutex::new(storage);
-        (uuid, storage_mutex)
+        (uuid, storage)
     }
 
-    fn initialize_uda_login() -> (String, Mutex<CredentialsStorage<UdaCredentials>>) {
+    fn initialize_uda_login() -> (String, CredentialsStorage<UdaCredentials>) {
         let credentials: UdaCredentials = uda_connector::credentials::UdaCredentials::new(
             "https://test.reg.unicycling-software.com".to_owned(),
             "login@test.com".to_owned(),
@@ -139,23 +294,25 @@ mod tests {
         .into();
 
         let uuid = "e9af5e0f-c441-4bcd-bf22-31cc5b1f2f9e".to_owned();
-        let mut storage = CredentialsStorage::<UdaCredentials>::default();
+        let storage = CredentialsStorage::<UdaCredentials>::default();
         storage.store(uuid.clone(), credentials);
 
-        let storage_mutex = Mutex::new(storage);
-        (uuid, storage_mutex)
+        (uuid, storage)
     }
 
     mod check_members {
+        use crate::database::DatabaseConnection;
         use crate::database::with_temp_database;
         use crate::web::api::memberships_controller::check_uda_members;
         use crate::web::api::memberships_controller::tests::{
             initialize_fileo_login, initialize_uda_login,
         };
-        use diesel::SqliteConnection;
+        use chrono::NaiveDate;
         use diesel::r2d2::{ConnectionManager, Pool};
         use dto::checked_member::{CheckResult, CheckedMember};
+        use dto::match_explanation::{ComparedField, MatchExplanation, MatchingRule};
         use dto::membership::tests::get_expected_membership;
+        use dto::membership_status::{EventPeriod, MemberStatus};
         use dto::uda_member::UdaMember;
         use rocket::http::hyper::header::CONTENT_TYPE;
         use rocket::http::{ContentType, Header, Status};
@@ -163,9 +320,19 @@ mod tests {
         use rocket::serde::json::json;
         use rocket::tokio::runtime::Runtime;
 
+        /// The members checked here only differ from the membership by their email address.
+        fn agreeing_fields() -> Vec<ComparedField> {
+            vec![
+                ComparedField::MembershipNumber,
+                ComparedField::LastName,
+                ComparedField::FirstName,
+                ComparedField::Identity,
+            ]
+        }
+
         #[test]
         fn success() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let member_1 = UdaMember::new(
                     1,
                     Some("123456".to_owned()),
@@ -186,8 +353,8 @@ mod tests {
                 );
                 let members = vec![member_1.clone(), member_2.clone()];
 
-                let (fileo_uuid, fileo_credentials_storage_mutex) = initialize_fileo_login();
-                let (uda_uuid, uda_credentials_storage_mutex) = initialize_uda_login();
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+                let (uda_uuid, uda_credentials_storage) = initialize_uda_login();
 
                 let mut connection = pool.get().unwrap();
                 crate::database::dao::membership::replace_memberships(
@@ -197,8 +364,8 @@ mod tests {
                 .unwrap();
 
                 let rocket = rocket::build()
-                    .manage(fileo_credentials_storage_mutex)
-                    .manage(uda_credentials_storage_mutex)
+                    .manage(fileo_credentials_storage)
+                    .manage(uda_credentials_storage)
                     .manage(pool)
                     .mount("/", routes![check_uda_members]);
 
@@ -223,8 +390,28 @@ mod tests {
                     response.into_json().await.unwrap();
                 assert_eq!(
                     vec![
-                        CheckedMember::new(member_1, CheckResult::Match(get_expected_membership())),
-                        CheckedMember::new(member_2, CheckResult::NoMatch),
+                        CheckedMember::new(
+                            member_1,
+                            CheckResult::Match(get_expected_membership()),
+                            None,
+                            vec![],
+                            Some(MatchExplanation::new(
+                                MatchingRule::MembershipNumberAndNames,
+                                agreeing_fields(),
+                                vec![ComparedField::EmailAddress]
+                            ))
+                        ),
+                        CheckedMember::new(
+                            member_2,
+                            CheckResult::NoMatch,
+                            None,
+                            vec![],
+                            Some(MatchExplanation::new(
+                                MatchingRule::UnknownMembershipNumber,
+                                vec![],
+                                vec![]
+                            ))
+                        ),
                     ],
                     checked_members
                 )
@@ -232,9 +419,228 @@ mod tests {
 
             with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
+
+        #[test]
+        fn partial_match_when_wrong_membership_type() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let member = UdaMember::new(
+                    1,
+                    Some("123456".to_owned()),
+                    "Jon".to_owned(),
+                    "Doe".to_owned(),
+                    "jon.doe@email.com".to_owned(),
+                    Some("Le club de test".to_owned()),
+                    true,
+                );
+
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+                let (uda_uuid, uda_credentials_storage) = initialize_uda_login();
+
+                let mut connection = pool.get().unwrap();
+                crate::database::dao::membership::replace_memberships(
+                    &mut connection,
+                    &[get_expected_membership()],
+                )
+                .unwrap();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(uda_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![check_uda_members]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let request = client
+                    .post("/members/uda/check?required_membership_type=Loisir")
+                    .cookie((
+                        crate::fileo::authentication::AUTHENTICATION_COOKIE,
+                        fileo_uuid,
+                    ))
+                    .cookie((crate::uda::authentication::AUTHENTICATION_COOKIE, uda_uuid))
+                    .body(json!(vec![member.clone()]).to_string().as_bytes())
+                    .header(Header::new(
+                        CONTENT_TYPE.to_string(),
+                        ContentType::JSON.to_string(),
+                    ));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+
+                let checked_members: Vec<CheckedMember<UdaMember>> =
+                    response.into_json().await.unwrap();
+                assert_eq!(
+                    vec![CheckedMember::new(
+                        member,
+                        CheckResult::PartialMatch(get_expected_membership()),
+                        None,
+                        vec![],
+                        Some(MatchExplanation::new(
+                            MatchingRule::MembershipNumberAndNames,
+                            agreeing_fields(),
+                            vec![ComparedField::EmailAddress, ComparedField::MembershipType]
+                        ))
+                    )],
+                    checked_members
+                )
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn not_covering_event_when_membership_expires_during_event() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let member = UdaMember::new(
+                    1,
+                    Some("123456".to_owned()),
+                    "Jon".to_owned(),
+                    "Doe".to_owned(),
+                    "jon.doe@email.com".to_owned(),
+                    Some("Le club de test".to_owned()),
+                    true,
+                );
+
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+                let (uda_uuid, uda_credentials_storage) = initialize_uda_login();
+
+                let mut connection = pool.get().unwrap();
+                crate::database::dao::membership::replace_memberships(
+                    &mut connection,
+                    &[get_expected_membership()],
+                )
+                .unwrap();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(uda_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![check_uda_members]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let request = client
+                    .post(
+                        "/members/uda/check?event_start_date=2025-09-29&event_end_date=2025-10-01",
+                    )
+                    .cookie((
+                        crate::fileo::authentication::AUTHENTICATION_COOKIE,
+                        fileo_uuid,
+                    ))
+                    .cookie((crate::uda::authentication::AUTHENTICATION_COOKIE, uda_uuid))
+                    .body(json!(vec![member.clone()]).to_string().as_bytes())
+                    .header(Header::new(
+                        CONTENT_TYPE.to_string(),
+                        ContentType::JSON.to_string(),
+                    ));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+
+                let checked_members: Vec<CheckedMember<UdaMember>> =
+                    response.into_json().await.unwrap();
+                assert_eq!(
+                    vec![CheckedMember::new(
+                        member,
+                        CheckResult::Match(get_expected_membership()),
+                        Some(EventPeriod::new(
+                            NaiveDate::from_ymd_opt(2025, 9, 29).unwrap(),
+                            NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+                        )),
+                        vec![],
+                        Some(MatchExplanation::new(
+                            MatchingRule::MembershipNumberAndNames,
+                            agreeing_fields(),
+                            vec![ComparedField::EmailAddress]
+                        ))
+                    )],
+                    checked_members
+                );
+                assert_eq!(
+                    MemberStatus::NotCoveringEvent,
+                    checked_members[0].compute_member_status()
+                );
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn fail_when_event_ends_before_it_starts() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+                let (uda_uuid, uda_credentials_storage) = initialize_uda_login();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(uda_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![check_uda_members]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let request = client
+                    .post(
+                        "/members/uda/check?event_start_date=2025-10-01&event_end_date=2025-09-29",
+                    )
+                    .cookie((
+                        crate::fileo::authentication::AUTHENTICATION_COOKIE,
+                        fileo_uuid,
+                    ))
+                    .cookie((crate::uda::authentication::AUTHENTICATION_COOKIE, uda_uuid))
+                    .body(json!(Vec::<UdaMember>::new()).to_string().as_bytes())
+                    .header(Header::new(
+                        CONTENT_TYPE.to_string(),
+                        ContentType::JSON.to_string(),
+                    ));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::BadRequest, response.status());
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod parse_event_period {
+        use crate::web::api::memberships_controller::parse_event_period;
+        use chrono::NaiveDate;
+        use dto::membership_status::EventPeriod;
+        use rocket::http::Status;
+
+        #[test]
+        fn success() {
+            assert_eq!(
+                Ok(Some(EventPeriod::new(
+                    NaiveDate::from_ymd_opt(2025, 6, 7).unwrap(),
+                    NaiveDate::from_ymd_opt(2025, 6, 7).unwrap(),
+                ))),
+                parse_event_period(Some("2025-06-07"), Some(" 2025-06-07 "))
+            );
+        }
+
+        #[test]
+        fn none_when_empty_dates() {
+            assert_eq!(Ok(None), parse_event_period(None, None));
+            assert_eq!(Ok(None), parse_event_period(Some(""), Some(" ")));
+        }
+
+        #[test]
+        fn fail_when_invalid_dates() {
+            assert_eq!(
+                Err(Status::BadRequest),
+                parse_event_period(Some("2025-06-07"), None)
+            );
+            assert_eq!(
+                Err(Status::BadRequest),
+                parse_event_period(Some("07/06/2025"), Some("2025-06-08"))
+            );
+            assert_eq!(
+                Err(Status::BadRequest),
+                parse_event_period(Some("2025-06-08"), Some("2025-06-07"))
+            );
+        }
     }
 
     mod look_member_up {
+        use crate::database::DatabaseConnection;
         use crate::database::{dao, with_temp_database};
         use crate::fileo::authentication::AUTHENTICATION_COOKIE;
         use crate::membership::tests::{
@@ -242,10 +648,12 @@ mod tests {
         };
         use crate::web::api::memberships_controller::look_member_up;
         use crate::web::api::memberships_controller::tests::initialize_fileo_login;
-        use diesel::SqliteConnection;
+        use chrono::NaiveDate;
         use diesel::r2d2::{ConnectionManager, Pool};
-        use dto::member_to_look_up::MemberToLookUp;
+        use dto::audit_log::{AuditAction, AuditLogFilter};
+        use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
         use dto::membership::Membership;
+        use dto::membership_candidate::MembershipCandidate;
         use rocket::http::hyper::header::CONTENT_TYPE;
         use rocket::http::{ContentType, Header, Status};
         use rocket::local::asynchronous::Client;
@@ -254,8 +662,8 @@ mod tests {
 
         #[test]
         fn success() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
-                let (fileo_uuid, fileo_credentials_storage_mutex) = initialize_fileo_login();
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
 
                 let mut connection = pool.get().unwrap();
                 dao::membership::replace_memberships(
@@ -270,14 +678,19 @@ mod tests {
                 .unwrap();
 
                 let rocket = rocket::build()
-                    .manage(fileo_credentials_storage_mutex)
+                    .manage(fileo_credentials_storage)
                     .manage(pool)
                     .mount("/", routes![look_member_up]);
 
                 let client = Client::tracked(rocket).await.unwrap();
 
-                let member_to_look_up =
-                    MemberToLookUp::new(Some(jon_doe().membership_number().to_owned()), None, None);
+                let member_to_look_up = MemberToLookUp::new(
+                    Some(jon_doe().membership_number().to_owned()),
+                    None,
+                    None,
+                    None,
+                    LookUpMode::Exact,
+                );
                 let request = client
                     .post("/members/lookup")
                     .cookie((AUTHENTICATION_COOKIE, fileo_uuid))
@@ -294,15 +707,65 @@ mod tests {
                 assert_eq!(
                     vec![jon_doe_previous_membership(), jon_doe()],
                     matching_memberships
-                )
+                );
+
+                let entries =
+                    dao::audit_log::retrieve(&mut connection, &AuditLogFilter::default()).unwrap();
+                assert_eq!(1, entries.len());
+                assert_eq!("test_login", entries[0].actor());
+                assert_eq!(&AuditAction::LookUp, entries[0].action());
+                assert_eq!(&2, entries[0].count());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn fuzzy_success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+
+                let mut connection = pool.get().unwrap();
+                dao::membership::replace_memberships(&mut connection, &[jon_doe(), jonette_snow()])
+                    .unwrap();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![look_member_up]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+
+                let member_to_look_up = MemberToLookUp::new(
+                    None,
+                    None,
+                    Some("Jonete".to_owned()),
+                    None,
+                    LookUpMode::Fuzzy,
+                );
+                let request = client
+                    .post("/members/lookup")
+                    .cookie((AUTHENTICATION_COOKIE, fileo_uuid))
+                    .body(json!(member_to_look_up).to_string().as_bytes())
+                    .header(Header::new(
+                        CONTENT_TYPE.to_string(),
+                        ContentType::JSON.to_string(),
+                    ));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+
+                let candidates: Vec<MembershipCandidate> = response.into_json().await.unwrap();
+                assert_eq!(1, candidates.len());
+                assert_eq!(&jonette_snow(), candidates[0].membership());
+                assert_eq!(1.0 - 1.0 / 7.0, *candidates[0].score());
             }
             with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
 
         #[test]
         fn bad_request() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
-                let (fileo_uuid, fileo_credentials_storage_mutex) = initialize_fileo_login();
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
                 let mut connection = pool.get().unwrap();
                 dao::membership::replace_memberships(
                     &mut connection,
@@ -316,13 +779,14 @@ mod tests {
                 .unwrap();
 
                 let rocket = rocket::build()
-                    .manage(fileo_credentials_storage_mutex)
+                    .manage(fileo_credentials_storage)
                     .manage(pool)
                     .mount("/", routes![look_member_up]);
 
                 let client = Client::tracked(rocket).await.unwrap();
 
-                let member_to_look_up = MemberToLookUp::new(None, None, None);
+                let member_to_look_up =
+                    MemberToLookUp::new(None, None, None, None, LookUpMode::Exact);
                 let request = client
                     .post("/members/lookup")
                     .cookie((AUTHENTICATION_COOKIE, fileo_uuid))
@@ -337,5 +801,102 @@ mod tests {
             }
             with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
+
+        #[test]
+        fn bad_request_when_fuzzy_on_date() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![look_member_up]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+
+                let member_to_look_up = MemberToLookUp::new(
+                    None,
+                    Some(jon_doe().name().to_owned()),
+                    None,
+                    NaiveDate::from_ymd_opt(2024, 3, 1),
+                    LookUpMode::Fuzzy,
+                );
+                let request = client
+                    .post("/members/lookup")
+                    .cookie((AUTHENTICATION_COOKIE, fileo_uuid))
+                    .body(json!(member_to_look_up).to_string().as_bytes())
+                    .header(Header::new(
+                        CONTENT_TYPE.to_string(),
+                        ContentType::JSON.to_string(),
+                    ));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::BadRequest, response.status());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod last_import_diff {
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::membership::tests::jon_doe;
+        use crate::web::api::memberships_controller::last_import_diff;
+        use crate::web::api::memberships_controller::tests::initialize_fileo_login;
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::membership_diff::MembershipsDiff;
+        use rocket::http::Status;
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+
+                let mut connection = pool.get().unwrap();
+                let expected_diff =
+                    dao::membership::replace_memberships(&mut connection, &[jon_doe()]).unwrap();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![last_import_diff]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let request = client
+                    .get("/memberships/diff")
+                    .cookie((AUTHENTICATION_COOKIE, fileo_uuid));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+
+                let diff: MembershipsDiff = response.into_json().await.unwrap();
+                assert_eq!(expected_diff, diff);
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn not_found_when_never_imported() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let (fileo_uuid, fileo_credentials_storage) = initialize_fileo_login();
+
+                let rocket = rocket::build()
+                    .manage(fileo_credentials_storage)
+                    .manage(pool)
+                    .mount("/", routes![last_import_diff]);
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let request = client
+                    .get("/memberships/diff")
+                    .cookie((AUTHENTICATION_COOKIE, fileo_uuid));
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::NotFound, response.status());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
     }
 }
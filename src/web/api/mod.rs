@@ -1,4 +1,7 @@
+mod audit_log_controller;
+mod database_controller;
 mod fileo_controller;
+mod import_history_controller;
 mod memberships_controller;
 pub mod server;
 mod uda_controller;
@@ -1,19 +1,35 @@
+use crate::database::DatabaseConnection;
+use crate::database::backup::{retrieve_scheduled_backup_config, scheduled_backup_fairing};
+use crate::fileo;
 use crate::fileo::credentials::FileoCredentials;
+use crate::fileo::export_filters::retrieve_fileo_export_filters;
+use crate::fileo::scheduled_refresh::{
+    retrieve_scheduled_refresh_config, scheduled_refresh_fairing,
+};
 use crate::membership::config::MembershipsProviderConfig;
+use crate::membership::retention::{personal_data_purge_fairing, retrieve_retention_policy};
+use crate::uda;
 use crate::uda::credentials::UdaCredentials;
-use crate::web::api::{fileo_controller, memberships_controller, uda_controller};
-use crate::web::credentials_storage::CredentialsStorage;
+use crate::web::admin::retrieve_admin_logins;
+use crate::web::api::{
+    audit_log_controller, database_controller, fileo_controller, import_history_controller,
+    memberships_controller, uda_controller,
+};
+use crate::web::credentials_storage::{CredentialsStorage, retrieve_session_config};
 use crate::web::server::Server;
+use diesel::r2d2::{ConnectionManager, Pool};
 use dto::uda_instance::InstancesList;
 use regex::Regex;
 use rocket::{Build, Rocket};
 use std::sync::Mutex;
 
-pub struct ApiServer {}
+pub struct ApiServer {
+    pool: Pool<ConnectionManager<DatabaseConnection>>,
+}
 
 impl ApiServer {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(pool: Pool<ConnectionManager<DatabaseConnection>>) -> Self {
+        Self { pool }
     }
 }
 
@@ -24,9 +40,23 @@ impl Server for ApiServer {
         rocket_build
             .manage(members_provider_config)
             .manage(build_uda_configuration())
-            .manage(Mutex::new(CredentialsStorage::<FileoCredentials>::default()))
-            .manage(Mutex::new(CredentialsStorage::<UdaCredentials>::default()))
+            .manage(CredentialsStorage::<FileoCredentials>::new(
+                self.pool.clone(),
+                fileo::authentication::AUTHENTICATION_COOKIE,
+                retrieve_session_config(),
+            ))
+            .manage(CredentialsStorage::<UdaCredentials>::new(
+                self.pool.clone(),
+                uda::authentication::AUTHENTICATION_COOKIE,
+                retrieve_session_config(),
+            ))
             .manage(Mutex::new(InstancesList::default()))
+            .manage(retrieve_admin_logins())
+            .attach(scheduled_refresh_fairing(
+                retrieve_scheduled_refresh_config(),
+            ))
+            .attach(scheduled_backup_fairing(retrieve_scheduled_backup_config()))
+            .attach(personal_data_purge_fairing(retrieve_retention_policy()))
             .mount(
                 "/api/",
                 routes![
@@ -34,19 +64,30 @@ impl Server for ApiServer {
                     memberships_controller::check_uda_members,
                     memberships_controller::notify_members,
                     memberships_controller::look_member_up,
+                    memberships_controller::last_import_diff,
                     fileo_controller::login,
                     fileo_controller::download_memberships,
+                    fileo_controller::upload_memberships,
+                    fileo_controller::check_health,
+                    fileo_controller::last_health_check,
                     uda_controller::login,
                     uda_controller::retrieve_members_to_check,
                     uda_controller::confirm_members,
                     uda_controller::list_instances,
+                    audit_log_controller::list_audit_log,
+                    import_history_controller::list_imports,
+                    database_controller::download_backup,
                 ],
             )
     }
 }
 
 fn build_members_provider_config() -> MembershipsProviderConfig {
-    MembershipsProviderConfig::new(get_fileo_host(), get_download_link_regex())
+    MembershipsProviderConfig::new(
+        get_fileo_host(),
+        get_download_link_regex(),
+        retrieve_fileo_export_filters(),
+    )
 }
 
 #[cfg(not(feature = "demo"))]
@@ -1,13 +1,16 @@
+use crate::audit::record_action;
+use crate::database::DatabaseConnection;
 use crate::database::dao::last_update::UpdatableElement::UdaInstances;
 use crate::database::dao::last_update::get_last_update;
-use crate::tools::web::build_client;
+use crate::tools::web::{build_client, with_retries};
 use crate::tools::{log_error, log_error_and_return};
 use crate::uda::authentication::AUTHENTICATION_COOKIE;
 use crate::uda::credentials::UdaCredentials;
 use crate::uda::instances::retrieve_uda_instances;
+use crate::uda::retry::is_transient_failure;
 use crate::web::credentials_storage::CredentialsStorage;
-use diesel::SqliteConnection;
 use diesel::r2d2::ConnectionManager;
+use dto::audit_log::AuditAction;
 use dto::uda_instance::InstancesList;
 use r2d2::Pool;
 use reqwest::Client;
@@ -16,7 +19,6 @@ use rocket::form::validate::Contains;
 use rocket::http::{Cookie, CookieJar, Status};
 use rocket::serde::json::{Json, Value, json};
 use rocket::time::Duration;
-use std::sync::Mutex;
 use uda_connector::configuration::Configuration;
 use uda_connector::confirm_member::confirm_member;
 use uda_connector::error::UdaError;
@@ -31,22 +33,19 @@ use uuid::Uuid;
 /// The UUID is returned to the caller through a private cookie, so that it is their new access token.
 #[post("/uda/login", format = "application/json", data = "<credentials>")]
 pub async fn login(
-    credentials_storage: &State<Mutex<CredentialsStorage<UdaCredentials>>>,
+    credentials_storage: &State<CredentialsStorage<UdaCredentials>>,
     cookie_jar: &CookieJar<'_>,
     credentials: Json<uda_connector::credentials::UdaCredentials>,
 ) -> Result<Status, Status> {
     let client = build_client().map_err(log_error_and_return(Status::InternalServerError))?;
     let credentials = UdaCredentials::from(credentials.into_inner());
     authenticate(&client, &credentials).await?;
-    let mut mutex = credentials_storage
-        .lock()
-        .map_err(log_error_and_return(Status::InternalServerError))?;
     let uuid = Uuid::new_v4().to_string();
     let cookie = Cookie::build((AUTHENTICATION_COOKIE.to_owned(), uuid.clone()))
         .max_age(Duration::days(365))
         .build();
     cookie_jar.add_private(cookie);
-    (*mutex).store(uuid.clone(), credentials);
+    credentials_storage.store(uuid.clone(), credentials);
     Ok(Status::Ok)
 }
 
@@ -56,7 +55,11 @@ pub async fn retrieve_members_to_check(credentials: UdaCredentials) -> Result<St
     let client = build_client().map_err(log_error_and_return(Status::InternalServerError))?;
     authenticate(&client, &credentials).await?;
     let url = credentials.uda_url();
-    match retrieve_members(&client, url).await {
+    match with_retries("UDA members retrieval", is_transient_failure, || {
+        retrieve_members(&client, url)
+    })
+    .await
+    {
         Ok(members) => Ok(json!(members).to_string()),
         Err(UdaError::LackOfPermissions) => Err(Status::Unauthorized),
         Err(_) => Err(Status::BadGateway),
@@ -64,6 +67,7 @@ pub async fn retrieve_members_to_check(credentials: UdaCredentials) -> Result<St
 }
 
 /// Confirm members on UDA if authorized.
+/// Confirmations are never retried, as a failed one may have been applied anyway.
 /// Return a JSON containing members ids which have been marked as confirmed and whose confirmation has failed:
 /// ```json
 /// {
@@ -73,6 +77,7 @@ pub async fn retrieve_members_to_check(credentials: UdaCredentials) -> Result<St
 /// ```
 #[post("/uda/confirm", format = "application/json", data = "<members_ids>")]
 pub async fn confirm_members(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     members_ids: Json<Vec<u16>>,
     credentials: UdaCredentials,
 ) -> (Status, Value) {
@@ -96,8 +101,7 @@ pub async fn confirm_members(
     let mut not_marked_ids = vec![];
     let mut errors = vec![];
     for id in &members_ids {
-        let result = confirm_member(&client, url, *id).await;
-        if let Err(error) = result {
+        if let Err(error) = confirm_member(&client, url, *id).await {
             debug!(
                 "Member has not been confirmed. [member_id: {id}, error: {:?}]",
                 error
@@ -112,6 +116,13 @@ pub async fn confirm_members(
         .filter(|id| !not_marked_ids.contains(**id))
         .copied()
         .collect();
+    record_action(
+        pool,
+        credentials.login(),
+        AuditAction::UdaConfirmation,
+        marked_ids.len(),
+        marked_ids.iter().map(u16::to_string).collect(),
+    );
 
     (
         if not_marked_ids.is_empty() {
@@ -127,7 +138,7 @@ pub async fn confirm_members(
 /// - i.e. the date this endpoint is called.
 #[get("/uda/instances")]
 pub async fn list_instances(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     configuration: &State<Configuration>,
 ) -> Result<Value, Status> {
     let client = build_client().map_err(log_error_and_return(Status::InternalServerError))?;
@@ -149,7 +160,10 @@ async fn authenticate(client: &Client, credentials: &UdaCredentials) -> Result<(
     let login = credentials.login();
     let password = credentials.password();
 
-    let authentication_result = authenticate_into_uda(client, url, login, password).await;
+    let authentication_result = with_retries("UDA authentication", is_transient_failure, || {
+        authenticate_into_uda(client, url, login, password)
+    })
+    .await;
     if let Err(error) = authentication_result {
         match error {
             ConnectionFailed => Err(Status::BadGateway),
@@ -182,7 +196,6 @@ mod tests {
         use rocket::http::{ContentType, Header, Status};
         use rocket::local::asynchronous::Client;
         use rocket::serde::json::json;
-        use std::sync::Mutex;
         use uda_connector::login::setup_authentication;
         use wiremock::matchers::{body_string, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -199,11 +212,10 @@ mod tests {
                     "password".to_owned(),
                 )
                 .into();
-            let credentials_storage_mutex =
-                Mutex::new(CredentialsStorage::<UdaCredentials>::default());
+            let credentials_storage = CredentialsStorage::<UdaCredentials>::default();
 
             let rocket = rocket::build()
-                .manage(credentials_storage_mutex)
+                .manage(credentials_storage)
                 .mount("/", routes![login]);
             let client = Client::tracked(rocket).await.unwrap();
             let credentials_as_json = json!(credentials).to_string();
@@ -243,11 +255,10 @@ mod tests {
                     "password".to_owned(),
                 )
                 .into();
-            let credentials_storage_mutex =
-                Mutex::new(CredentialsStorage::<UdaCredentials>::default());
+            let credentials_storage = CredentialsStorage::<UdaCredentials>::default();
 
             let rocket = rocket::build()
-                .manage(credentials_storage_mutex)
+                .manage(credentials_storage)
                 .mount("/", routes![login]);
             let client = Client::tracked(rocket).await.unwrap();
             let credentials_as_json = json!(credentials).to_string();
@@ -300,11 +311,10 @@ mod tests {
                     "password".to_owned(),
                 )
                 .into();
-            let credentials_storage_mutex =
-                Mutex::new(CredentialsStorage::<UdaCredentials>::default());
+            let credentials_storage = CredentialsStorage::<UdaCredentials>::default();
 
             let rocket = rocket::build()
-                .manage(credentials_storage_mutex)
+                .manage(credentials_storage)
                 .mount("/", routes![login]);
             let client = Client::tracked(rocket).await.unwrap();
             let credentials_as_json = json!(credentials).to_string();
@@ -335,7 +345,6 @@ mod tests {
         use dto::uda_member::UdaMember;
         use rocket::http::Status;
         use rocket::local::asynchronous::Client;
-        use std::sync::Mutex;
         use uda_connector::login::setup_authentication;
         use uda_connector::retrieve_members::setup_member_retrieval;
         use wiremock::MockServer;
@@ -347,12 +356,11 @@ mod tests {
             let expected_result = setup_member_retrieval(&mock_server).await;
 
             let uuid = "e9af5e0f-c441-4bcd-bf22-31cc5b1f2f9e";
-            let mut credentials_storage = CredentialsStorage::<UdaCredentials>::default();
+            let credentials_storage = CredentialsStorage::<UdaCredentials>::default();
             credentials_storage.store(uuid.to_string(), credentials.into());
-            let credentials_storage_mutex = Mutex::new(credentials_storage);
 
             let rocket = rocket::build()
-                .manage(credentials_storage_mutex)
+                .manage(credentials_storage)
                 .mount("/", routes![retrieve_members_to_check]);
 
             let client = Client::tracked(rocket).await.unwrap();
@@ -369,11 +377,10 @@ mod tests {
         #[async_test]
         async fn fail_when_unauthorized() {
             let uuid = "e9af5e0f-c441-4bcd-bf22-31cc5b1f2f9e";
-            let credentials_storage_mutex =
-                Mutex::new(CredentialsStorage::<UdaCredentials>::default());
+            let credentials_storage = CredentialsStorage::<UdaCredentials>::default();
 
             let rocket = rocket::build()
-                .manage(credentials_storage_mutex)
+                .manage(credentials_storage)
                 .mount("/", routes![retrieve_members_to_check]);
 
             let client = Client::tracked(rocket).await.unwrap();
@@ -390,12 +397,11 @@ mod tests {
             let mock_server = MockServer::start().await;
             let credentials = setup_authentication(&mock_server).await;
             let uuid = "e9af5e0f-c441-4bcd-bf22-31cc5b1f2f9e";
-            let mut credentials_storage = CredentialsStorage::<UdaCredentials>::default();
+            let credentials_storage = CredentialsStorage::<UdaCredentials>::default();
             credentials_storage.store(uuid.to_string(), credentials.into());
-            let credentials_storage_mutex = Mutex::new(credentials_storage);
 
             let rocket = rocket::build()
-                .manage(credentials_storage_mutex)
+                .manage(credentials_storage)
                 .mount("/", routes![retrieve_members_to_check]);
 
             let client = Client::tracked(rocket).await.unwrap();
@@ -409,74 +415,114 @@ mod tests {
     }
 
     mod confirm_members {
+        use crate::database::DatabaseConnection;
+        use crate::database::dao;
+        use crate::database::with_temp_database;
         use crate::uda::credentials::UdaCredentials;
         use crate::web::api::uda_controller::confirm_members;
+        use diesel::r2d2::ConnectionManager;
+        use dto::audit_log::{AuditAction, AuditLogFilter};
+        use r2d2::Pool;
+        use rocket::State;
         use rocket::http::Status;
         use rocket::serde::json::Json;
+        use rocket::tokio::runtime::Runtime;
         use std::collections::HashMap;
         use uda_connector::confirm_member::{setup_confirm_member, setup_csrf_token};
         use uda_connector::login::{setup_authentication, setup_authenticity_token};
         use wiremock::MockServer;
 
-        #[async_test]
-        async fn success() {
-            let mock_server = MockServer::start().await;
-            let credentials = setup_authentication(&mock_server).await;
-            let csrf_token = setup_csrf_token(&mock_server).await;
-            setup_confirm_member(&mock_server, &csrf_token, 1).await;
-            setup_confirm_member(&mock_server, &csrf_token, 2).await;
-            setup_confirm_member(&mock_server, &csrf_token, 3).await;
-
-            let (status, value) =
-                confirm_members(Json::from(vec![1_u16, 2_u16, 3_u16]), credentials.into()).await;
-
-            assert_eq!(Status::Ok, status);
-            let result: HashMap<String, Vec<u16>> = rocket::serde::json::from_value(value).unwrap();
-            assert_eq!(&vec![1_u16, 2_u16, 3_u16], result.get("ok").unwrap());
-            assert_eq!(&Vec::<u16>::new(), result.get("nok").unwrap());
-        }
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mock_server = MockServer::start().await;
+                let credentials = setup_authentication(&mock_server).await;
+                let csrf_token = setup_csrf_token(&mock_server).await;
+                setup_confirm_member(&mock_server, &csrf_token, 1).await;
+                setup_confirm_member(&mock_server, &csrf_token, 2).await;
+                setup_confirm_member(&mock_server, &csrf_token, 3).await;
+
+                let (status, value) = confirm_members(
+                    State::from(&pool),
+                    Json::from(vec![1_u16, 2_u16, 3_u16]),
+                    credentials.into(),
+                )
+                .await;
 
-        #[async_test]
-        async fn fail_to_confirm_some_members() {
-            let mock_server = MockServer::start().await;
-            let credentials = setup_authentication(&mock_server).await;
-            let csrf_token = setup_csrf_token(&mock_server).await;
-            setup_confirm_member(&mock_server, &csrf_token, 1).await;
+                assert_eq!(Status::Ok, status);
+                let result: HashMap<String, Vec<u16>> =
+                    rocket::serde::json::from_value(value).unwrap();
+                assert_eq!(&vec![1_u16, 2_u16, 3_u16], result.get("ok").unwrap());
+                assert_eq!(&Vec::<u16>::new(), result.get("nok").unwrap());
 
-            let (status, value) =
-                confirm_members(Json::from(vec![1, 2, 3]), credentials.into()).await;
+                let mut connection = pool.get().unwrap();
+                let entries =
+                    dao::audit_log::retrieve(&mut connection, &AuditLogFilter::default()).unwrap();
+                assert_eq!(1, entries.len());
+                assert_eq!(&AuditAction::UdaConfirmation, entries[0].action());
+                assert_eq!(
+                    &vec!["1".to_owned(), "2".to_owned(), "3".to_owned()],
+                    entries[0].targets()
+                );
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
 
-            assert_eq!(Status::Unauthorized, status);
-            let result: HashMap<String, Vec<u16>> = rocket::serde::json::from_value(value).unwrap();
-            assert_eq!(&vec![1], result.get("ok").unwrap());
-            assert_eq!(&vec![2, 3], result.get("nok").unwrap());
+        #[test]
+        fn fail_to_confirm_some_members() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mock_server = MockServer::start().await;
+                let credentials = setup_authentication(&mock_server).await;
+                let csrf_token = setup_csrf_token(&mock_server).await;
+                setup_confirm_member(&mock_server, &csrf_token, 1).await;
+
+                let (status, value) = confirm_members(
+                    State::from(&pool),
+                    Json::from(vec![1, 2, 3]),
+                    credentials.into(),
+                )
+                .await;
+
+                assert_eq!(Status::Unauthorized, status);
+                let result: HashMap<String, Vec<u16>> =
+                    rocket::serde::json::from_value(value).unwrap();
+                assert_eq!(&vec![1], result.get("ok").unwrap());
+                assert_eq!(&vec![2, 3], result.get("nok").unwrap());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
 
-        #[async_test]
-        async fn fail_when_no_authentication() {
-            let mock_server = MockServer::start().await;
-            setup_authenticity_token(&mock_server).await;
+        #[test]
+        fn fail_when_no_authentication() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let mock_server = MockServer::start().await;
+                setup_authenticity_token(&mock_server).await;
 
-            let credentials: UdaCredentials = uda_connector::credentials::UdaCredentials::new(
-                mock_server.uri(),
-                "login".to_owned(),
-                "password".to_owned(),
-            )
-            .into();
+                let credentials: UdaCredentials = uda_connector::credentials::UdaCredentials::new(
+                    mock_server.uri(),
+                    "login".to_owned(),
+                    "password".to_owned(),
+                )
+                .into();
 
-            let (status, value) = confirm_members(Json::from(vec![1, 2, 3]), credentials).await;
+                let (status, value) =
+                    confirm_members(State::from(&pool), Json::from(vec![1, 2, 3]), credentials)
+                        .await;
 
-            assert_eq!(Status::Unauthorized, status);
-            let result: HashMap<String, Vec<u16>> = rocket::serde::json::from_value(value).unwrap();
-            assert_eq!(&Vec::<u16>::new(), result.get("ok").unwrap());
-            assert_eq!(&vec![1, 2, 3], result.get("nok").unwrap());
+                assert_eq!(Status::Unauthorized, status);
+                let result: HashMap<String, Vec<u16>> =
+                    rocket::serde::json::from_value(value).unwrap();
+                assert_eq!(&Vec::<u16>::new(), result.get("ok").unwrap());
+                assert_eq!(&vec![1, 2, 3], result.get("nok").unwrap());
+            }
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
         }
     }
 
     mod list_instances {
+        use crate::database::DatabaseConnection;
         use crate::database::with_temp_database;
         use crate::web::api::uda_controller::list_instances;
-        use diesel::SqliteConnection;
         use diesel::r2d2::ConnectionManager;
         use dto::uda_instance::Instance;
         use dto::uda_instance::InstancesList;
@@ -491,7 +537,7 @@ mod tests {
 
         #[test]
         fn success() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
                 Mock::given(method("GET"))
                     .and(path("tenants"))
@@ -522,7 +568,7 @@ mod tests {
 
         #[test]
         fn fail_when_bad_gateway() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let mock_server = MockServer::start().await;
                 Mock::given(method("GET"))
                     .and(path("tenants"))
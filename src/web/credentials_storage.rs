@@ -1,47 +1,328 @@
+use crate::database::DatabaseConnection;
+use crate::database::dao;
+use crate::database::encryption::{decrypt, encrypt, is_encryption_enabled};
+use crate::database::error::DatabaseError;
+use crate::database::error::DatabaseError::ConversionError;
+use crate::tools::env_args::retrieve_arg_value;
 use cached::{Cached, TimedSizedCache};
-use std::fmt::Debug;
+use chrono::{TimeDelta, Utc};
+use derive_getters::Getters;
+use diesel::r2d2::{ConnectionManager, Pool};
+use rocket::serde::Serialize;
+use rocket::serde::de::DeserializeOwned;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+const CAPACITY_ARG: &str = "--session-capacity";
+const LIFETIME_ARG: &str = "--session-lifetime-days";
 
 const CACHE_SIZE: usize = 100;
+const DEFAULT_CAPACITY: usize = 1000;
+const DEFAULT_LIFETIME_IN_DAYS: u64 = 30;
+
+/// How many sessions of a kind can be kept at a time, and how long they last.
+#[derive(Getters, Clone, Debug, PartialEq)]
+pub struct SessionConfig {
+    capacity: usize,
+    lifetime: Duration,
+}
+
+impl SessionConfig {
+    pub fn new(capacity: usize, lifetime: Duration) -> Self {
+        Self { capacity, lifetime }
+    }
+}
+
+/// Build the config from the args passed to the app.
+/// Missing or invalid args fall back to their default value.
+pub fn retrieve_session_config() -> SessionConfig {
+    let capacity = retrieve_arg_value(CAPACITY_ARG)
+        .and_then(|capacity| capacity.parse::<usize>().ok())
+        .filter(|capacity| *capacity > 0)
+        .unwrap_or(DEFAULT_CAPACITY);
+    let lifetime_in_days = retrieve_arg_value(LIFETIME_ARG)
+        .and_then(|lifetime| lifetime.parse::<u64>().ok())
+        .filter(|lifetime| *lifetime > 0)
+        .unwrap_or(DEFAULT_LIFETIME_IN_DAYS);
+
+    SessionConfig::new(
+        capacity,
+        Duration::from_secs(lifetime_in_days * 60 * 60 * 24),
+    )
+}
+
+enum Backend<C> {
+    Memory(Mutex<TimedSizedCache<String, C>>),
+    /// Credentials are stored encrypted, so that sessions survive restarts.
+    Database {
+        pool: Pool<ConnectionManager<DatabaseConnection>>,
+        kind: &'static str,
+    },
+}
 
-/// A container for storing credentials. Only 100 credentials can be stored at a time,
-/// and they expire after one month.
-#[derive(Debug)]
+/// A container for storing credentials, which can be shared between requests.
+/// Sessions are persisted in the database when an encryption key has been provided,
+/// and only kept in memory otherwise.
+/// Only in-memory sessions are locked, so that requests don't wait for each other's queries.
 pub struct CredentialsStorage<C: Send + Sync> {
-    credentials: TimedSizedCache<String, C>,
+    backend: Backend<C>,
+    config: SessionConfig,
 }
 
-impl<C: Send + Sync> CredentialsStorage<C> {
-    pub fn store(&mut self, id: String, credentials: C) {
-        self.credentials.cache_set(id, credentials);
+impl<C: Send + Sync + Clone + Serialize + DeserializeOwned> CredentialsStorage<C> {
+    /// The kind tells apart the sessions of the different services sharing the database.
+    pub fn new(
+        pool: Pool<ConnectionManager<DatabaseConnection>>,
+        kind: &'static str,
+        config: SessionConfig,
+    ) -> Self {
+        let backend = if is_encryption_enabled() {
+            Backend::Database { pool, kind }
+        } else {
+            warn!("No database encryption key provided, {kind} sessions will be lost on restart.");
+            Backend::Memory(build_cache(&config))
+        };
+        Self { backend, config }
     }
 
-    pub fn get(&mut self, id: &str) -> Option<&C> {
-        self.credentials.cache_get(id)
+    pub fn store(&self, id: String, credentials: C) {
+        match &self.backend {
+            Backend::Memory(cache) => {
+                // A cache left by a panicking thread is still consistent
+                let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+                cache.cache_set(id, credentials);
+            }
+            Backend::Database { pool, kind } => {
+                if let Err(error) = store_session(pool, kind, &self.config, &id, &credentials) {
+                    error!("Can't store session [kind: {kind}]\n{error:#?}");
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<C> {
+        match &self.backend {
+            Backend::Memory(cache) => {
+                let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+                cache.cache_get(id).cloned()
+            }
+            Backend::Database { pool, kind } => find_session(pool, kind, id)
+                .inspect_err(|error| error!("Can't retrieve session [kind: {kind}]\n{error:#?}"))
+                .ok()
+                .flatten(),
+        }
+    }
+}
+
+impl<C: Send + Sync> Debug for CredentialsStorage<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let backend = match &self.backend {
+            Backend::Memory(_) => "Memory",
+            Backend::Database { .. } => "Database",
+        };
+        f.debug_struct("CredentialsStorage")
+            .field("backend", &backend)
+            .field("config", &self.config)
+            .finish()
     }
 }
 
 impl<C: Send + Sync> Default for CredentialsStorage<C> {
-    /// By default, credentials expire after one month.
+    /// By default, only 100 credentials are kept in memory, and they expire after one month.
     fn default() -> Self {
-        let credentials = TimedSizedCache::with_size_and_lifespan(CACHE_SIZE, 60 * 60 * 24 * 30);
-        Self { credentials }
+        let config = SessionConfig::new(
+            CACHE_SIZE,
+            Duration::from_secs(DEFAULT_LIFETIME_IN_DAYS * 60 * 60 * 24),
+        );
+        Self {
+            backend: Backend::Memory(build_cache(&config)),
+            config,
+        }
     }
 }
 
+fn build_cache<C>(config: &SessionConfig) -> Mutex<TimedSizedCache<String, C>> {
+    Mutex::new(TimedSizedCache::with_size_and_lifespan(
+        *config.capacity(),
+        config.lifetime().as_secs(),
+    ))
+}
+
+/// Expired sessions and sessions beyond the capacity are cleaned up on each login.
+fn store_session<C: Serialize>(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    kind: &str,
+    config: &SessionConfig,
+    id: &str,
+    credentials: &C,
+) -> Result<(), DatabaseError> {
+    let credentials = rocket::serde::json::to_string(credentials)
+        .map_err(|error| ConversionError(error.to_string()))?;
+    let lifetime = TimeDelta::from_std(*config.lifetime())
+        .map_err(|error| ConversionError(error.to_string()))?;
+    let now = Utc::now().naive_local();
+
+    let mut connection = pool.get()?;
+    dao::session::delete_expired_and_excess(
+        &mut connection,
+        kind,
+        &now,
+        config.capacity().saturating_sub(1),
+    )?;
+    dao::session::insert(
+        &mut connection,
+        kind,
+        id,
+        &encrypt(&credentials)?,
+        &(now + lifetime),
+    )
+}
+
+fn find_session<C: DeserializeOwned>(
+    pool: &Pool<ConnectionManager<DatabaseConnection>>,
+    kind: &str,
+    id: &str,
+) -> Result<Option<C>, DatabaseError> {
+    let mut connection = pool.get()?;
+    let Some(credentials) =
+        dao::session::find(&mut connection, kind, id, &Utc::now().naive_local())?
+    else {
+        return Ok(None);
+    };
+
+    let credentials = rocket::serde::json::from_str(&decrypt(credentials)?)
+        .map_err(|error| ConversionError(error.to_string()))?;
+    Ok(Some(credentials))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::web::credentials_storage::CredentialsStorage;
+    use crate::web::credentials_storage::{Backend, CredentialsStorage};
     use cached::Cached;
 
+    fn cache_size<C: Send + Sync>(storage: &CredentialsStorage<C>) -> usize {
+        match &storage.backend {
+            Backend::Memory(cache) => cache.lock().unwrap().cache_size(),
+            Backend::Database { .. } => panic!("Expected an in-memory storage"),
+        }
+    }
+
     #[test]
     fn should_store_only_100_credentials() {
-        let mut storage: CredentialsStorage<()> = CredentialsStorage::default();
-        assert_eq!(0, storage.credentials.cache_size());
+        let storage: CredentialsStorage<()> = CredentialsStorage::default();
+        assert_eq!(0, cache_size(&storage));
         (0..100).for_each(|id| storage.store(id.to_string(), ()));
-        (0..100).for_each(|id| assert_eq!(Some(&()), storage.get(&id.to_string())));
-        assert_eq!(100, storage.credentials.cache_size());
+        (0..100).for_each(|id| assert_eq!(Some(()), storage.get(&id.to_string())));
+        assert_eq!(100, cache_size(&storage));
         storage.store("100".to_owned(), ());
-        assert_eq!(100, storage.credentials.cache_size());
+        assert_eq!(100, cache_size(&storage));
         assert_eq!(None, storage.get("0"));
     }
+
+    mod retrieve_session_config {
+        use crate::tools::env_args::with_env_args;
+        use crate::web::credentials_storage::{
+            CAPACITY_ARG, DEFAULT_CAPACITY, LIFETIME_ARG, SessionConfig, retrieve_session_config,
+        };
+        use std::time::Duration;
+
+        #[test]
+        fn success() {
+            let config = with_env_args(
+                vec![format!("{CAPACITY_ARG}=50"), format!("{LIFETIME_ARG}=7")],
+                retrieve_session_config,
+            );
+
+            assert_eq!(
+                SessionConfig::new(50, Duration::from_secs(7 * 24 * 3600)),
+                config
+            );
+        }
+
+        #[test]
+        fn default_when_invalid() {
+            let config = with_env_args(
+                vec![
+                    format!("{CAPACITY_ARG}=0"),
+                    format!("{LIFETIME_ARG}=forever"),
+                ],
+                retrieve_session_config,
+            );
+
+            assert_eq!(
+                SessionConfig::new(DEFAULT_CAPACITY, Duration::from_secs(30 * 24 * 3600)),
+                config
+            );
+        }
+    }
+
+    mod persistent_storage {
+        use crate::database::encryption::tests::encryption_key_arg;
+        use crate::database::schema::session::credentials;
+        use crate::database::schema::session::dsl::session;
+        use crate::database::with_temp_database;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::tools::env_args::with_env_args;
+        use crate::web::credentials_storage::{Backend, CredentialsStorage, SessionConfig};
+        use diesel::prelude::*;
+        use std::time::Duration;
+
+        fn config() -> SessionConfig {
+            SessionConfig::new(2, Duration::from_secs(3600))
+        }
+
+        #[test]
+        fn success() {
+            with_temp_database(|pool| {
+                with_env_args(vec![encryption_key_arg()], || {
+                    let fileo_credentials =
+                        FileoCredentials::new("login".to_owned(), "secret".to_owned());
+                    let storage = CredentialsStorage::new(pool.clone(), "Fileo", config());
+                    storage.store("id".to_owned(), fileo_credentials.clone());
+
+                    let stored_credentials = session
+                        .select(credentials)
+                        .load::<String>(&mut pool.get().unwrap())
+                        .unwrap();
+                    assert_eq!(1, stored_credentials.len());
+                    assert!(!stored_credentials[0].contains("secret"));
+
+                    // As after a restart
+                    let storage = CredentialsStorage::<FileoCredentials>::new(
+                        pool.clone(),
+                        "Fileo",
+                        config(),
+                    );
+                    assert_eq!(Some(fileo_credentials), storage.get("id"));
+                    assert_eq!(None, storage.get("unknown"));
+                })
+            })
+        }
+
+        #[test]
+        fn success_when_capacity_reached() {
+            with_temp_database(|pool| {
+                with_env_args(vec![encryption_key_arg()], || {
+                    let storage = CredentialsStorage::new(pool.clone(), "Fileo", config());
+                    for id in ["first", "second", "third"] {
+                        storage.store(id.to_owned(), ());
+                        std::thread::sleep(Duration::from_millis(2));
+                    }
+
+                    assert_eq!(None, storage.get("first"));
+                    assert_eq!(Some(()), storage.get("second"));
+                    assert_eq!(Some(()), storage.get("third"));
+                })
+            })
+        }
+
+        #[test]
+        fn in_memory_without_encryption_key() {
+            with_temp_database(|pool| {
+                let storage = CredentialsStorage::<()>::new(pool, "Fileo", config());
+                assert!(matches!(storage.backend, Backend::Memory(_)));
+            })
+        }
+    }
 }
@@ -1,10 +1,13 @@
+use crate::audit::build_filter;
+use crate::database::DatabaseConnection;
 use crate::database::dao;
 use crate::database::dao::last_update::UpdatableElement;
 use crate::database::dao::last_update::UpdatableElement::UdaInstances;
 use crate::fileo::credentials::FileoCredentials;
 use crate::tools::log_error_and_return;
-use diesel::SqliteConnection;
+use crate::web::admin::AdminCredentials;
 use diesel::r2d2::{ConnectionManager, Pool};
+use dto::import_history::ImportedElement;
 use rocket::http::Status;
 use rocket::response::Redirect;
 use rocket::{Request, State};
@@ -32,15 +35,25 @@ pub async fn fileo_login() -> Template {
 
 #[get("/memberships/update")]
 pub async fn update_memberships(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     _credentials: FileoCredentials,
 ) -> Result<Template, Status> {
     let last_update = retrieve_last_update(pool)?;
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let report = dao::membership_import::retrieve_last_report(&mut connection)
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let imports =
+        dao::import_history::retrieve(&mut connection, Some(ImportedElement::Memberships))
+            .map_err(log_error_and_return(Status::InternalServerError))?;
     Ok(Template::render(
         "member/update-memberships",
         context! {
             title: "Mise à jour de la liste des licences",
-            last_update: last_update
+            last_update: last_update,
+            report: report,
+            imports: imports
         },
     ))
 }
@@ -50,9 +63,58 @@ pub async fn update_memberships_unauthenticated() -> Redirect {
     Redirect::to(uri!("/fileo/login/?page=/memberships/update"))
 }
 
+#[get("/memberships/upload")]
+pub async fn upload_memberships(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    _credentials: FileoCredentials,
+) -> Result<Template, Status> {
+    let last_update = retrieve_last_update(pool)?;
+    Ok(Template::render(
+        "member/upload-memberships",
+        context! {
+            title: "Import manuel de la liste des licences",
+            last_update: last_update
+        },
+    ))
+}
+
+#[get("/memberships/upload", rank = 2)]
+pub async fn upload_memberships_unauthenticated() -> Redirect {
+    Redirect::to(uri!("/fileo/login/?page=/memberships/upload"))
+}
+
+#[get("/memberships/diff")]
+pub async fn memberships_diff(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    _credentials: FileoCredentials,
+) -> Result<Template, Status> {
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let diff = dao::membership_import::retrieve_last_diff(&mut connection)
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let date = diff
+        .as_ref()
+        .map(|diff| diff.date().format("%d/%m/%Y à %H:%M").to_string());
+
+    Ok(Template::render(
+        "member/memberships-diff",
+        context! {
+            title: "Modifications de la dernière mise à jour",
+            date: date,
+            diff: diff
+        },
+    ))
+}
+
+#[get("/memberships/diff", rank = 2)]
+pub async fn memberships_diff_unauthenticated() -> Redirect {
+    Redirect::to(uri!("/fileo/login/?page=/memberships/diff"))
+}
+
 #[get("/memberships")]
 pub async fn list_memberships(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     _credentials: FileoCredentials,
 ) -> Result<Template, Status> {
     let mut connection = pool
@@ -75,6 +137,40 @@ pub async fn list_memberships_unauthenticated() -> Redirect {
     Redirect::to(uri!("/fileo/login/?page=/memberships"))
 }
 
+#[get("/audit-log?<actor>&<action>&<from>&<to>")]
+pub async fn audit_log(
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
+    actor: Option<&str>,
+    action: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    _credentials: AdminCredentials,
+) -> Result<Template, Status> {
+    let filter = build_filter(actor, action, from, to).map_err(|error| {
+        debug!("Invalid audit log filter [error: {error}]");
+        Status::BadRequest
+    })?;
+    let mut connection = pool
+        .get()
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+    let entries = dao::audit_log::retrieve(&mut connection, &filter)
+        .map_err(log_error_and_return(Status::InternalServerError))?;
+
+    Ok(Template::render(
+        "audit/audit-log",
+        context! {
+            title: "Journal des actions",
+            filter: filter,
+            entries: entries
+        },
+    ))
+}
+
+#[get("/audit-log", rank = 2)]
+pub async fn audit_log_unauthenticated() -> Redirect {
+    Redirect::to(uri!("/fileo/login/?page=/audit-log"))
+}
+
 #[get("/memberships/lookup")]
 pub async fn look_membership_up(_credentials: FileoCredentials) -> Template {
     Template::render(
@@ -92,7 +188,7 @@ pub async fn look_membership_up_unauthenticated() -> Redirect {
 
 #[get("/csv/check")]
 pub async fn check_members_from_csv(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     _credentials: FileoCredentials,
 ) -> Result<Template, Status> {
     let last_update = retrieve_last_update(pool)?;
@@ -112,7 +208,7 @@ pub async fn check_members_from_csv_unauthenticated() -> Redirect {
 
 #[get("/uda/check")]
 pub async fn check_members_from_uda(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
     _credentials: FileoCredentials, // Fileo credentials are required for importing from UDA as well
 ) -> Result<Template, Status> {
     let mut connection = pool
@@ -154,7 +250,7 @@ pub async fn not_found(req: &Request<'_>) -> Template {
 }
 
 fn retrieve_last_update(
-    pool: &State<Pool<ConnectionManager<SqliteConnection>>>,
+    pool: &State<Pool<ConnectionManager<DatabaseConnection>>>,
 ) -> Result<String, Status> {
     let mut connection = pool
         .get()
@@ -192,6 +288,7 @@ mod tests {
     }
 
     mod list_memberships {
+        use crate::database::DatabaseConnection;
         use crate::database::with_temp_database;
         use crate::fileo::authentication::AUTHENTICATION_COOKIE;
         use crate::fileo::credentials::FileoCredentials;
@@ -199,23 +296,20 @@ mod tests {
         use crate::web::frontend::frontend_controller::{
             list_memberships, list_memberships_unauthenticated,
         };
-        use diesel::SqliteConnection;
         use diesel::r2d2::{ConnectionManager, Pool};
         use rocket::http::{Cookie, Status};
         use rocket::local::asynchronous::Client;
         use rocket::tokio::runtime::Runtime;
         use rocket_dyn_templates::Template;
-        use std::sync::Mutex;
 
         #[test]
         fn should_render_membership_list() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let mut credentials_storage = CredentialsStorage::default();
+                let credentials_storage = CredentialsStorage::default();
                 let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
                 credentials_storage.store(uuid.clone(), credentials);
-                let credentials_storage_mutex = Mutex::new(credentials_storage);
 
                 let rocket = rocket::build()
                     .mount(
@@ -223,7 +317,7 @@ mod tests {
                         routes![list_memberships, list_memberships_unauthenticated],
                     )
                     .manage(pool)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .attach(Template::fairing());
 
                 let client = Client::tracked(rocket).await.unwrap();
@@ -239,7 +333,7 @@ mod tests {
 
         #[test]
         fn should_not_render_membership_list_when_unauthenticated() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let rocket = rocket::build()
                     .mount(
                         "/",
@@ -263,7 +357,315 @@ mod tests {
         }
     }
 
+    mod update_memberships {
+        use crate::database::DatabaseConnection;
+        use crate::database::with_temp_database;
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::membership::import::import_memberships;
+        use crate::membership::tests::jon_doe;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use crate::web::frontend::frontend_controller::{
+            update_memberships, update_memberships_unauthenticated,
+        };
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use dto::import_report::{ImportReport, RejectedRow};
+        use rocket::http::{Cookie, Status};
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use rocket_dyn_templates::Template;
+
+        #[test]
+        fn success_with_rejected_rows() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let credentials =
+                    FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
+                let credentials_storage = CredentialsStorage::default();
+                let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
+                credentials_storage.store(uuid.clone(), credentials);
+
+                let mut connection = pool.get().unwrap();
+                let report = ImportReport::new(vec![RejectedRow::new(
+                    3,
+                    "Snow;Jonette;Oops".to_owned(),
+                    "expected 10 fields, found 3".to_owned(),
+                )]);
+                import_memberships(&mut connection, &[jon_doe()], &report, false).unwrap();
+
+                let rocket = rocket::build()
+                    .mount(
+                        "/",
+                        routes![update_memberships, update_memberships_unauthenticated],
+                    )
+                    .manage(pool)
+                    .manage(credentials_storage)
+                    .attach(Template::fairing());
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let cookie = Cookie::new(AUTHENTICATION_COOKIE, uuid);
+
+                let request = client.get("/memberships/update").cookie(cookie);
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+                let body = response.into_string().await.unwrap();
+                assert!(body.contains("Snow;Jonette;Oops"));
+                assert!(body.contains("expected 10 fields, found 3"));
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod upload_memberships {
+        use crate::database::DatabaseConnection;
+        use crate::database::with_temp_database;
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use crate::web::frontend::frontend_controller::{
+            upload_memberships, upload_memberships_unauthenticated,
+        };
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use rocket::http::{Cookie, Status};
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use rocket_dyn_templates::Template;
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let credentials =
+                    FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
+                let credentials_storage = CredentialsStorage::default();
+                let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
+                credentials_storage.store(uuid.clone(), credentials);
+
+                let rocket = rocket::build()
+                    .mount(
+                        "/",
+                        routes![upload_memberships, upload_memberships_unauthenticated],
+                    )
+                    .manage(pool)
+                    .manage(credentials_storage)
+                    .attach(Template::fairing());
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let cookie = Cookie::new(AUTHENTICATION_COOKIE, uuid);
+
+                let response = client
+                    .get("/memberships/upload")
+                    .cookie(cookie)
+                    .dispatch()
+                    .await;
+                assert_eq!(Status::Ok, response.status());
+                let body = response.into_string().await.unwrap();
+                assert!(body.contains("memberships-file-picker"));
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn redirect_when_unauthenticated() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let rocket = rocket::build()
+                    .mount(
+                        "/",
+                        routes![upload_memberships, upload_memberships_unauthenticated],
+                    )
+                    .manage(pool)
+                    .manage(CredentialsStorage::<FileoCredentials>::default())
+                    .attach(Template::fairing());
+
+                let client = Client::tracked(rocket).await.unwrap();
+
+                let response = client.get("/memberships/upload").dispatch().await;
+                assert_eq!(Status::SeeOther, response.status());
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod audit_log {
+        use crate::database::DatabaseConnection;
+        use crate::database::with_temp_database;
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::web::admin::AdminLogins;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use crate::web::frontend::frontend_controller::{audit_log, audit_log_unauthenticated};
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use rocket::http::{Cookie, Status};
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use rocket_dyn_templates::Template;
+
+        const UUID: &str = "5d1c5c1e-8d47-4f4a-9a8c-3b0f4d7e2a61";
+
+        async fn create_client(
+            pool: Pool<ConnectionManager<DatabaseConnection>>,
+            admin_logins: Vec<String>,
+        ) -> Client {
+            let credentials_storage = CredentialsStorage::default();
+            credentials_storage.store(
+                UUID.to_owned(),
+                FileoCredentials::new("test_login".to_owned(), "test_password".to_owned()),
+            );
+
+            let rocket = rocket::build()
+                .mount("/", routes![audit_log, audit_log_unauthenticated])
+                .manage(pool)
+                .manage(credentials_storage)
+                .manage(AdminLogins::new(admin_logins))
+                .attach(Template::fairing());
+
+            Client::tracked(rocket).await.unwrap()
+        }
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let client = create_client(pool, vec!["test_login".to_owned()]).await;
+
+                let response = client
+                    .get("/audit-log")
+                    .cookie(Cookie::new(AUTHENTICATION_COOKIE, UUID))
+                    .dispatch()
+                    .await;
+                assert_eq!(Status::Ok, response.status());
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn forbidden_when_not_admin() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let client = create_client(pool, vec!["admin".to_owned()]).await;
+
+                let response = client
+                    .get("/audit-log")
+                    .cookie(Cookie::new(AUTHENTICATION_COOKIE, UUID))
+                    .dispatch()
+                    .await;
+                assert_eq!(Status::Forbidden, response.status());
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
+    mod memberships_diff {
+        use crate::database::DatabaseConnection;
+        use crate::database::{dao, with_temp_database};
+        use crate::fileo::authentication::AUTHENTICATION_COOKIE;
+        use crate::fileo::credentials::FileoCredentials;
+        use crate::membership::tests::jon_doe;
+        use crate::web::credentials_storage::CredentialsStorage;
+        use crate::web::frontend::frontend_controller::{
+            memberships_diff, memberships_diff_unauthenticated,
+        };
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use rocket::http::{Cookie, Status};
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::runtime::Runtime;
+        use rocket_dyn_templates::Template;
+
+        #[test]
+        fn success() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let credentials =
+                    FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
+                let credentials_storage = CredentialsStorage::default();
+                let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
+                credentials_storage.store(uuid.clone(), credentials);
+
+                let mut connection = pool.get().unwrap();
+                dao::membership::replace_memberships(&mut connection, &[jon_doe()]).unwrap();
+
+                let rocket = rocket::build()
+                    .mount(
+                        "/",
+                        routes![memberships_diff, memberships_diff_unauthenticated],
+                    )
+                    .manage(pool)
+                    .manage(credentials_storage)
+                    .attach(Template::fairing());
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let cookie = Cookie::new(AUTHENTICATION_COOKIE, uuid);
+
+                let request = client.get("/memberships/diff").cookie(cookie.clone());
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+                let body = response.into_string().await.unwrap();
+                assert!(body.contains(jon_doe().membership_number()));
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn success_when_never_updated() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let credentials =
+                    FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
+                let credentials_storage = CredentialsStorage::default();
+                let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
+                credentials_storage.store(uuid.clone(), credentials);
+
+                let rocket = rocket::build()
+                    .mount(
+                        "/",
+                        routes![memberships_diff, memberships_diff_unauthenticated],
+                    )
+                    .manage(pool)
+                    .manage(credentials_storage)
+                    .attach(Template::fairing());
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let cookie = Cookie::new(AUTHENTICATION_COOKIE, uuid);
+
+                let request = client.get("/memberships/diff").cookie(cookie.clone());
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::Ok, response.status());
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+
+        #[test]
+        fn fail_when_unauthenticated() {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
+                let rocket = rocket::build()
+                    .mount(
+                        "/",
+                        routes![memberships_diff, memberships_diff_unauthenticated],
+                    )
+                    .manage(pool)
+                    .attach(Template::fairing());
+
+                let client = Client::tracked(rocket).await.unwrap();
+                let request = client.get("/memberships/diff");
+
+                let response = request.dispatch().await;
+                assert_eq!(Status::SeeOther, response.status());
+                assert_eq!(
+                    "/fileo/login?page=/memberships/diff",
+                    response.headers().get_one("location").unwrap()
+                );
+            }
+
+            with_temp_database(|pool| Runtime::new().unwrap().block_on(test(pool)));
+        }
+    }
+
     mod check_members_from_csv {
+        use crate::database::DatabaseConnection;
         use crate::database::{dao, with_temp_database};
         use crate::fileo::authentication::AUTHENTICATION_COOKIE;
         use crate::fileo::credentials::FileoCredentials;
@@ -271,23 +673,20 @@ mod tests {
         use crate::web::frontend::frontend_controller::{
             check_members_from_csv, check_members_from_csv_unauthenticated,
         };
-        use diesel::SqliteConnection;
         use diesel::r2d2::{ConnectionManager, Pool};
         use rocket::http::{Cookie, Status};
         use rocket::local::asynchronous::Client;
         use rocket::tokio::runtime::Runtime;
         use rocket_dyn_templates::Template;
-        use std::sync::Mutex;
 
         #[test]
         fn success() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let mut credentials_storage = CredentialsStorage::default();
+                let credentials_storage = CredentialsStorage::default();
                 let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
                 credentials_storage.store(uuid.clone(), credentials);
-                let credentials_storage_mutex = Mutex::new(credentials_storage);
 
                 let mut connection = pool.get().unwrap();
                 dao::membership::replace_memberships(&mut connection, &[]).unwrap(); // Updating last update date
@@ -301,7 +700,7 @@ mod tests {
                         ],
                     )
                     .manage(pool)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .attach(Template::fairing());
 
                 let client = Client::tracked(rocket).await.unwrap();
@@ -320,13 +719,12 @@ mod tests {
 
         #[test]
         fn success_when_never_updated() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let credentials =
                     FileoCredentials::new("test_login".to_owned(), "test_password".to_owned());
-                let mut credentials_storage = CredentialsStorage::default();
+                let credentials_storage = CredentialsStorage::default();
                 let uuid = "0ea9a5fb-0f46-4057-902a-2552ed956bde".to_owned();
                 credentials_storage.store(uuid.clone(), credentials);
-                let credentials_storage_mutex = Mutex::new(credentials_storage);
 
                 let rocket = rocket::build()
                     .mount(
@@ -337,7 +735,7 @@ mod tests {
                         ],
                     )
                     .manage(pool)
-                    .manage(credentials_storage_mutex)
+                    .manage(credentials_storage)
                     .attach(Template::fairing());
 
                 let client = Client::tracked(rocket).await.unwrap();
@@ -356,7 +754,7 @@ mod tests {
 
         #[test]
         fn fail_when_unauthenticated() {
-            async fn test(pool: Pool<ConnectionManager<SqliteConnection>>) {
+            async fn test(pool: Pool<ConnectionManager<DatabaseConnection>>) {
                 let rocket = rocket::build()
                     .mount(
                         "/",
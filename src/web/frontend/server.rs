@@ -22,8 +22,14 @@ impl Server for FrontendServer {
                     frontend_controller::fileo_login,
                     frontend_controller::update_memberships,
                     frontend_controller::update_memberships_unauthenticated,
+                    frontend_controller::upload_memberships,
+                    frontend_controller::upload_memberships_unauthenticated,
+                    frontend_controller::memberships_diff,
+                    frontend_controller::memberships_diff_unauthenticated,
                     frontend_controller::list_memberships,
                     frontend_controller::list_memberships_unauthenticated,
+                    frontend_controller::audit_log,
+                    frontend_controller::audit_log_unauthenticated,
                     frontend_controller::look_membership_up,
                     frontend_controller::look_membership_up_unauthenticated,
                     frontend_controller::check_members_from_csv,
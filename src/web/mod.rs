@@ -1,8 +1,9 @@
+use crate::database::DatabaseConnection;
 use crate::web::server::build_server;
-use diesel::SqliteConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
 use rocket::{Build, Rocket};
 
+pub(crate) mod admin;
 mod api;
 pub(crate) mod authentication;
 pub mod credentials_storage;
@@ -10,6 +11,6 @@ pub mod error;
 mod frontend;
 mod server;
 
-pub fn start_servers(pool: Pool<ConnectionManager<SqliteConnection>>) -> Rocket<Build> {
+pub fn start_servers(pool: Pool<ConnectionManager<DatabaseConnection>>) -> Rocket<Build> {
     build_server(pool)
 }
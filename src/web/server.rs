@@ -1,4 +1,4 @@
-use diesel::SqliteConnection;
+use crate::database::DatabaseConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
 use rocket::{Build, Rocket};
 
@@ -13,14 +13,16 @@ pub trait Server {
     fn configure(&self, rocket_build: Rocket<Build>) -> Rocket<Build>;
 }
 
-pub fn build_server(pool: Pool<ConnectionManager<SqliteConnection>>) -> Rocket<Build> {
+pub fn build_server(pool: Pool<ConnectionManager<DatabaseConnection>>) -> Rocket<Build> {
     let api_port = get_api_port();
     let rocket_build = rocket::build()
         .configure(rocket::Config::figment().merge(("port", api_port)))
-        .manage(pool);
+        .manage(pool.clone());
 
-    let servers: Vec<Box<dyn Server>> =
-        vec![Box::new(ApiServer::new()), Box::new(FrontendServer::new())];
+    let servers: Vec<Box<dyn Server>> = vec![
+        Box::new(ApiServer::new(pool)),
+        Box::new(FrontendServer::new()),
+    ];
     servers.iter().fold(rocket_build, |rocket_build, server| {
         server.configure(rocket_build)
     })
@@ -6,9 +6,10 @@ use crate::utils::{
 };
 use chrono::Utc;
 use dto::checked_member::{CheckResult, CheckedMember};
+use dto::match_explanation::{ComparedField, MatchExplanation, MatchingRule};
 use dto::member_to_check::MemberToCheck;
 use dto::membership::Membership;
-use dto::membership_status::{MemberStatus, compute_member_status};
+use dto::membership_status::{EventPeriod, MemberStatus, compute_member_status};
 use wasm_bindgen::JsCast;
 use web_sys::{Document, Element, HtmlAnchorElement, HtmlInputElement};
 
@@ -35,6 +36,10 @@ pub fn create_card_for_member_to_check(
     } else if let Some(identity) = member_to_check.identity() {
         query_selector_single_element(&element, ".identity")?.set_inner_html(identity.as_str());
     }
+    if let Some(birthdate) = member_to_check.birthdate() {
+        query_selector_single_element(&element, ".birthdate")?
+            .set_inner_html(&birthdate.format("%d/%m/%Y").to_string());
+    }
     if let Some(club) = member_to_check.club() {
         let club_element = query_selector_single_element(&element, ".club")?;
         club_element.set_inner_html(&club);
@@ -81,72 +86,161 @@ pub fn create_card_for_checked_member(
     let member_card = create_card_for_member_to_check(document, checked_member.member_to_check())?;
     append_child(&checked_member_card_template, &member_card)?;
 
-    let membership_card = create_membership_card(document, checked_member.membership(), &status)?;
+    let membership_card = create_membership_card(document, checked_member, &status)?;
     append_child(&checked_member_card_template, &membership_card)?;
 
+    if let (Some(membership), Some(email_address)) = (
+        checked_member.membership().membership(),
+        checked_member.member_to_check().email(),
+    ) {
+        if membership.has_email_address(&email_address) {
+            add_class(&member_card, "matching-email-address");
+            add_class(&membership_card, "matching-email-address");
+        }
+    }
+
+    if let Some(explanation) = checked_member.explanation() {
+        let explanation_card = create_explanation_card(document, explanation)?;
+        append_child(&checked_member_card_template, &explanation_card)?;
+    }
+
     Ok(checked_member_card_template)
 }
 
 fn create_membership_card(
     document: &Document,
-    check_result: &CheckResult,
+    checked_member: &CheckedMember<impl MemberToCheck>,
     status: &MemberStatus,
 ) -> Result<Element> {
     let card = get_membership_template(document, status)?;
+    let check_result = checked_member.membership();
+    let event_period = checked_member.event_period().as_ref();
+    let event_memberships = checked_member.event_memberships();
 
-    match &check_result {
+    match check_result {
         CheckResult::Match(membership) | CheckResult::PartialMatch(membership) => {
-            if *status == MemberStatus::UpToDate || *status == MemberStatus::Expired {
-                query_selector_single_element(&card, ".membership-num")?
-                    .set_inner_html(membership.membership_number());
-                query_selector_single_element(&card, ".membership-name")?
-                    .set_inner_html(membership.name());
-                query_selector_single_element(&card, ".membership-first-name")?
-                    .set_inner_html(membership.first_name());
-                query_selector_single_element(&card, ".membership-start-date")?
-                    .set_inner_html(&membership.start_date().format("%d/%m/%Y").to_string());
-                query_selector_single_element(&card, ".membership-end-date")?
-                    .set_inner_html(&membership.end_date().format("%d/%m/%Y").to_string());
-                let email_address_container =
-                    query_selector_single_element(&card, "a.membership-email-address")?
-                        .dyn_into::<HtmlAnchorElement>()?;
-                email_address_container.set_inner_html(membership.email_address());
-                email_address_container
-                    .set_href(&format!("mailto:{}", &membership.email_address()));
-            }
+            fill_membership_card(&card, membership)?;
+            add_event_coverage(document, &card, event_memberships)?;
 
             if matches!(check_result, CheckResult::PartialMatch(_)) {
                 add_class(&card, "membership-partial-match");
             }
         }
+        CheckResult::FuzzyMatch(membership, confidence) => {
+            query_selector_single_element(&card, ".membership-confidence")?
+                .set_inner_html(&confidence.to_string());
+
+            let proposal_card = create_proposal_card(
+                document,
+                membership,
+                &checked_member.compute_membership_status(),
+            )?;
+            add_event_coverage(document, &proposal_card, event_memberships)?;
+            append_child(
+                &query_selector_single_element(&card, ".membership-proposal")?,
+                &proposal_card,
+            )?;
+        }
+        CheckResult::Ambiguous(candidates) => {
+            let candidates_container =
+                query_selector_single_element(&card, ".membership-candidates")?;
+            for candidate in candidates {
+                let candidate_card = create_candidate_card(document, candidate, event_period)?;
+                append_child(&candidates_container, &candidate_card)?;
+            }
+        }
         CheckResult::NoMatch => {}
     }
     Ok(card)
 }
 
-pub fn create_known_membership_card(
+/// A candidate of an ambiguous result, which the checker can pick.
+fn create_candidate_card(
     document: &Document,
     membership: &Membership,
+    event_period: Option<&EventPeriod>,
 ) -> Result<Element> {
-    let status = compute_member_status(Some(membership));
-
+    let status = compute_member_status(Some(membership), event_period);
     let card = get_membership_template(document, &status)?;
+    fill_membership_card(&card, membership)?;
+    // The club helps telling namesakes apart
+    query_selector_single_element(&card, ".membership-club")?.set_inner_html(membership.club());
+    add_class(&card, "membership-candidate");
+
+    let button = create_element(document, "button")?;
+    set_attribute(&button, "type", "button")?;
+    set_attribute(&button, "onclick", "app.pick_candidate(this)")?;
+    button.set_inner_html("Choisir cette adhésion");
+    append_child(&card, &button)?;
+
+    Ok(card)
+}
+
+/// The membership of a fuzzy match, which the checker has to confirm.
+fn create_proposal_card(
+    document: &Document,
+    membership: &Membership,
+    status: &MemberStatus,
+) -> Result<Element> {
+    let card = get_membership_template(document, status)?;
+    fill_membership_card(&card, membership)?;
+    add_class(&card, "membership-fuzzy-match-proposal");
+
+    let button = create_element(document, "button")?;
+    set_attribute(&button, "type", "button")?;
+    set_attribute(&button, "onclick", "app.accept_fuzzy_match(this)")?;
+    button.set_inner_html("Confirmer cette adhésion");
+    append_child(&card, &button)?;
+
+    Ok(card)
+}
 
-    query_selector_single_element(&card, ".membership-num")?
+fn add_event_coverage(
+    document: &Document,
+    card: &Element,
+    event_memberships: &[Membership],
+) -> Result<()> {
+    if let [first_membership, .., last_membership] = event_memberships {
+        let event_coverage = create_element(document, "p")?;
+        add_class(&event_coverage, "membership-event-coverage");
+        event_coverage.set_inner_html(&format!(
+            "L'événement est couvert par des adhésions successives, du {} au {}.",
+            first_membership.start_date().format("%d/%m/%Y"),
+            last_membership.end_date().format("%d/%m/%Y")
+        ));
+        append_child(card, &event_coverage)?;
+    }
+    Ok(())
+}
+
+fn fill_membership_card(card: &Element, membership: &Membership) -> Result<()> {
+    query_selector_single_element(card, ".membership-num")?
         .set_inner_html(membership.membership_number());
-    query_selector_single_element(&card, ".membership-name")?.set_inner_html(membership.name());
-    query_selector_single_element(&card, ".membership-first-name")?
+    query_selector_single_element(card, ".membership-name")?.set_inner_html(membership.name());
+    query_selector_single_element(card, ".membership-first-name")?
         .set_inner_html(membership.first_name());
-    query_selector_single_element(&card, ".membership-start-date")?
+    query_selector_single_element(card, ".membership-start-date")?
         .set_inner_html(&membership.start_date().format("%d/%m/%Y").to_string());
-    query_selector_single_element(&card, ".membership-end-date")?
+    query_selector_single_element(card, ".membership-end-date")?
         .set_inner_html(&membership.end_date().format("%d/%m/%Y").to_string());
-    query_selector_single_element(&card, ".membership-club")?.set_inner_html(membership.club());
+    set_membership_type_and_tariff(card, membership)?;
     let email_address_container =
-        query_selector_single_element(&card, "a.membership-email-address")?
+        query_selector_single_element(card, "a.membership-email-address")?
             .dyn_into::<HtmlAnchorElement>()?;
     email_address_container.set_inner_html(membership.email_address());
     email_address_container.set_href(&format!("mailto:{}", &membership.email_address()));
+    Ok(())
+}
+
+pub fn create_known_membership_card(
+    document: &Document,
+    membership: &Membership,
+) -> Result<Element> {
+    let status = compute_member_status(Some(membership), None);
+
+    let card = get_membership_template(document, &status)?;
+    fill_membership_card(&card, membership)?;
+    query_selector_single_element(&card, ".membership-club")?.set_inner_html(membership.club());
 
     let today = Utc::now().date_naive();
     if membership.start_date() <= &today && &today <= membership.end_date() {
@@ -156,6 +250,64 @@ pub fn create_known_membership_card(
     Ok(card)
 }
 
+/// Show the checker which rule matched, and which fields agreed or not.
+fn create_explanation_card(document: &Document, explanation: &MatchExplanation) -> Result<Element> {
+    let card = get_template(document, "match-explanation")?;
+    query_selector_single_element(&card, ".matching-rule")?
+        .set_inner_html(describe_rule(explanation.rule()));
+    query_selector_single_element(&card, ".agreeing-fields")?
+        .set_inner_html(&describe_fields(explanation.agreeing_fields()));
+    query_selector_single_element(&card, ".disagreeing-fields")?
+        .set_inner_html(&describe_fields(explanation.disagreeing_fields()));
+    Ok(card)
+}
+
+fn describe_rule(rule: &MatchingRule) -> &'static str {
+    match rule {
+        MatchingRule::MembershipNumberAndNames => "numéro d'adhésion, nom et prénom",
+        MatchingRule::MembershipNumberAndIdentity => "numéro d'adhésion et identité",
+        MatchingRule::MembershipNumber => "numéro d'adhésion seul",
+        MatchingRule::UnknownMembershipNumber => "numéro d'adhésion inconnu",
+        MatchingRule::Names => "nom et prénom",
+        MatchingRule::Identity => "identité",
+        MatchingRule::EmailAddress => "adresse mail",
+        MatchingRule::SimilarNames => "nom et prénom approchants",
+        MatchingRule::NoMatchingMembership => "aucune",
+    }
+}
+
+fn describe_fields(fields: &[ComparedField]) -> String {
+    if fields.is_empty() {
+        return "aucune".to_owned();
+    }
+
+    fields
+        .iter()
+        .map(|field| match field {
+            ComparedField::MembershipNumber => "numéro d'adhésion",
+            ComparedField::LastName => "nom",
+            ComparedField::FirstName => "prénom",
+            ComparedField::Identity => "identité",
+            ComparedField::Birthdate => "date de naissance",
+            ComparedField::EmailAddress => "adresse mail",
+            ComparedField::MembershipType => "type d'adhésion",
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn set_membership_type_and_tariff(card: &Element, membership: &Membership) -> Result<()> {
+    query_selector_single_element(card, ".membership-type")?.set_inner_html(
+        membership
+            .membership_type()
+            .as_deref()
+            .unwrap_or("Non renseigné"),
+    );
+    query_selector_single_element(card, ".membership-tariff")?
+        .set_inner_html(membership.tariff().as_deref().unwrap_or("Non renseigné"));
+    Ok(())
+}
+
 fn get_member_to_check_template(document: &Document) -> Result<Element> {
     get_template(document, "member-to-check-template")
 }
@@ -168,6 +320,9 @@ fn get_membership_template(document: &Document, member_status: &MemberStatus) ->
     match member_status {
         MemberStatus::UpToDate => get_template(document, "membership-up-to-date"),
         MemberStatus::Expired => get_template(document, "membership-expired"),
+        MemberStatus::NotCoveringEvent => get_template(document, "membership-not-covering-event"),
+        MemberStatus::FuzzyMatch => get_template(document, "membership-fuzzy-match"),
+        MemberStatus::Ambiguous => get_template(document, "membership-ambiguous"),
         MemberStatus::Unknown => get_template(document, "membership-unknown"),
     }
 }
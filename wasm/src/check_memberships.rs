@@ -1,16 +1,19 @@
 use crate::Result;
 use crate::component::alert::{AlertLevel, create_alert, unwrap_or_alert};
 use crate::component::stepper::next_step;
-use crate::error::{DEFAULT_SERVER_ERROR_MESSAGE, Error};
+use crate::error::{DEFAULT_ERROR_MESSAGE, DEFAULT_SERVER_ERROR_MESSAGE, Error};
 use crate::json;
 use crate::user_interface::{get_email_body, get_email_subject, set_loading, with_loading};
-use crate::utils::{get_document, get_element_by_id_dyn, query_selector_single_element};
+use crate::utils::{
+    get_document, get_element_by_id_dyn, query_selector_single_element, remove_class,
+};
 use crate::web::fetch;
 use dto::email::Email;
 use std::collections::BTreeSet;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::wasm_bindgen;
-use web_sys::{Document, HtmlButtonElement, HtmlInputElement, HtmlTextAreaElement};
+use web_sys::js_sys::encode_uri_component;
+use web_sys::{Document, Element, HtmlButtonElement, HtmlInputElement, HtmlTextAreaElement};
 
 // region Handle steps
 #[wasm_bindgen]
@@ -39,6 +42,99 @@ pub fn go_to_notification_step(document: &Document) {
 
     next_step(document);
 }
+
+/// Keep the candidate of an ambiguous result picked by the user,
+/// as if it had been matched in the first place.
+#[wasm_bindgen]
+pub fn pick_candidate(button: &Element) {
+    unwrap_or_alert(pick(button));
+}
+
+fn pick(button: &Element) -> Result<()> {
+    let candidate = find_closest(button, ".membership-candidate")?;
+    let ambiguous_card = find_closest(&candidate, ".membership-ambiguous")?;
+
+    button.remove();
+    remove_class(&candidate, "membership-candidate");
+    ambiguous_card.replace_with_with_node_1(&candidate)?;
+
+    toggle_go_to_email_step_button(&get_document()?);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn accept_fuzzy_match(button: &Element) {
+    unwrap_or_alert(accept(button));
+}
+
+fn accept(button: &Element) -> Result<()> {
+    let proposal = find_closest(button, ".membership-fuzzy-match-proposal")?;
+    let fuzzy_match_card = find_closest(&proposal, ".membership-fuzzy-match")?;
+
+    button.remove();
+    remove_class(&proposal, "membership-fuzzy-match-proposal");
+    fuzzy_match_card.replace_with_with_node_1(&proposal)?;
+
+    toggle_go_to_email_step_button(&get_document()?);
+    Ok(())
+}
+
+fn find_closest(element: &Element, selector: &str) -> Result<Element> {
+    element.closest(selector)?.ok_or_else(|| {
+        Error::new(
+            DEFAULT_ERROR_MESSAGE,
+            &format!("No element found [selector: {selector}]"),
+        )
+    })
+}
+// endregion
+
+// region Handle check
+/// Add the membership type required by the user and the dates of the event, if any,
+/// to the URL of the check endpoint.
+pub fn with_check_parameters(document: &Document, url: &str) -> Result<String> {
+    let mut parameters = vec![];
+
+    let membership_type = get_input_value(document, "required-membership-type")?;
+    if !membership_type.is_empty() {
+        let membership_type = String::from(encode_uri_component(&membership_type));
+        parameters.push(format!("required_membership_type={membership_type}"));
+    }
+
+    // Date inputs provide dates as YYYY-MM-DD, which can be compared as is
+    let event_start_date = get_input_value(document, "event-start-date")?;
+    let event_end_date = get_input_value(document, "event-end-date")?;
+    match (event_start_date.is_empty(), event_end_date.is_empty()) {
+        (true, true) => {}
+        (false, false) if event_start_date <= event_end_date => {
+            parameters.push(format!("event_start_date={event_start_date}"));
+            parameters.push(format!("event_end_date={event_end_date}"));
+        }
+        (false, false) => {
+            return Err(Error::new(
+                "La fin de l'événement ne peut pas précéder son début.",
+                "Event ending before it starts.",
+            ));
+        }
+        _ => {
+            return Err(Error::new(
+                "Veuillez renseigner à la fois le début et la fin de l'événement.",
+                "Incomplete event dates.",
+            ));
+        }
+    }
+
+    if parameters.is_empty() {
+        Ok(url.to_owned())
+    } else {
+        Ok(format!("{url}?{}", parameters.join("&")))
+    }
+}
+
+fn get_input_value(document: &Document, id: &str) -> Result<String> {
+    let input = get_element_by_id_dyn::<HtmlInputElement>(document, id)?;
+    Ok(input.value().trim().to_owned())
+}
 // endregion
 
 // region Handle email sending
@@ -89,7 +185,14 @@ fn get_email_addresses_to_notify(document: &Document) -> Result<BTreeSet<String>
     let mut email_addresses_to_notify = BTreeSet::new();
     for index in 0..memberships.length() {
         let membership = memberships.get_with_index(index).unwrap();
-        if membership.class_name().contains("membership-unknown") {
+        // Candidates which haven't been picked belong to other people,
+        // and fuzzy matches which haven't been confirmed may too
+        let class_name = membership.class_name();
+        if class_name.contains("membership-unknown")
+            || class_name.contains("membership-ambiguous")
+            || class_name.contains("membership-candidate")
+            || class_name.contains("membership-fuzzy-match")
+        {
             continue;
         }
 
@@ -1,4 +1,4 @@
-use crate::check_memberships::toggle_go_to_email_step_button;
+use crate::check_memberships::{toggle_go_to_email_step_button, with_check_parameters};
 use crate::component::stepper::next_step;
 use crate::error::{DEFAULT_SERVER_ERROR_MESSAGE, Error};
 use crate::fileo::load_members_from_csv;
@@ -36,10 +36,10 @@ pub async fn handle_form_submission() {
             ));
         }
 
-        let url = "/api/members/csv/check";
+        let url = with_check_parameters(document, "/api/members/csv/check")?;
         let body = json::to_string(&members_to_check);
         let response = fetch(
-            url,
+            &url,
             "post",
             Some("application/json"),
             Some(&body),
@@ -1,5 +1,6 @@
 use crate::error::Error;
 use crate::utils::get_element_by_id_dyn;
+use chrono::NaiveDate;
 use csv::{Reader, StringRecord};
 use dto::csv_member::CsvMember;
 use log::warn;
@@ -11,9 +12,13 @@ enum MembersToCheckFileFormat {
     MembershipNumberIdentity,
     MembershipNumber,
     LastNameFirstName,
+    LastNameFirstNameBirthdate,
     Identity,
 }
 
+/// Birthdates may be written the French way or the ISO way.
+const BIRTHDATE_FORMATS: [&str; 2] = ["%d/%m/%Y", "%Y-%m-%d"];
+
 impl TryFrom<String> for MembersToCheckFileFormat {
     type Error = Error;
 
@@ -25,6 +30,9 @@ impl TryFrom<String> for MembersToCheckFileFormat {
             "MembershipNumberIdentity" => Ok(MembersToCheckFileFormat::MembershipNumberIdentity),
             "MembershipNumber" => Ok(MembersToCheckFileFormat::MembershipNumber),
             "LastNameFirstName" => Ok(MembersToCheckFileFormat::LastNameFirstName),
+            "LastNameFirstNameBirthdate" => {
+                Ok(MembersToCheckFileFormat::LastNameFirstNameBirthdate)
+            }
             "Identity" => Ok(MembersToCheckFileFormat::Identity),
             _ => Err(Error::new("Format inexistant", "Format doesn't exist")),
         }
@@ -116,6 +124,7 @@ fn deserialize_member_to_check(
                         None,
                         Some(record.get(1).unwrap().to_owned()),
                         Some(record.get(2).unwrap().to_owned()),
+                        None,
                     ))
                 }
             }
@@ -128,6 +137,7 @@ fn deserialize_member_to_check(
                         Some(record.get(1).unwrap().to_owned()),
                         None,
                         None,
+                        None,
                     ))
                 }
             }
@@ -140,6 +150,7 @@ fn deserialize_member_to_check(
                         None,
                         None,
                         None,
+                        None,
                     ))
                 }
             }
@@ -152,6 +163,21 @@ fn deserialize_member_to_check(
                         None,
                         Some(record.get(0).unwrap().to_owned()),
                         Some(record.get(1).unwrap().to_owned()),
+                        None,
+                    ))
+                }
+            }
+            MembersToCheckFileFormat::LastNameFirstNameBirthdate => {
+                let birthdate = record.get(2).and_then(parse_birthdate);
+                if fields_count != 3 || birthdate.is_none() {
+                    Err(Some(record.iter().collect::<Vec<_>>().join(";")))
+                } else {
+                    Ok(CsvMember::new(
+                        None,
+                        None,
+                        Some(record.get(0).unwrap().to_owned()),
+                        Some(record.get(1).unwrap().to_owned()),
+                        birthdate,
                     ))
                 }
             }
@@ -164,6 +190,7 @@ fn deserialize_member_to_check(
                         Some(record.get(0).unwrap().to_owned()),
                         None,
                         None,
+                        None,
                     ))
                 }
             }
@@ -179,6 +206,12 @@ fn deserialize_member_to_check(
     }
 }
 
+fn parse_birthdate(birthdate: &str) -> Option<NaiveDate> {
+    BIRTHDATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(birthdate.trim(), format).ok())
+}
+
 #[cfg(test)]
 mod tests {
     mod load_members_to_check_from_csv_string {
@@ -205,6 +238,7 @@ mod tests {
                             None,
                             Some(name),
                             Some(first_name),
+                            None
                         )]),
                         vec![]
                     ),
@@ -255,6 +289,7 @@ mod tests {
                             Some(identity),
                             None,
                             None,
+                            None
                         )]),
                         vec![]
                     ),
@@ -301,6 +336,7 @@ mod tests {
                             None,
                             None,
                             None,
+                            None
                         )]),
                         vec![]
                     ),
@@ -351,6 +387,7 @@ mod tests {
                             None,
                             Some(name),
                             Some(first_name),
+                            None
                         )]),
                         vec![]
                     ),
@@ -379,6 +416,54 @@ mod tests {
             }
         }
 
+        mod last_name_first_name_birthdate {
+            use crate::fileo::load_members_from_csv::{
+                MembersToCheckFileFormat, load_members_to_check_from_csv_string,
+            };
+            use MembersToCheckFileFormat::LastNameFirstNameBirthdate;
+            use chrono::NaiveDate;
+            use dto::csv_member::CsvMember;
+            use std::collections::BTreeSet;
+
+            #[test]
+            fn success() {
+                let csv = "Doe;John;05/11/1990\nDoe;John;1980-02-01";
+                let result =
+                    load_members_to_check_from_csv_string(csv, &LastNameFirstNameBirthdate);
+                assert_eq!(
+                    (
+                        BTreeSet::from_iter(vec![
+                            CsvMember::new(
+                                None,
+                                None,
+                                Some("Doe".to_owned()),
+                                Some("John".to_owned()),
+                                NaiveDate::from_ymd_opt(1990, 11, 5)
+                            ),
+                            CsvMember::new(
+                                None,
+                                None,
+                                Some("Doe".to_owned()),
+                                Some("John".to_owned()),
+                                NaiveDate::from_ymd_opt(1980, 2, 1)
+                            )
+                        ]),
+                        vec![]
+                    ),
+                    result
+                )
+            }
+
+            #[test]
+            fn fail_when_invalid_birthdate() {
+                let csv = "Doe;John;31/02/1990";
+                let result =
+                    load_members_to_check_from_csv_string(csv, &LastNameFirstNameBirthdate);
+                let expected_result = (BTreeSet::new(), vec![csv.to_owned()]);
+                assert_eq!(expected_result, result)
+            }
+        }
+
         mod identity {
             use crate::fileo::load_members_from_csv::{
                 MembersToCheckFileFormat, load_members_to_check_from_csv_string,
@@ -394,9 +479,13 @@ mod tests {
                 let result = load_members_to_check_from_csv_string(&csv, &Identity);
                 assert_eq!(
                     (
-                        BTreeSet::from_iter(vec![
-                            CsvMember::new(None, Some(identity), None, None,)
-                        ]),
+                        BTreeSet::from_iter(vec![CsvMember::new(
+                            None,
+                            Some(identity),
+                            None,
+                            None,
+                            None
+                        )]),
                         vec![]
                     ),
                     result
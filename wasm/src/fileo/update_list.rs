@@ -1,49 +1,163 @@
+use crate::Result;
 use crate::component::alert::{AlertLevel, create_alert};
 use crate::error::Error;
+use crate::json;
 use crate::user_interface::with_loading;
-use crate::utils::{get_document, get_element_by_id};
-use crate::web::fetch;
+use crate::utils::{get_document, get_element_by_id, get_element_by_id_dyn, get_window};
+use crate::web::{Response, fetch, fetch_with_raw_body};
+use dto::import_rejection::ImportRejection;
+use dto::import_report::ImportReport;
 use wasm_bindgen::prelude::wasm_bindgen;
 use web_sys::js_sys::Date;
+use web_sys::{File, HtmlInputElement};
 
 /// Validate the field, then try to log into Fileo app.
 /// If it succeeds, then redirect to the verification page.
+/// If the server refuses the import because too many memberships would disappear,
+/// then ask the user whether the import should be forced.
 #[wasm_bindgen]
 pub async fn update_fileo_list() {
     with_loading(async || {
-        let url = "/api/fileo/memberships";
-        let response = fetch(url, "get", None, None).await.map_err(|error| {
+        let mut response = fetch_memberships(false).await?;
+        if response.status() == 409 {
+            if !confirm_forced_import(&response)? {
+                return Ok(());
+            }
+
+            response = fetch_memberships(true).await?;
+        }
+
+        handle_import_response(&response)
+    })
+    .await;
+}
+
+/// Send the Fileo export selected by the user, so that it replaces the current memberships.
+/// Useful when Fileo can't be reached by the server.
+#[wasm_bindgen]
+pub async fn upload_fileo_list() {
+    with_loading(async || {
+        let document = get_document()?;
+        let file_picker =
+            get_element_by_id_dyn::<HtmlInputElement>(&document, "memberships-file-picker")?;
+        let file = file_picker
+            .files()
+            .and_then(|files| files.get(0))
+            .ok_or_else(|| {
+                Error::new(
+                    "Veuillez sélectionner un fichier exporté depuis Fileo.",
+                    "No file selected",
+                )
+            })?;
+
+        let mut response = upload_memberships(&file, false).await?;
+        if response.status() == 409 {
+            if !confirm_forced_import(&response)? {
+                return Ok(());
+            }
+
+            response = upload_memberships(&file, true).await?;
+        }
+
+        handle_import_response(&response)
+    })
+    .await;
+}
+
+async fn fetch_memberships(force: bool) -> Result<Response> {
+    let url = if force {
+        "/api/fileo/memberships?force=true"
+    } else {
+        "/api/fileo/memberships"
+    };
+    fetch(url, "get", None, None).await.map_err(|error| {
+        Error::from_parent(
+            "Le serveur a rencontré une erreur lors du traitement. Veuillez réessayer.",
+            error,
+        )
+    })
+}
+
+async fn upload_memberships(file: &File, force: bool) -> Result<Response> {
+    let url = if force {
+        "/api/fileo/memberships/upload?force=true"
+    } else {
+        "/api/fileo/memberships/upload"
+    };
+    fetch_with_raw_body(url, "post", Some("text/csv"), Some(file))
+        .await
+        .map_err(|error| {
             Error::from_parent(
                 "Le serveur a rencontré une erreur lors du traitement. Veuillez réessayer.",
                 error,
             )
-        })?;
-        let status = response.status();
-        if (200..400).contains(&status) {
-            create_alert(
-                "Mise à jour effectuée. Vous pouvez désormais vérifier les licences.",
-                AlertLevel::Info,
-            );
-            let document = get_document()?;
-            let last_update_field = get_element_by_id(&document, "last-update")?;
-            let now = Date::new_0();
-            let day = now.get_date();
-            let month = now.get_month() + 1;
-            let year = now.get_full_year();
-            last_update_field.set_text_content(Some(&format!("{:02}/{:02}/{}", day, month, year)));
-
-            Ok(())
-        } else if status == 401 {
-            Err(Error::new(
-                "Vos identifiants sont incorrects. Veuillez réessayer.",
-                "Wrong credentials",
-            ))
-        } else {
-            Err(Error::new(
-                "Impossible de mettre à jour la liste. Veuillez réessayer.",
-                &format!("Server error: {}", status),
-            ))
+        })
+}
+
+/// Ask the user whether an import refused by the server should be forced.
+fn confirm_forced_import(response: &Response) -> Result<bool> {
+    let rejection: ImportRejection = json::from_str(response.body().as_deref().unwrap_or_default());
+    let message = format!(
+        "La nouvelle liste contient {} licences, contre {} actuellement. Plus de {} % des licences disparaîtraient. Voulez-vous tout de même la mettre à jour ?",
+        rejection.new_count(),
+        rejection.previous_count(),
+        rejection.max_drop_percentage()
+    );
+    let confirmed = get_window()?.confirm_with_message(&message)?;
+    if !confirmed {
+        create_alert("Mise à jour annulée.", AlertLevel::Info);
+    }
+
+    Ok(confirmed)
+}
+
+fn handle_import_response(response: &Response) -> Result<()> {
+    let status = response.status();
+    if (200..400).contains(&status) {
+        create_alert(
+            "Mise à jour effectuée. Vous pouvez désormais vérifier les licences.",
+            AlertLevel::Info,
+        );
+        if let Some(body) = response.body() {
+            let report: ImportReport = json::from_str(body);
+            let rejected_rows_count = report.rejected_rows().len();
+            if rejected_rows_count > 0 {
+                create_alert(
+                    &format!(
+                        "{rejected_rows_count} ligne(s) n'ont pas pu être lues. Rechargez la page de mise à jour pour les consulter."
+                    ),
+                    AlertLevel::Error,
+                );
+            }
         }
-    })
-    .await;
+        let document = get_document()?;
+        let last_update_field = get_element_by_id(&document, "last-update")?;
+        let now = Date::new_0();
+        let day = now.get_date();
+        let month = now.get_month() + 1;
+        let year = now.get_full_year();
+        last_update_field.set_text_content(Some(&format!("{:02}/{:02}/{}", day, month, year)));
+
+        Ok(())
+    } else if status == 401 {
+        Err(Error::new(
+            "Vos identifiants sont incorrects. Veuillez réessayer.",
+            "Wrong credentials",
+        ))
+    } else if status == 403 {
+        Err(Error::new(
+            "Seuls les administrateurs peuvent forcer la mise à jour.",
+            "Forbidden forced import",
+        ))
+    } else if status == 413 {
+        Err(Error::new(
+            "Le fichier est trop volumineux.",
+            "Payload too large",
+        ))
+    } else {
+        Err(Error::new(
+            "Impossible de mettre à jour la liste. Veuillez réessayer.",
+            &format!("Server error: {}", status),
+        ))
+    }
 }
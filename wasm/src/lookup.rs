@@ -8,14 +8,16 @@ use crate::error::{DEFAULT_ERROR_MESSAGE, Error};
 use crate::json;
 use crate::user_interface::with_loading;
 use crate::utils::{
-    add_class, append_child, clear_element, get_element_by_id, get_element_by_id_dyn,
-    get_value_from_element, remove_class,
+    ElementBuilder, add_class, append_child, clear_element, get_element_by_id,
+    get_element_by_id_dyn, get_value_from_element, remove_class,
 };
 use crate::web::fetch;
-use dto::member_to_look_up::MemberToLookUp;
+use chrono::NaiveDate;
+use dto::member_to_look_up::{LookUpMode, MemberToLookUp};
 use dto::membership::Membership;
+use dto::membership_candidate::MembershipCandidate;
 use wasm_bindgen::prelude::wasm_bindgen;
-use web_sys::Document;
+use web_sys::{Document, HtmlSelectElement};
 
 pub fn init_lookup_page(document: &Document) {
     if let Some(stepper) = document
@@ -37,9 +39,30 @@ pub async fn lookup(document: &Document) {
             unwrap_or_alert(get_element_by_id_dyn(document, "membership-num-input"));
         let last_name_input = unwrap_or_alert(get_element_by_id_dyn(document, "last-name-input"));
         let first_name_input = unwrap_or_alert(get_element_by_id_dyn(document, "first-name-input"));
+        let valid_on_input = unwrap_or_alert(get_element_by_id_dyn(document, "valid-on-input"));
         let membership_num = get_value_from_element(&membership_num_input);
         let last_name = get_value_from_element(&last_name_input);
         let first_name = get_value_from_element(&first_name_input);
+        let valid_on = get_value_from_element(&valid_on_input);
+        let mode_selector =
+            get_element_by_id_dyn::<HtmlSelectElement>(document, "lookup-mode-selector")?;
+        let mode = match mode_selector.value().as_str() {
+            "Prefix" => LookUpMode::Prefix,
+            "Fuzzy" => LookUpMode::Fuzzy,
+            _ => LookUpMode::Exact,
+        };
+        let valid_on = if valid_on.is_empty() {
+            None
+        } else {
+            Some(
+                NaiveDate::parse_from_str(&valid_on, "%Y-%m-%d").map_err(|error| {
+                    Error::new(
+                        "La date saisie n'est pas valide.",
+                        &format!("Can't parse date to look memberships up [error: {error}]"),
+                    )
+                })?,
+            )
+        };
 
         let member_to_look_up = MemberToLookUp::new(
             if membership_num.is_empty() {
@@ -57,6 +80,8 @@ pub async fn lookup(document: &Document) {
             } else {
                 Some(first_name)
             },
+            valid_on,
+            mode,
         );
 
         let response = fetch(
@@ -79,12 +104,17 @@ pub async fn lookup(document: &Document) {
                 .body()
                 .clone()
                 .ok_or_else(|| Error::new(DEFAULT_ERROR_MESSAGE, "No body"))?;
-            let memberships = json::from_str::<Vec<Membership>>(&body);
-            display_memberships(document, &memberships)?;
+            if mode == LookUpMode::Exact {
+                let memberships = json::from_str::<Vec<Membership>>(&body);
+                display_memberships(document, &memberships)?;
+            } else {
+                let candidates = json::from_str::<Vec<MembershipCandidate>>(&body);
+                display_candidates(document, &candidates)?;
+            }
         } else if status == 400 {
             Err(Error::new(
-                "Au moins un critère est nécessaire pour rechercher une adhésion.",
-                "Missing criteria for looking memberships up.",
+                "Au moins un critère est nécessaire pour rechercher une adhésion. La date de validité n'est possible qu'avec une recherche exacte.",
+                "Invalid criteria for looking memberships up.",
             ))?;
         } else if status == 401 {
             Err(Error::new(
@@ -122,3 +152,26 @@ fn display_memberships(document: &Document, memberships: &[Membership]) -> Resul
 
     Ok(())
 }
+
+/// Display the candidates in the order they were ranked, along with their relevance.
+fn display_candidates(document: &Document, candidates: &[MembershipCandidate]) -> Result<()> {
+    let memberships_container = get_element_by_id(document, "memberships")?;
+    if candidates.is_empty() {
+        add_class(&memberships_container, "no-membership-found");
+    } else {
+        clear_element(&memberships_container);
+        remove_class(&memberships_container, "no-membership-found");
+        for candidate in candidates {
+            let card = create_known_membership_card(document, candidate.membership())?;
+            let score = format!("Pertinence : {:.0} %", candidate.score() * 100.0);
+            let score_element = ElementBuilder::default()
+                .parent(&card)
+                .inner_html(&score)
+                .build(document, "div")?;
+            add_class(&score_element, "membership-score");
+            append_child(&memberships_container, &card)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::check_memberships::toggle_go_to_email_step_button;
+use crate::check_memberships::{toggle_go_to_email_step_button, with_check_parameters};
 use crate::component::stepper::next_step;
 use crate::error::{DEFAULT_ERROR_MESSAGE, Error};
 use crate::user_interface::{handle_checked_members, with_loading};
@@ -32,8 +32,9 @@ async fn check(document: &Document) -> Result<Vec<CheckedMember<UdaMember>>> {
                 &format!("No members to check [id: {element_id}]."),
             )
         })?;
+    let url = with_check_parameters(document, "/api/members/uda/check")?;
     let response = fetch(
-        "/api/members/uda/check",
+        &url,
         "post",
         Some("application/json"),
         Some(members.as_str()),
@@ -12,7 +12,8 @@ pub async fn confirm_members() {
     with_loading(async || {
         let body = get_body()?;
         let id_inputs =
-            query_selector_all(&body, ".checked-member:has(.membership-up-to-date) .uda-id")?;
+            // Candidates of ambiguous results are nested, so they are only confirmed once picked
+            query_selector_all(&body, ".checked-member:has(> .membership-up-to-date) .uda-id")?;
 
         let mut ids = vec![];
         for id_input in id_inputs {
@@ -87,6 +87,9 @@ pub fn handle_checked_members(
 
     let mut up_to_date_member_cards = vec![];
     let mut expired_member_cards = vec![];
+    let mut not_covering_event_member_cards = vec![];
+    let mut fuzzy_match_member_cards = vec![];
+    let mut ambiguous_member_cards = vec![];
     let mut unknown_member_cards = vec![];
 
     for checked_member in checked_members {
@@ -94,6 +97,9 @@ pub fn handle_checked_members(
         match checked_member.compute_member_status() {
             MemberStatus::UpToDate => up_to_date_member_cards.push(card),
             MemberStatus::Expired => expired_member_cards.push(card),
+            MemberStatus::NotCoveringEvent => not_covering_event_member_cards.push(card),
+            MemberStatus::FuzzyMatch => fuzzy_match_member_cards.push(card),
+            MemberStatus::Ambiguous => ambiguous_member_cards.push(card),
             MemberStatus::Unknown => unknown_member_cards.push(card),
         }
     }
@@ -102,6 +108,9 @@ pub fn handle_checked_members(
         document,
         &up_to_date_member_cards,
         &expired_member_cards,
+        &not_covering_event_member_cards,
+        &fuzzy_match_member_cards,
+        &ambiguous_member_cards,
         &unknown_member_cards,
     )?;
     append_child(&parent, &accordion)?;
@@ -113,6 +122,9 @@ pub fn create_accordion_for_checked_members(
     document: &Document,
     up_to_date_member_cards: &[Element],
     expired_member_cards: &[Element],
+    not_covering_event_member_cards: &[Element],
+    fuzzy_match_member_cards: &[Element],
+    ambiguous_member_cards: &[Element],
     unknown_member_cards: &[Element],
 ) -> Result<HtmlElement> {
     let up_to_date_element = create_accordion_line_for_checked_members(
@@ -127,6 +139,24 @@ pub fn create_accordion_for_checked_members(
         "Membres expirés",
         expired_member_cards,
     )?;
+    let not_covering_event_element = create_accordion_line_for_checked_members(
+        document,
+        "not-covering-event",
+        "Membres dont l'adhésion ne couvre pas l'événement",
+        not_covering_event_member_cards,
+    )?;
+    let fuzzy_match_element = create_accordion_line_for_checked_members(
+        document,
+        "fuzzy-match",
+        "Membres trouvés par approximation, à confirmer",
+        fuzzy_match_member_cards,
+    )?;
+    let ambiguous_element = create_accordion_line_for_checked_members(
+        document,
+        "ambiguous",
+        "Membres ambigus, à départager",
+        ambiguous_member_cards,
+    )?;
     let unknown_element = create_accordion_line_for_checked_members(
         document,
         "unknown",
@@ -134,10 +164,17 @@ pub fn create_accordion_for_checked_members(
         unknown_member_cards,
     )?;
 
-    let elements = [up_to_date_element, expired_element, unknown_element]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+    let elements = [
+        up_to_date_element,
+        expired_element,
+        not_covering_event_element,
+        fuzzy_match_element,
+        ambiguous_element,
+        unknown_element,
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
     create_accordion(document, &elements, true)
 }
 
@@ -27,11 +27,22 @@ pub async fn fetch(
     method: &str,
     content_type: Option<&str>,
     body: Option<&str>,
+) -> Result<Response> {
+    let body = body.map(JsValue::from_str);
+    fetch_with_raw_body(url, method, content_type, body.as_ref()).await
+}
+
+/// Same as [fetch], but the body is sent as is (e.g. a file), without being converted into a string.
+pub async fn fetch_with_raw_body(
+    url: &str,
+    method: &str,
+    content_type: Option<&str>,
+    body: Option<&JsValue>,
 ) -> Result<Response> {
     let window = get_window()?;
     let request_init = RequestInit::new();
     if let Some(body) = body {
-        request_init.set_body(&JsValue::from_str(body));
+        request_init.set_body(body);
     }
     request_init.set_method(method);
     let headers = Headers::new()?;